@@ -0,0 +1,401 @@
+//! This module runs the configured actions when an alert fires or resolves:
+//! a shell command with the alert and the current metric values in environment variables,
+//! a line appended to a log file, or a desktop notification sent with `notify-send`.
+//!
+//! Every action has its own rate limit, so a flapping metric cannot spawn hundreds of commands:
+//! - an alert triggers the action at most once per `cooldown`, later firings are suppressed,
+//! - the action runs at most `max_per_hour` times per hour over all alerts,
+//! - a resolve only runs the action if the firing of the same alert did.
+//!
+//! Actions never block the UI: commands, log lines and notifications run in the background
+//! and failures, including commands exiting with an error, are collected as messages.
+use crate::backend::alerts::{AlertEvent, AlertEventKind, AlertInput, Severity};
+use crate::notify::{self, Notification, Urgency};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Default time an alert has to wait before it triggers an action again.
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(300);
+
+/// Default number of runs of an action per hour.
+pub const DEFAULT_MAX_PER_HOUR: usize = 20;
+
+/// Commands still running when an action is due are not started again beyond this number.
+const MAX_RUNNING_COMMANDS: usize = 4;
+
+/// Maximum number of messages kept.
+const MAX_MESSAGES: usize = 20;
+
+/// What an action does.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ActionKind {
+    /// Runs the command with `sh -c`
+    Command(String),
+    /// Appends one line per event to the file
+    Log(PathBuf),
+    /// Sends a desktop notification
+    Notify,
+}
+
+impl ActionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Command(_) => "command",
+            Self::Log(_) => "log",
+            Self::Notify => "notify",
+        }
+    }
+}
+
+/// A configured action and the events it runs for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertAction {
+    pub kind: ActionKind,
+    /// The event kinds the action runs for
+    pub on: Vec<AlertEventKind>,
+    /// Only alerts of this severity, all if `None`
+    pub severity: Option<Severity>,
+    pub cooldown: Duration,
+    pub max_per_hour: usize,
+}
+
+impl AlertAction {
+    /// Creates an action running for all events, with the default rate limits.
+    pub fn new(kind: ActionKind) -> Self {
+        Self {
+            kind,
+            on: vec![AlertEventKind::Fired, AlertEventKind::Resolved],
+            severity: None,
+            cooldown: DEFAULT_COOLDOWN,
+            max_per_hour: DEFAULT_MAX_PER_HOUR,
+        }
+    }
+
+    fn applies_to(&self, event: &AlertEvent) -> bool {
+        self.on.contains(&event.kind) && self.severity.is_none_or(|s| s == event.severity)
+    }
+}
+
+/// Delivery state of one alert for one action.
+#[derive(Debug, Clone, Copy, Default)]
+struct Delivery {
+    /// Timestamp the firing of the alert last ran the action
+    last_fired: Option<f64>,
+    /// Whether the current firing ran the action, its resolve runs it then as well
+    delivered: bool,
+}
+
+/// RateLimiter decides whether an action may run for an event.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    cooldown: Duration,
+    max_per_hour: usize,
+    /// Timestamps of the runs within the last hour
+    runs: VecDeque<f64>,
+    alerts: HashMap<String, Delivery>,
+}
+
+impl RateLimiter {
+    pub fn new(cooldown: Duration, max_per_hour: usize) -> Self {
+        Self {
+            cooldown,
+            max_per_hour,
+            runs: VecDeque::new(),
+            alerts: HashMap::new(),
+        }
+    }
+
+    /// Returns whether the action may run for `kind` of the alert `rule` at `timestamp`
+    /// (seconds since the Unix epoch) and records the run. The error is the reason it may not.
+    ///
+    /// # Example
+    /// ```
+    /// use linux_dashboard::actions::RateLimiter;
+    /// use linux_dashboard::backend::alerts::AlertEventKind::{Fired, Resolved};
+    /// use std::time::Duration;
+    /// let mut limiter = RateLimiter::new(Duration::from_secs(60), 10);
+    /// assert!(limiter.allow(Fired, "cpu", 0.0).is_ok());
+    /// assert!(limiter.allow(Resolved, "cpu", 5.0).is_ok());
+    /// // fired again within the cooldown
+    /// assert!(limiter.allow(Fired, "cpu", 10.0).is_err());
+    /// // so its resolve is not sent either
+    /// assert!(limiter.allow(Resolved, "cpu", 15.0).is_err());
+    /// ```
+    ///
+    pub fn allow(
+        &mut self,
+        kind: AlertEventKind,
+        rule: &str,
+        timestamp: f64,
+    ) -> Result<(), &'static str> {
+        while self
+            .runs
+            .front()
+            .is_some_and(|run| timestamp - run >= 3600.0)
+        {
+            self.runs.pop_front();
+        }
+        let limited = self.runs.len() >= self.max_per_hour;
+        let delivery = self.alerts.entry(rule.to_string()).or_default();
+
+        match kind {
+            AlertEventKind::Fired => {
+                delivery.delivered = false;
+                if delivery
+                    .last_fired
+                    .is_some_and(|last| timestamp - last < self.cooldown.as_secs_f64())
+                {
+                    return Err("fired again within the cooldown");
+                }
+                if limited {
+                    return Err("hourly limit reached");
+                }
+                delivery.last_fired = Some(timestamp);
+                delivery.delivered = true;
+            }
+            AlertEventKind::Resolved => {
+                if !delivery.delivered {
+                    return Err("the firing was not sent");
+                }
+                delivery.delivered = false;
+                if limited {
+                    return Err("hourly limit reached");
+                }
+            }
+        }
+        self.runs.push_back(timestamp);
+        Ok(())
+    }
+}
+
+/// Returns the environment variables a command action runs with.
+pub fn alert_environment(
+    event: &AlertEvent,
+    input: &AlertInput,
+    host: &str,
+) -> Vec<(&'static str, String)> {
+    vec![
+        ("DASHBOARD_ALERT_STATE", event.kind.as_str().to_string()),
+        ("DASHBOARD_ALERT_RULE", event.rule.clone()),
+        ("DASHBOARD_ALERT_EXPRESSION", event.expression.clone()),
+        (
+            "DASHBOARD_ALERT_SEVERITY",
+            event.severity.as_str().to_string(),
+        ),
+        ("DASHBOARD_ALERT_VALUE", event.value.clone()),
+        ("DASHBOARD_ALERT_TIME", event.time.to_rfc3339()),
+        ("DASHBOARD_HOST", host.to_string()),
+        (
+            "DASHBOARD_CPU_USAGE",
+            format!("{:.1}", input.cpu.total_usage),
+        ),
+        ("DASHBOARD_MEMORY_TOTAL", input.memory.total.to_string()),
+        ("DASHBOARD_MEMORY_USED", input.memory.used().to_string()),
+        (
+            "DASHBOARD_MEMORY_AVAILABLE",
+            input.memory.available.to_string(),
+        ),
+        ("DASHBOARD_SWAP_USED", input.memory.swap_used().to_string()),
+    ]
+}
+
+/// Formats an event as a line of the log action.
+pub fn log_line(event: &AlertEvent) -> String {
+    format!(
+        "{} {} {} {}: {} ({})",
+        event.time.to_rfc3339(),
+        event.kind.as_str().to_uppercase(),
+        event.severity.as_str(),
+        event.rule,
+        event.value,
+        event.expression
+    )
+}
+
+/// ActionDispatcher runs the configured actions for the events of every evaluation.
+pub struct ActionDispatcher {
+    actions: Vec<(AlertAction, RateLimiter)>,
+    host: String,
+    /// The running commands with the rule that started them
+    children: Vec<(String, Child)>,
+    messages: Vec<String>,
+    /// Lines for the log writer thread, started with the first log action
+    log_tx: Option<mpsc::Sender<LogEntry>>,
+    /// Failures of the log lines and notifications written in the background
+    results_tx: mpsc::Sender<String>,
+    results_rx: mpsc::Receiver<String>,
+}
+
+/// A line of a log action, written by the log writer thread.
+struct LogEntry {
+    path: PathBuf,
+    line: String,
+    rule: String,
+}
+
+fn append_line(path: &Path, line: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{line}")
+}
+
+impl ActionDispatcher {
+    /// Creates a dispatcher for the actions. `host` is passed to commands and notifications.
+    pub fn new(actions: Vec<AlertAction>, host: impl Into<String>) -> Self {
+        let (results_tx, results_rx) = mpsc::channel();
+        Self {
+            actions: actions
+                .into_iter()
+                .map(|action| {
+                    let limiter = RateLimiter::new(action.cooldown, action.max_per_hour);
+                    (action, limiter)
+                })
+                .collect(),
+            host: host.into(),
+            children: Vec::new(),
+            messages: Vec::new(),
+            log_tx: None,
+            results_tx,
+            results_rx,
+        }
+    }
+
+    /// Runs the actions for the events of an evaluation at `timestamp`.
+    pub fn dispatch(&mut self, events: &[AlertEvent], input: &AlertInput, timestamp: f64) {
+        // Collect the results of earlier log lines, notifications and finished commands
+        while let Ok(message) = self.results_rx.try_recv() {
+            self.message(message);
+        }
+        let mut failures = Vec::new();
+        self.children.retain_mut(|(rule, child)| {
+            let failure = match child.try_wait() {
+                Ok(None) => return true,
+                Ok(Some(status)) if status.success() => return false,
+                Ok(Some(status)) => status.to_string(),
+                Err(e) => e.to_string(),
+            };
+            failures.push(format!("command action for '{rule}' failed: {failure}"));
+            false
+        });
+        for failure in failures {
+            self.message(failure);
+        }
+
+        for event in events {
+            for i in 0..self.actions.len() {
+                let (action, limiter) = &mut self.actions[i];
+                if !action.applies_to(event) {
+                    continue;
+                }
+                let kind = action.kind.clone();
+                if let Err(reason) = limiter.allow(event.kind, &event.rule, timestamp) {
+                    self.message(format!(
+                        "{} action for '{}' suppressed: {reason}",
+                        kind.as_str(),
+                        event.rule
+                    ));
+                    continue;
+                }
+                if let Err(e) = self.run(&kind, event, input) {
+                    self.message(format!(
+                        "{} action for '{}' failed: {e}",
+                        kind.as_str(),
+                        event.rule
+                    ));
+                }
+            }
+        }
+    }
+
+    fn run(&mut self, kind: &ActionKind, event: &AlertEvent, input: &AlertInput) -> io::Result<()> {
+        match kind {
+            ActionKind::Command(command) => {
+                if self.children.len() >= MAX_RUNNING_COMMANDS {
+                    return Err(io::Error::other(format!(
+                        "{} earlier commands are still running",
+                        self.children.len()
+                    )));
+                }
+                let child = Command::new("sh")
+                    .arg("-c")
+                    .arg(command)
+                    .envs(alert_environment(event, input, &self.host))
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .spawn()?;
+                self.children.push((event.rule.clone(), child));
+                Ok(())
+            }
+            ActionKind::Log(path) => {
+                // One thread writes all lines, so they stay in the order of the events
+                let log_tx = self.log_tx.get_or_insert_with(|| {
+                    let (log_tx, log_rx) = mpsc::channel::<LogEntry>();
+                    let results = self.results_tx.clone();
+                    std::thread::spawn(move || {
+                        for entry in log_rx {
+                            if let Err(e) = append_line(&entry.path, &entry.line) {
+                                let _ = results
+                                    .send(format!("log action for '{}' failed: {e}", entry.rule));
+                            }
+                        }
+                    });
+                    log_tx
+                });
+                log_tx
+                    .send(LogEntry {
+                        path: path.clone(),
+                        line: log_line(event),
+                        rule: event.rule.clone(),
+                    })
+                    .map_err(|_| io::Error::other("the log writer stopped"))
+            }
+            ActionKind::Notify => {
+                let notification = Notification {
+                    summary: match event.kind {
+                        AlertEventKind::Fired => format!("Alert: {}", event.rule),
+                        AlertEventKind::Resolved => format!("Resolved: {}", event.rule),
+                    },
+                    body: format!("{} on {}", event.value, self.host),
+                    urgency: match (event.kind, event.severity) {
+                        (AlertEventKind::Resolved, _) => Urgency::Low,
+                        (_, Severity::Warning) => Urgency::Normal,
+                        (_, Severity::Critical) => Urgency::Critical,
+                    },
+                };
+                let rule = event.rule.clone();
+                let results = self.results_tx.clone();
+                std::thread::spawn(move || {
+                    if let Err(e) = notify::send_notification(&notification) {
+                        let _ = results.send(format!("notify action for '{rule}' failed: {e}"));
+                    }
+                });
+                Ok(())
+            }
+        }
+    }
+
+    fn message(&mut self, message: String) {
+        self.messages.push(message);
+        if self.messages.len() > MAX_MESSAGES {
+            self.messages.remove(0);
+        }
+    }
+
+    /// Returns the failed and suppressed runs, oldest first.
+    pub fn messages(&self) -> &[String] {
+        &self.messages
+    }
+
+    /// Removes and returns the failed and suppressed runs, e.g. to print them.
+    pub fn take_messages(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.messages)
+    }
+}
@@ -0,0 +1,904 @@
+//! This module implements the `agent` mode and the connection the terminal UI attaches with.
+//! The agent refreshes the system once for all viewers and streams the samples over a
+//! Unix domain socket, so several users watching the same host share one collector.
+//! The stream has the format of a recording: an `AgentHello` line, which is a
+//! `RecordingHeader` with a few more fields, then one `Sample` per line. A viewer first
+//! receives the samples of the last `backlog`, so it starts with the same charts as the
+//! viewers that attached earlier.
+//! The agent can also listen on TCP for viewers on other hosts. A TCP viewer first sends
+//! `AUTH <token>` in one line, the agent answers with the hello or an `AgentRefusal`.
+//! The stream is not encrypted, the token only keeps out viewers that do not know it.
+use crate::actions::{ActionDispatcher, AlertAction};
+use crate::args::Args;
+use crate::backend::alerts::{AlertEngine, AlertInput, AlertRule};
+use crate::backend::host::get_current_user;
+use crate::backend::network::NetworkManager;
+use crate::backend::network_source::SampleNetworks;
+use crate::backend::sample::{Sample, SampleCollector};
+use crate::record::{RECORDING_VERSION, RecordingHeader};
+use crate::refresh::spawn_refresh_task;
+use crate::serve::{MAX_CONNECTIONS, accept_failed};
+use crate::store::{HistorySampler, HistoryStore, Tier};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{self, Permissions};
+use std::io;
+use std::net::SocketAddr;
+use std::os::unix::fs::{DirBuilderExt, FileTypeExt, MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use sysinfo::System;
+use tokio::io::{
+    AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader,
+};
+use tokio::net::{TcpListener, TcpStream, UnixListener, UnixStream};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore, mpsc, watch};
+use tokio::task::{JoinHandle, JoinSet};
+use tokio::time::Instant;
+
+/// Number of samples buffered for a viewer that is slow to read, older samples are skipped.
+const VIEWER_BUFFER: usize = 64;
+/// Time between two attempts to attach again after the agent went away.
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+/// Time a TCP viewer has to authenticate, and to connect and read the hello.
+const AUTH_TIMEOUT: Duration = Duration::from_secs(10);
+/// Longest `AUTH` line accepted.
+const MAX_AUTH_LINE: u64 = 1024;
+/// Longest line a viewer reads from an agent, a longer one is invalid data.
+pub const MAX_SAMPLE_LINE: u64 = 16 * 1024 * 1024;
+/// Environment variable holding the token if no token file is given.
+pub const TOKEN_ENV: &str = "DASHBOARD_AGENT_TOKEN";
+/// Shortest token accepted, in bytes.
+pub const MIN_TOKEN_LEN: usize = 16;
+/// Longest token accepted, in bytes, it has to fit into the `AUTH` line.
+pub const MAX_TOKEN_LEN: usize = 512;
+/// Time a TCP viewer with an invalid token waits for its refusal. Refusals are answered
+/// one after another, so all viewers together cannot guess faster than one token per delay.
+pub const REFUSAL_DELAY: Duration = Duration::from_secs(1);
+/// Longest wait for a refusal. Beyond it refusals are not queued, they are answered after
+/// this wait, and `MAX_CONNECTIONS` unauthenticated viewers bound how many wait at once.
+pub const MAX_REFUSAL_QUEUE: Duration = Duration::from_secs(5);
+
+/// First line the agent sends to a viewer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentHello {
+    #[serde(flatten)]
+    pub header: RecordingHeader,
+    /// The history store the agent writes, if the history is enabled
+    pub history: Option<AgentHistory>,
+}
+
+/// Sent instead of the hello when the agent refuses a viewer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentRefusal {
+    pub error: String,
+}
+
+/// Where the agent keeps the on-disk history, viewers on the same host read it from there.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentHistory {
+    pub path: PathBuf,
+    /// The tiers as `RETENTION@RESOLUTION`
+    pub tiers: Vec<String>,
+}
+
+impl AgentHello {
+    /// Creates the hello of an agent on this host refreshing every `interval`.
+    pub fn new(interval: Duration, history: Option<&HistoryStore>) -> Self {
+        Self {
+            header: RecordingHeader::new(interval),
+            history: history.map(|store| AgentHistory {
+                path: store.dir().to_path_buf(),
+                tiers: store.tiers().iter().map(Tier::to_string).collect(),
+            }),
+        }
+    }
+}
+
+/// Options of the agent mode.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentOptions {
+    /// Socket the viewers attach to
+    pub socket: PathBuf,
+    /// Time between two samples
+    pub interval: Duration,
+    /// How far back the samples sent to a new viewer reach
+    pub backlog: Duration,
+    /// Permissions of the socket, connecting needs write permission
+    pub mode: u32,
+    /// Address to listen on for viewers on other hosts
+    pub listen: Option<SocketAddr>,
+    /// File holding the token TCP viewers authenticate with
+    pub token_file: Option<PathBuf>,
+}
+
+/// Usage text of the agent mode.
+pub const USAGE: &str = "Usage: linux_dashboard agent [--socket PATH] [--interval MS] [--backlog SECONDS] [--mode OCTAL]
+                             [--listen ADDR --token-file FILE]
+  --socket PATH        socket the viewers attach to (default $XDG_RUNTIME_DIR/linux_dashboard.sock,
+                       or /tmp/linux_dashboard-$USER/agent.sock)
+  --interval MS        refresh interval in milliseconds (default 1000)
+  --backlog SECONDS    samples sent to a viewer when it attaches (default 300)
+  --mode OCTAL         permissions of the socket (default 600, e.g. 660 for a group)
+  --listen ADDR        also accept viewers on other hosts over TCP, e.g. 0.0.0.0:9185
+  --token-file FILE    token of the TCP viewers (default $DASHBOARD_AGENT_TOKEN)";
+
+/// Usage text of the attach mode.
+pub const ATTACH_USAGE: &str =
+    "Usage: linux_dashboard attach [SOCKET | HOST:PORT] [--token-file FILE]
+  SOCKET             socket of a running agent (default $XDG_RUNTIME_DIR/linux_dashboard.sock)
+  HOST:PORT          agent on another host listening with --listen
+  --token-file FILE  token of the agent (default $DASHBOARD_AGENT_TOKEN)";
+
+/// Where an agent is attached to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AgentAddress {
+    /// A Unix domain socket on this host
+    Unix(PathBuf),
+    /// A TCP address as `HOST:PORT`, the host may be a name
+    Tcp(String),
+}
+
+impl AgentAddress {
+    /// Parses the address given to `attach`: `HOST:PORT` is a TCP address,
+    /// anything else, e.g. a path with a `/`, is a socket.
+    ///
+    /// # Example
+    /// ```
+    /// use linux_dashboard::agent::AgentAddress;
+    /// assert_eq!(AgentAddress::parse("buildbox:9185"), AgentAddress::Tcp("buildbox:9185".to_string()));
+    /// assert_eq!(AgentAddress::parse("[::1]:9185"), AgentAddress::Tcp("[::1]:9185".to_string()));
+    /// assert!(matches!(AgentAddress::parse("/run/agent.sock"), AgentAddress::Unix(_)));
+    /// assert!(matches!(AgentAddress::parse("agent.sock"), AgentAddress::Unix(_)));
+    /// ```
+    ///
+    pub fn parse(text: &str) -> Self {
+        match text.rsplit_once(':') {
+            Some((host, port))
+                if !host.is_empty() && !text.contains('/') && port.parse::<u16>().is_ok() =>
+            {
+                Self::Tcp(text.to_string())
+            }
+            _ => Self::Unix(PathBuf::from(text)),
+        }
+    }
+
+    /// Returns true for an agent on another host.
+    pub fn is_remote(&self) -> bool {
+        matches!(self, Self::Tcp(_))
+    }
+}
+
+impl fmt::Display for AgentAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unix(path) => write!(f, "{}", path.display()),
+            Self::Tcp(address) => write!(f, "{address}"),
+        }
+    }
+}
+
+/// Options of the attach mode.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttachOptions {
+    pub address: AgentAddress,
+    /// File holding the token of an agent on another host
+    pub token_file: Option<PathBuf>,
+}
+
+impl AttachOptions {
+    /// Parses the arguments following `attach`.
+    /// Returns an error message for unknown arguments or invalid values.
+    ///
+    /// # Example
+    /// ```
+    /// use linux_dashboard::agent::AttachOptions;
+    /// let args = vec!["buildbox:9185".to_string()];
+    /// let options = AttachOptions::from_args(&args).unwrap();
+    /// assert!(options.address.is_remote());
+    /// ```
+    ///
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut address = None;
+        let mut token_file = None;
+        let mut args = Args::new(args);
+        while let Some(arg) = args.next() {
+            match arg {
+                "--token-file" => token_file = Some(PathBuf::from(args.value("--token-file")?)),
+                other if other.starts_with("--") => {
+                    return Err(format!("unknown argument '{other}'"));
+                }
+                other if address.is_none() => address = Some(AgentAddress::parse(other)),
+                other => return Err(format!("unexpected argument '{other}'")),
+            }
+        }
+        Ok(Self {
+            address: address.unwrap_or_else(|| AgentAddress::Unix(default_socket())),
+            token_file,
+        })
+    }
+}
+
+/// Reads the token from `token_file`, or from `$DASHBOARD_AGENT_TOKEN` without a file.
+/// Surrounding whitespace is ignored, a token shorter than `MIN_TOKEN_LEN` or longer
+/// than `MAX_TOKEN_LEN` bytes is an error.
+pub fn load_token(token_file: Option<&Path>) -> Result<Option<String>, String> {
+    let token = match token_file {
+        Some(path) => fs::read_to_string(path)
+            .map_err(|e| format!("cannot read the token file {}: {e}", path.display()))?,
+        None => match std::env::var(TOKEN_ENV) {
+            Ok(token) => token,
+            Err(_) => return Ok(None),
+        },
+    };
+    let token = token.trim();
+    if token.contains(char::is_whitespace) {
+        return Err("the token must not contain whitespace".to_string());
+    }
+    if !(MIN_TOKEN_LEN..=MAX_TOKEN_LEN).contains(&token.len()) {
+        return Err(format!(
+            "the token must have {MIN_TOKEN_LEN} to {MAX_TOKEN_LEN} characters, e.g. `head -c 24 /dev/urandom | base64`"
+        ));
+    }
+    Ok(Some(token.to_string()))
+}
+
+/// Returns the default socket: in `$XDG_RUNTIME_DIR`, falling back to a per-user
+/// directory in the temporary directory, see `fallback_socket_dir`.
+pub fn default_socket() -> PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir).join("linux_dashboard.sock"),
+        None => fallback_socket_dir().join("agent.sock"),
+    }
+}
+
+/// Returns the directory of the default socket without `$XDG_RUNTIME_DIR`.
+/// Everybody can create files in the temporary directory, so the agent only uses it
+/// if it is a private directory of the user, see `ensure_private_dir`.
+pub fn fallback_socket_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("linux_dashboard-{}", get_current_user()))
+}
+
+/// Creates `dir` accessible only to the user, or checks that an existing `dir` is a
+/// directory owned by the user that nobody else can access.
+pub fn ensure_private_dir(dir: &Path) -> io::Result<()> {
+    match fs::DirBuilder::new().mode(0o700).create(dir) {
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+        result => return result,
+    }
+    let metadata = fs::symlink_metadata(dir)?;
+    // The process directory belongs to the effective user of the process
+    let uid = fs::metadata("/proc/self")?.uid();
+    if !metadata.is_dir() || metadata.uid() != uid || metadata.mode() & 0o077 != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!(
+                "{} is not a private directory of the user, give --socket",
+                dir.display()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+impl AgentOptions {
+    /// Parses the arguments following `agent`.
+    /// Returns an error message for unknown arguments or invalid values.
+    ///
+    /// # Example
+    /// ```
+    /// use linux_dashboard::agent::AgentOptions;
+    /// let args = vec!["--mode".to_string(), "660".to_string()];
+    /// let options = AgentOptions::from_args(&args).unwrap();
+    /// assert_eq!(options.mode, 0o660);
+    /// assert_eq!(options.backlog.as_secs(), 300);
+    /// ```
+    ///
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut options = Self {
+            socket: default_socket(),
+            interval: Duration::from_millis(1000),
+            backlog: Duration::from_secs(300),
+            mode: 0o600,
+            listen: None,
+            token_file: None,
+        };
+        let mut args = Args::new(args);
+        while let Some(arg) = args.next() {
+            match arg {
+                "--socket" => options.socket = PathBuf::from(args.value("--socket")?),
+                "--interval" => options.interval = args.interval("--interval")?,
+                "--backlog" => {
+                    options.backlog =
+                        Duration::from_secs(args.parse("--backlog", "a number of seconds")?);
+                }
+                "--mode" => {
+                    options.mode = u32::from_str_radix(args.value("--mode")?, 8)
+                        .ok()
+                        .filter(|mode| *mode <= 0o777)
+                        .ok_or_else(|| "--mode expects permissions like 660".to_string())?;
+                }
+                "--listen" => {
+                    options.listen = Some(args.parse("--listen", "an address like 0.0.0.0:9185")?);
+                }
+                "--token-file" => {
+                    options.token_file = Some(PathBuf::from(args.value("--token-file")?));
+                }
+                other => return Err(format!("unknown argument '{other}'")),
+            }
+        }
+        Ok(options)
+    }
+}
+
+struct FeedState {
+    /// The samples within the backlog, encoded with their line break
+    backlog: VecDeque<(f64, Arc<str>)>,
+    sender: broadcast::Sender<Arc<str>>,
+}
+
+/// SampleFeed hands the samples of the agent to the viewers.
+/// Every sample is encoded once, however many viewers are attached.
+#[derive(Clone)]
+pub struct SampleFeed {
+    hello: Arc<str>,
+    backlog: Duration,
+    state: Arc<Mutex<FeedState>>,
+}
+
+impl SampleFeed {
+    /// Creates a feed starting with `hello` that keeps the samples of the last `backlog`.
+    pub fn new(hello: &AgentHello, backlog: Duration) -> io::Result<Self> {
+        let (sender, _) = broadcast::channel(VIEWER_BUFFER);
+        Ok(Self {
+            hello: encode_line(hello)?,
+            backlog,
+            state: Arc::new(Mutex::new(FeedState {
+                backlog: VecDeque::new(),
+                sender,
+            })),
+        })
+    }
+
+    /// Sends the sample to the attached viewers and keeps it for the viewers attaching later.
+    /// The latest sample is always kept, so a new viewer has data right away.
+    pub fn publish(&self, sample: &Sample) -> io::Result<()> {
+        let line = encode_line(sample)?;
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        // Fails only while no viewer is attached
+        let _ = state.sender.send(Arc::clone(&line));
+        state.backlog.push_back((sample.timestamp, line));
+        let oldest = sample.timestamp - self.backlog.as_secs_f64();
+        while state.backlog.len() > 1 && state.backlog[0].0 < oldest {
+            state.backlog.pop_front();
+        }
+        Ok(())
+    }
+
+    /// Returns the lines a new viewer starts with, the hello and the backlog,
+    /// and the receiver of the samples published from now on.
+    pub fn subscribe(&self) -> (Vec<Arc<str>>, broadcast::Receiver<Arc<str>>) {
+        // Under the lock, so no sample is missed or sent twice
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let mut lines = vec![Arc::clone(&self.hello)];
+        lines.extend(state.backlog.iter().map(|(_, line)| Arc::clone(line)));
+        (lines, state.sender.subscribe())
+    }
+}
+
+fn encode_line(value: &impl Serialize) -> io::Result<Arc<str>> {
+    let mut line = serde_json::to_string(value)?;
+    line.push('\n');
+    Ok(line.into())
+}
+
+enum Listener {
+    Unix {
+        listener: UnixListener,
+        path: PathBuf,
+    },
+    Tcp {
+        listener: TcpListener,
+        token: Arc<str>,
+        refusals: Arc<RefusalQueue>,
+    },
+}
+
+/// The socket the viewers attach to, a Unix domain socket or a TCP port.
+/// The socket file is removed when the server is dropped.
+pub struct AgentServer {
+    listener: Listener,
+    feed: SampleFeed,
+    messages: Option<mpsc::UnboundedSender<String>>,
+}
+
+impl AgentServer {
+    /// Creates the socket at `path` with the permissions `mode`.
+    /// A socket left behind by an agent that is gone is replaced, a running agent is an error.
+    pub fn bind(path: &Path, mode: u32, feed: SampleFeed) -> io::Result<Self> {
+        match fs::symlink_metadata(path) {
+            Ok(metadata) if !metadata.file_type().is_socket() => {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} exists and is not a socket", path.display()),
+                ));
+            }
+            Ok(_) => {
+                if std::os::unix::net::UnixStream::connect(path).is_ok() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::AddrInUse,
+                        format!("an agent is already running on {}", path.display()),
+                    ));
+                }
+                fs::remove_file(path)?;
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(parent)?;
+        // The socket is created in a directory only the user can enter and linked to `path`
+        // once it has its permissions, so nobody can connect while it has the default ones
+        let staging = parent.join(format!(".linux_dashboard-{}", std::process::id()));
+        if fs::symlink_metadata(&staging).is_ok() {
+            fs::remove_dir_all(&staging)?;
+        }
+        fs::DirBuilder::new().mode(0o700).create(&staging)?;
+        let staged = staging.join("agent.sock");
+        let listener = UnixListener::bind(&staged).and_then(|listener| {
+            fs::set_permissions(&staged, Permissions::from_mode(mode))?;
+            fs::hard_link(&staged, path)?;
+            Ok(listener)
+        });
+        let _ = fs::remove_dir_all(&staging);
+        Ok(Self {
+            listener: Listener::Unix {
+                listener: listener?,
+                path: path.to_path_buf(),
+            },
+            feed,
+            messages: None,
+        })
+    }
+
+    /// Sends the problems that do not stop the agent, e.g. a failed accept, to `messages`.
+    pub fn report_to(mut self, messages: mpsc::UnboundedSender<String>) -> Self {
+        self.messages = Some(messages);
+        self
+    }
+
+    /// Listens on the TCP `address` for viewers that authenticate with `token`.
+    pub async fn bind_tcp(
+        address: SocketAddr,
+        token: String,
+        feed: SampleFeed,
+    ) -> io::Result<Self> {
+        Ok(Self {
+            listener: Listener::Tcp {
+                listener: TcpListener::bind(address).await?,
+                token: token.into(),
+                refusals: Arc::default(),
+            },
+            feed,
+            messages: None,
+        })
+    }
+
+    /// Returns the address viewers attach to, with the actual port if bound to port 0.
+    pub fn address(&self) -> io::Result<AgentAddress> {
+        Ok(match &self.listener {
+            Listener::Unix { path, .. } => AgentAddress::Unix(path.clone()),
+            Listener::Tcp { listener, .. } => AgentAddress::Tcp(listener.local_addr()?.to_string()),
+        })
+    }
+
+    /// Accepts viewers until the future is dropped, every viewer is served by its own task.
+    /// Dropping the future also ends the tasks, which closes the connections of the viewers.
+    /// A failed accept is reported and retried after a pause instead of ending the agent.
+    /// At most `MAX_CONNECTIONS` TCP viewers authenticate at once, further ones wait in
+    /// the backlog of the socket.
+    pub async fn run(&self) -> io::Result<()> {
+        let mut viewers = JoinSet::new();
+        let unauthenticated = Arc::new(Semaphore::new(MAX_CONNECTIONS));
+        loop {
+            let feed = self.feed.clone();
+            // A viewer that went away is no error of the agent
+            let accepted = match &self.listener {
+                Listener::Unix { listener, .. } => listener.accept().await.map(|(stream, _)| {
+                    viewers.spawn(async move {
+                        let _ = serve_viewer(stream, &feed).await;
+                    });
+                }),
+                Listener::Tcp {
+                    listener,
+                    token,
+                    refusals,
+                } => {
+                    let Ok(permit) = Arc::clone(&unauthenticated).acquire_owned().await else {
+                        return Ok(());
+                    };
+                    listener.accept().await.map(|(stream, _)| {
+                        let token = Arc::clone(token);
+                        let refusals = Arc::clone(refusals);
+                        viewers.spawn(async move {
+                            let _ =
+                                serve_remote_viewer(stream, permit, &token, &refusals, &feed).await;
+                        });
+                    })
+                }
+            };
+            if let Err(e) = accepted {
+                accept_failed(&e, self.messages.as_ref()).await;
+            }
+            while viewers.try_join_next().is_some() {}
+        }
+    }
+}
+
+impl Drop for AgentServer {
+    fn drop(&mut self) {
+        if let Listener::Unix { path, .. } = &self.listener {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+// Compares all `MAX_TOKEN_LEN` bytes, padded with zeros, whatever the lengths are, so the
+// time taken does not tell how much of the token matched or how long it is
+fn tokens_match(given: &str, token: &str) -> bool {
+    let (given, token) = (given.as_bytes(), token.as_bytes());
+    let mut difference = (given.len() ^ token.len()) as u64;
+    for i in 0..MAX_TOKEN_LEN {
+        let a = given.get(i).copied().unwrap_or(0);
+        let b = token.get(i).copied().unwrap_or(0);
+        difference |= std::hint::black_box(u64::from(a ^ b));
+    }
+    difference == 0
+}
+
+/// The times the refused TCP viewers get their answer, shared by all connections.
+#[derive(Default)]
+struct RefusalQueue {
+    /// When the last refusal in the queue is answered
+    last: Mutex<Option<Instant>>,
+}
+
+impl RefusalQueue {
+    /// Queues a refusal and returns when it is answered, `REFUSAL_DELAY` after the
+    /// previous one. A full queue answers after `MAX_REFUSAL_QUEUE` without queueing.
+    fn push(&self, now: Instant) -> Instant {
+        let mut last = self.last.lock().unwrap_or_else(|e| e.into_inner());
+        let answer = last.map_or(now, |last| last.max(now)) + REFUSAL_DELAY;
+        if answer > now + MAX_REFUSAL_QUEUE {
+            return now + MAX_REFUSAL_QUEUE;
+        }
+        *last = Some(answer);
+        answer
+    }
+}
+
+async fn serve_remote_viewer(
+    stream: TcpStream,
+    permit: OwnedSemaphorePermit,
+    token: &str,
+    refusals: &RefusalQueue,
+    feed: &SampleFeed,
+) -> io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader.take(MAX_AUTH_LINE));
+    let mut line = String::new();
+    if let Ok(Ok(_)) = tokio::time::timeout(AUTH_TIMEOUT, reader.read_line(&mut line)).await
+        && let Some(given) = line.trim_end().strip_prefix("AUTH ")
+        && tokens_match(given, token)
+    {
+        drop(permit);
+        return serve_viewer(writer, feed).await;
+    }
+    tokio::time::sleep_until(refusals.push(Instant::now())).await;
+    let refusal = AgentRefusal {
+        error: "invalid token".to_string(),
+    };
+    writer.write_all(encode_line(&refusal)?.as_bytes()).await
+}
+
+async fn serve_viewer(mut stream: impl AsyncWrite + Unpin, feed: &SampleFeed) -> io::Result<()> {
+    let (lines, mut receiver) = feed.subscribe();
+    for line in lines {
+        stream.write_all(line.as_bytes()).await?;
+    }
+    loop {
+        match receiver.recv().await {
+            Ok(line) => stream.write_all(line.as_bytes()).await?,
+            // A slow viewer skips the samples it missed, the rates are computed from timestamps
+            Err(RecvError::Lagged(_)) => {}
+            Err(RecvError::Closed) => return Ok(()),
+        }
+    }
+}
+
+/// The work the agent does for every sample besides sending it: the alert actions and
+/// the history store, which would run once per viewer otherwise.
+struct AgentTasks {
+    messages: mpsc::UnboundedSender<String>,
+    alerts: AlertEngine,
+    actions: ActionDispatcher,
+    network: NetworkManager<SampleNetworks>,
+    history: Option<(HistoryStore, HistorySampler)>,
+}
+
+impl AgentTasks {
+    fn process(&mut self, sample: &Sample) {
+        let networks = self.network.source_mut();
+        networks.set_interfaces(sample.interfaces.clone());
+        self.network.refresh_at(sample.timestamp);
+        let input = AlertInput {
+            cpu: &sample.cpu,
+            memory: &sample.memory,
+            disks: &sample.disks,
+            network: self.network.throughput(),
+            processes: &sample.processes,
+        };
+        let events = self.alerts.evaluate(&input, sample.timestamp);
+        self.actions.dispatch(&events, &input, sample.timestamp);
+        for message in self.actions.take_messages() {
+            let _ = self.messages.send(message);
+        }
+
+        if let Some((store, sampler)) = &mut self.history
+            && let Some(point) = sampler.point(sample)
+            && let Err(e) = store.push(&point)
+        {
+            let _ = self.messages.send(format!("cannot write the history: {e}"));
+        }
+    }
+}
+
+/// Runs the agent until Ctrl-C is pressed.
+/// The system is refreshed by the same background task the terminal UI uses, the alert
+/// rules and actions run in the agent and the history is written to `history`.
+/// Listening on TCP requires a `token`. Problems that do not stop the agent are sent to
+/// `messages`.
+pub async fn agent(
+    options: AgentOptions,
+    token: Option<String>,
+    alert_rules: Vec<AlertRule>,
+    alert_actions: Vec<AlertAction>,
+    history: Option<HistoryStore>,
+    messages: mpsc::UnboundedSender<String>,
+) -> io::Result<()> {
+    let hello = AgentHello::new(options.interval, history.as_ref());
+    let feed = SampleFeed::new(&hello, options.backlog)?;
+    let remote = match (options.listen, token) {
+        (Some(address), Some(token)) => Some(
+            AgentServer::bind_tcp(address, token, feed.clone())
+                .await?
+                .report_to(messages.clone()),
+        ),
+        (Some(_), None) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("--listen needs a token, give --token-file or set ${TOKEN_ENV}"),
+            ));
+        }
+        (None, _) => None,
+    };
+    if options.socket.parent() == Some(fallback_socket_dir().as_path()) {
+        ensure_private_dir(&fallback_socket_dir())?;
+    }
+    let server =
+        AgentServer::bind(&options.socket, options.mode, feed.clone())?.report_to(messages.clone());
+    let mut addresses = vec![server.address()?.to_string()];
+    if let Some(remote) = &remote {
+        addresses.push(remote.address()?.to_string());
+    }
+    println!(
+        "Serving samples on {}, press Ctrl-C to stop",
+        addresses.join(" and ")
+    );
+    if let Some(store) = history.as_ref().filter(|store| !store.is_writable()) {
+        let _ = messages.send(format!(
+            "another process is writing to {}, the history is not written by the agent",
+            store.dir().display()
+        ));
+    }
+
+    let mut tasks = AgentTasks {
+        messages: messages.clone(),
+        alerts: AlertEngine::new(alert_rules),
+        actions: ActionDispatcher::new(alert_actions, hello.header.host.host_name.clone()),
+        network: NetworkManager::new(SampleNetworks::default()),
+        history: history.map(|store| (store, HistorySampler::default())),
+    };
+    let mut collector = SampleCollector::default();
+    let sys = Arc::new(Mutex::new(System::new_all()));
+    {
+        let s = sys.lock().unwrap_or_else(|e| e.into_inner());
+        let sample = collector.collect(&s);
+        tasks.process(&sample);
+        feed.publish(&sample)?;
+    }
+
+    let (interval_tx, interval_rx) = watch::channel(options.interval.as_millis() as u64);
+    let refresh = spawn_refresh_task(sys, interval_rx, move |s| {
+        let sample = collector.collect(s);
+        tasks.process(&sample);
+        if let Err(e) = feed.publish(&sample) {
+            let _ = messages.send(format!("cannot encode the sample: {e}"));
+        }
+    });
+
+    let result = tokio::select! {
+        result = tokio::signal::ctrl_c() => result,
+        result = server.run() => result,
+        result = async {
+            match &remote {
+                Some(remote) => remote.run().await,
+                None => std::future::pending().await,
+            }
+        } => result,
+    };
+    drop(interval_tx);
+    let _ = refresh.await;
+    result
+}
+
+/// A viewer's connection to an agent.
+pub struct AgentConnection {
+    pub hello: AgentHello,
+    reader: Box<dyn AsyncBufRead + Send + Unpin>,
+}
+
+/// Attaches to the agent at `address` and reads its hello.
+/// An agent on another host needs the `token` it was started with.
+pub async fn connect(address: &AgentAddress, token: Option<&str>) -> io::Result<AgentConnection> {
+    let (reader, line) = tokio::time::timeout(AUTH_TIMEOUT, open(address, token))
+        .await
+        .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "the agent did not answer"))??;
+    if let Ok(refusal) = serde_json::from_str::<AgentRefusal>(&line) {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("the agent refused the connection: {}", refusal.error),
+        ));
+    }
+    let hello: AgentHello = serde_json::from_str(&line).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid hello of the agent: {e}"),
+        )
+    })?;
+    if hello.header.version > RECORDING_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "the agent sends samples of version {}, the newest supported version is {RECORDING_VERSION}",
+                hello.header.version
+            ),
+        ));
+    }
+    Ok(AgentConnection { hello, reader })
+}
+
+// Connects, authenticates over TCP and returns the first line the agent sends
+async fn open(
+    address: &AgentAddress,
+    token: Option<&str>,
+) -> io::Result<(Box<dyn AsyncBufRead + Send + Unpin>, String)> {
+    let mut reader: Box<dyn AsyncBufRead + Send + Unpin> = match address {
+        AgentAddress::Unix(path) => Box::new(BufReader::new(UnixStream::connect(path).await?)),
+        AgentAddress::Tcp(host) => {
+            let token = token.ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    format!("an agent on another host needs a token, give --token-file or set ${TOKEN_ENV}"),
+                )
+            })?;
+            let mut stream = TcpStream::connect(host.as_str()).await?;
+            stream
+                .write_all(format!("AUTH {token}\n").as_bytes())
+                .await?;
+            Box::new(BufReader::new(stream))
+        }
+    };
+    let line = read_line(&mut reader).await?.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "the agent closed the connection",
+        )
+    })?;
+    Ok((reader, line))
+}
+
+// Reads a line of at most `MAX_SAMPLE_LINE` bytes, `None` at the end of the stream
+async fn read_line(reader: &mut (impl AsyncBufRead + Unpin)) -> io::Result<Option<String>> {
+    let mut line = Vec::new();
+    let read = reader
+        .take(MAX_SAMPLE_LINE)
+        .read_until(b'\n', &mut line)
+        .await?;
+    if read == 0 {
+        return Ok(None);
+    }
+    if read as u64 == MAX_SAMPLE_LINE && !line.ends_with(b"\n") {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("the agent sent a line longer than {MAX_SAMPLE_LINE} bytes"),
+        ));
+    }
+    String::from_utf8(line)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+impl AgentConnection {
+    /// Reads the next sample, `None` once the agent closed the connection.
+    pub async fn next_sample(&mut self) -> io::Result<Option<Sample>> {
+        let Some(line) = read_line(&mut self.reader).await? else {
+            return Ok(None);
+        };
+        serde_json::from_str(&line)
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// What a viewer receives from its connection task.
+#[derive(Debug)]
+pub enum ViewerEvent {
+    Sample(Box<Sample>),
+    /// The connection was lost, a new attempt is made every few seconds
+    Disconnected(String),
+    /// Attached again after the connection was lost
+    Reconnected(Box<AgentHello>),
+}
+
+/// Spawns a task reading the samples of `connection`. When the agent goes away, the task
+/// attaches to `address` again until the receiver is dropped. After attaching again the
+/// backlog is sent once more, the viewer skips the samples it already has.
+pub fn spawn_viewer(
+    address: AgentAddress,
+    token: Option<String>,
+    mut connection: AgentConnection,
+) -> (JoinHandle<()>, mpsc::UnboundedReceiver<ViewerEvent>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let handle = tokio::spawn(async move {
+        loop {
+            let reason = loop {
+                match connection.next_sample().await {
+                    Ok(Some(sample)) => {
+                        if tx.send(ViewerEvent::Sample(Box::new(sample))).is_err() {
+                            return;
+                        }
+                    }
+                    Ok(None) => break "the agent stopped".to_string(),
+                    Err(e) => break e.to_string(),
+                }
+            };
+            if tx.send(ViewerEvent::Disconnected(reason)).is_err() {
+                return;
+            }
+            connection = loop {
+                tokio::time::sleep(RECONNECT_DELAY).await;
+                if tx.is_closed() {
+                    return;
+                }
+                if let Ok(connection) = connect(&address, token.as_deref()).await {
+                    break connection;
+                }
+            };
+            let hello = Box::new(connection.hello.clone());
+            if tx.send(ViewerEvent::Reconnected(hello)).is_err() {
+                return;
+            }
+        }
+    });
+    (handle, rx)
+}
@@ -0,0 +1,79 @@
+//! This module parses the command line arguments of the subcommands.
+//! Every mode has its own options struct with a `from_args` function, they share the
+//! parsing of option values and their error messages through `Args`.
+use std::str::FromStr;
+use std::time::Duration;
+
+/// The arguments following a subcommand.
+/// Iterating yields the next argument, the methods read the value of the current option.
+pub struct Args<'a> {
+    args: std::slice::Iter<'a, String>,
+}
+
+impl<'a> Args<'a> {
+    pub fn new(args: &'a [String]) -> Self {
+        Self { args: args.iter() }
+    }
+
+    /// Returns the value following the option `name`.
+    pub fn value(&mut self, name: &str) -> Result<&'a str, String> {
+        self.args
+            .next()
+            .map(String::as_str)
+            .ok_or_else(|| format!("{name} expects a value"))
+    }
+
+    /// Parses the value following the option `name`, `expected` describes it in the error.
+    ///
+    /// # Example
+    /// ```
+    /// use linux_dashboard::args::Args;
+    /// use std::net::SocketAddr;
+    /// let values = vec!["somewhere".to_string()];
+    /// let error = Args::new(&values)
+    ///     .parse::<SocketAddr>("--listen", "an address like 127.0.0.1:9184")
+    ///     .unwrap_err();
+    /// assert_eq!(error, "--listen expects an address like 127.0.0.1:9184");
+    /// ```
+    ///
+    pub fn parse<T: FromStr>(&mut self, name: &str, expected: &str) -> Result<T, String> {
+        self.args
+            .next()
+            .and_then(|value| value.parse().ok())
+            .ok_or_else(|| format!("{name} expects {expected}"))
+    }
+
+    /// Parses the number following the option `name`.
+    pub fn number(&mut self, name: &str) -> Result<u64, String> {
+        self.parse(name, "a number")
+    }
+
+    /// Parses the milliseconds following the option `name` as the time between two samples.
+    /// Shorter intervals are raised to the minimum sysinfo needs for the CPU usage.
+    ///
+    /// # Example
+    /// ```
+    /// use linux_dashboard::args::Args;
+    /// let values = vec!["1".to_string(), "2500".to_string()];
+    /// let mut args = Args::new(&values);
+    /// assert_eq!(
+    ///     args.interval("--interval").unwrap(),
+    ///     sysinfo::MINIMUM_CPU_UPDATE_INTERVAL
+    /// );
+    /// assert_eq!(args.interval("--interval").unwrap().as_millis(), 2500);
+    /// assert!(args.interval("--interval").is_err());
+    /// ```
+    ///
+    pub fn interval(&mut self, name: &str) -> Result<Duration, String> {
+        let interval = Duration::from_millis(self.number(name)?);
+        Ok(interval.max(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL))
+    }
+}
+
+impl<'a> Iterator for Args<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.args.next().map(String::as_str)
+    }
+}
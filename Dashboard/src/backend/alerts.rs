@@ -0,0 +1,611 @@
+//! This module evaluates user-defined alert rules against the data of every refresh.
+//! A rule compares a metric with a threshold, e.g. `cpu.total > 90 for 30s`,
+//! `mem.available < 1G` or `disk./var.used > 95%`, or checks that a process is running,
+//! e.g. `process "postgres" missing`. A rule fires once its condition held for the
+//! `for` duration and resolves once the condition was false for the rule's
+//! `resolve_after` duration. Without one, the alert resolves as soon as the condition is false,
+//! a longer `resolve_after` keeps a flapping metric from firing and resolving on every refresh.
+//! The engine only works on the data handed to `AlertEngine::evaluate`,
+//! so it can be tested with synthetic data and replayed recordings.
+use super::{
+    converter::{format_bytes, format_rate},
+    cpu::CpuStats,
+    disk::DiskInfo,
+    history::local_time,
+    memory::MemInfo,
+    network::InterfaceThroughput,
+    processes::ProcessInfo,
+};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+/// Maximum number of events kept in the history.
+const MAX_EVENTS: usize = 100;
+
+/// How urgent an alert is, decides its colour in the UI.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Warning => "warning",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+/// A value a rule can compare against a threshold.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Metric {
+    /// `cpu.total`, usage of all cores in percent
+    CpuTotal,
+    /// `cpu.core.N`, usage of one core in percent
+    CpuCore(usize),
+    /// `mem.used`, `mem.available`, `mem.free` and `swap.used` in bytes
+    MemoryUsed,
+    MemoryAvailable,
+    MemoryFree,
+    SwapUsed,
+    /// `disk.<mount point>.used` and `disk.<mount point>.available` in bytes
+    DiskUsed(String),
+    DiskAvailable(String),
+    /// `net.<interface>.rx` and `net.<interface>.tx` in bytes per second
+    NetworkReceive(String),
+    NetworkTransmit(String),
+}
+
+/// The unit of a metric, used to format its values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    Percent,
+    Bytes,
+    BytesPerSecond,
+}
+
+impl Metric {
+    /// Parses a metric name like `cpu.total` or `disk./var.used`.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        let unknown = || format!("unknown metric '{name}'");
+        let metric = match name {
+            "cpu.total" => Self::CpuTotal,
+            "mem.used" => Self::MemoryUsed,
+            "mem.available" => Self::MemoryAvailable,
+            "mem.free" => Self::MemoryFree,
+            "swap.used" => Self::SwapUsed,
+            _ => {
+                if let Some(core) = name.strip_prefix("cpu.core.") {
+                    Self::CpuCore(core.parse().map_err(|_| unknown())?)
+                } else if let Some(rest) = name.strip_prefix("disk.") {
+                    // the mount point may contain dots itself, the field is after the last one
+                    match rest.rsplit_once('.') {
+                        Some((mount, "used")) if !mount.is_empty() => {
+                            Self::DiskUsed(mount.to_string())
+                        }
+                        Some((mount, "available")) if !mount.is_empty() => {
+                            Self::DiskAvailable(mount.to_string())
+                        }
+                        _ => return Err(unknown()),
+                    }
+                } else if let Some(rest) = name.strip_prefix("net.") {
+                    match rest.rsplit_once('.') {
+                        Some((interface, "rx")) if !interface.is_empty() => {
+                            Self::NetworkReceive(interface.to_string())
+                        }
+                        Some((interface, "tx")) if !interface.is_empty() => {
+                            Self::NetworkTransmit(interface.to_string())
+                        }
+                        _ => return Err(unknown()),
+                    }
+                } else {
+                    return Err(unknown());
+                }
+            }
+        };
+        Ok(metric)
+    }
+
+    fn unit(&self) -> Unit {
+        match self {
+            Self::CpuTotal | Self::CpuCore(_) => Unit::Percent,
+            Self::NetworkReceive(_) | Self::NetworkTransmit(_) => Unit::BytesPerSecond,
+            _ => Unit::Bytes,
+        }
+    }
+
+    /// Returns the current value and the total a percentage refers to.
+    /// `None` if the metric is not available, e.g. the disk is not mounted.
+    fn value(&self, input: &AlertInput) -> Option<(f64, Option<f64>)> {
+        let memory = input.memory;
+        let disk = |mount: &str| input.disks.iter().find(|d| d.mount_point == mount);
+        let interface = |name: &str| input.network.iter().find(|i| i.name == name);
+        match self {
+            Self::CpuTotal => Some((input.cpu.total_usage as f64, Some(100.0))),
+            Self::CpuCore(core) => input
+                .cpu
+                .cores
+                .get(*core)
+                .map(|usage| (*usage as f64, Some(100.0))),
+            Self::MemoryUsed => Some((memory.used() as f64, Some(memory.total as f64))),
+            Self::MemoryAvailable => Some((memory.available as f64, Some(memory.total as f64))),
+            Self::MemoryFree => Some((memory.free as f64, Some(memory.total as f64))),
+            Self::SwapUsed => Some((memory.swap_used() as f64, Some(memory.swap_total as f64))),
+            Self::DiskUsed(mount) => disk(mount).map(|d| {
+                (
+                    d.total.saturating_sub(d.available) as f64,
+                    Some(d.total as f64),
+                )
+            }),
+            Self::DiskAvailable(mount) => {
+                disk(mount).map(|d| (d.available as f64, Some(d.total as f64)))
+            }
+            Self::NetworkReceive(name) => interface(name)?.download.map(|rate| (rate, None)),
+            Self::NetworkTransmit(name) => interface(name)?.upload.map(|rate| (rate, None)),
+        }
+    }
+}
+
+/// How a metric is compared with the threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
+impl Comparison {
+    fn parse(operator: &str) -> Result<Self, String> {
+        match operator {
+            ">" => Ok(Self::Greater),
+            ">=" => Ok(Self::GreaterOrEqual),
+            "<" => Ok(Self::Less),
+            "<=" => Ok(Self::LessOrEqual),
+            other => Err(format!(
+                "unknown comparison '{other}', expected >, >=, < or <="
+            )),
+        }
+    }
+
+    fn holds(self, value: f64, threshold: f64) -> bool {
+        match self {
+            Self::Greater => value > threshold,
+            Self::GreaterOrEqual => value >= threshold,
+            Self::Less => value < threshold,
+            Self::LessOrEqual => value <= threshold,
+        }
+    }
+}
+
+/// The value a metric is compared with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Threshold {
+    /// In the unit of the metric: percent, bytes or bytes per second
+    Absolute(f64),
+    /// Percent of the total, e.g. of the memory or the disk size
+    Percent(f64),
+}
+
+impl Threshold {
+    /// Parses a threshold like `90`, `95%`, `1G`, `512MiB` or `10M/s`.
+    /// Sizes use binary prefixes, `1K` is 1024 bytes.
+    ///
+    /// # Example
+    /// ```
+    /// use linux_dashboard::backend::alerts::Threshold;
+    /// assert_eq!(Threshold::parse("95%"), Ok(Threshold::Percent(95.0)));
+    /// assert_eq!(Threshold::parse("1G"), Ok(Threshold::Absolute(1073741824.0)));
+    /// assert_eq!(Threshold::parse("2K/s"), Ok(Threshold::Absolute(2048.0)));
+    /// ```
+    ///
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let invalid = || format!("invalid threshold '{text}'");
+        if let Some(percent) = text.strip_suffix('%') {
+            return percent.parse().map(Self::Percent).map_err(|_| invalid());
+        }
+        let text_lower = text.to_ascii_lowercase();
+        let value = text_lower.strip_suffix("/s").unwrap_or(&text_lower);
+        let value = value
+            .strip_suffix("ib")
+            .or_else(|| value.strip_suffix('b'))
+            .unwrap_or(value);
+        let (number, multiplier) = match value.chars().last() {
+            Some('k') => (&value[..value.len() - 1], 1024.0),
+            Some('m') => (&value[..value.len() - 1], 1024.0 * 1024.0),
+            Some('g') => (&value[..value.len() - 1], 1024.0 * 1024.0 * 1024.0),
+            Some('t') => (&value[..value.len() - 1], 1024.0 * 1024.0 * 1024.0 * 1024.0),
+            _ => (value, 1.0),
+        };
+        number
+            .parse::<f64>()
+            .map(|number| Self::Absolute(number * multiplier))
+            .map_err(|_| invalid())
+    }
+}
+
+/// What a rule checks.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Compare {
+        metric: Metric,
+        comparison: Comparison,
+        threshold: Threshold,
+    },
+    /// No process with exactly this name is running
+    ProcessMissing(String),
+}
+
+/// A parsed alert rule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertRule {
+    /// Shown in the banner and the history, the rule expression if not configured
+    pub name: String,
+    /// The rule expression as written in the config file
+    pub expression: String,
+    pub condition: Condition,
+    /// How long the condition has to hold before the alert fires
+    pub duration: Duration,
+    /// How long the condition has to be false before an active alert resolves
+    pub resolve_after: Duration,
+    pub severity: Severity,
+}
+
+impl AlertRule {
+    /// Parses a rule expression of the form `METRIC OP THRESHOLD [for DURATION]`
+    /// or `process "NAME" missing [for DURATION]`. The rule is named after the expression.
+    ///
+    /// # Example
+    /// ```
+    /// use linux_dashboard::backend::alerts::AlertRule;
+    /// let rule = AlertRule::parse("cpu.total > 90 for 30s").unwrap();
+    /// assert_eq!(rule.duration.as_secs(), 30);
+    /// assert!(AlertRule::parse("cpu.total is high").is_err());
+    /// ```
+    ///
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let error = |message: String| format!("invalid rule '{expression}': {message}");
+        let mut tokens = tokenize(expression).map_err(error)?;
+
+        let mut duration = Duration::ZERO;
+        if tokens.len() >= 2 && tokens[tokens.len() - 2] == "for" {
+            let value = tokens.pop().unwrap_or_default();
+            tokens.pop();
+            duration = parse_duration(&value).map_err(error)?;
+        }
+
+        let condition = match tokens.as_slice() {
+            [keyword, name, state] if keyword == "process" && state == "missing" => {
+                Condition::ProcessMissing(name.clone())
+            }
+            [metric, operator, threshold] => {
+                let metric = Metric::parse(metric).map_err(error)?;
+                let threshold = Threshold::parse(threshold).map_err(error)?;
+                if matches!(threshold, Threshold::Percent(_))
+                    && metric.unit() == Unit::BytesPerSecond
+                {
+                    return Err(error(
+                        "network rates have no total, use an absolute threshold".to_string(),
+                    ));
+                }
+                Condition::Compare {
+                    metric,
+                    comparison: Comparison::parse(operator).map_err(error)?,
+                    threshold,
+                }
+            }
+            _ => {
+                return Err(error(
+                    "expected 'METRIC OP THRESHOLD [for DURATION]' or 'process \"NAME\" missing'"
+                        .to_string(),
+                ));
+            }
+        };
+
+        Ok(Self {
+            name: expression.trim().to_string(),
+            expression: expression.trim().to_string(),
+            condition,
+            duration,
+            resolve_after: Duration::ZERO,
+            severity: Severity::default(),
+        })
+    }
+
+    /// Checks the condition, returns `None` if the metric is not available.
+    /// The second value describes the current value of the metric.
+    fn check(&self, input: &AlertInput) -> Option<(bool, String)> {
+        match &self.condition {
+            Condition::ProcessMissing(name) => {
+                let missing = !input.processes.iter().any(|p| &p.name == name);
+                let description = if missing { "not running" } else { "running" };
+                Some((missing, description.to_string()))
+            }
+            Condition::Compare {
+                metric,
+                comparison,
+                threshold,
+            } => {
+                let (value, total) = metric.value(input)?;
+                match threshold {
+                    Threshold::Absolute(threshold) => Some((
+                        comparison.holds(value, *threshold),
+                        format_value(value, metric.unit()),
+                    )),
+                    Threshold::Percent(threshold) => {
+                        let total = total.filter(|total| *total > 0.0)?;
+                        let percent = value / total * 100.0;
+                        Some((
+                            comparison.holds(percent, *threshold),
+                            format_value(percent, Unit::Percent),
+                        ))
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Splits an expression at whitespace, double quoted parts may contain spaces
+fn tokenize(expression: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        let mut token = String::new();
+        if c == '"' {
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some(c) => token.push(c),
+                    None => return Err("unterminated quote".to_string()),
+                }
+            }
+        } else {
+            token.push(c);
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                token.push(c);
+            }
+        }
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// Parses a duration like `30s`, `5m`, `1h` or `7d`, a plain number is in seconds.
+///
+/// # Example
+/// ```
+/// use linux_dashboard::backend::alerts::parse_duration;
+/// assert_eq!(parse_duration("5m").unwrap().as_secs(), 300);
+/// assert_eq!(parse_duration("7d").unwrap().as_secs(), 604800);
+/// assert_eq!(parse_duration("10").unwrap().as_secs(), 10);
+/// assert!(parse_duration("1e300h").is_err());
+/// ```
+///
+pub fn parse_duration(text: &str) -> Result<Duration, String> {
+    let (number, multiplier) = match text.chars().last() {
+        Some('s') => (&text[..text.len() - 1], 1.0),
+        Some('m') => (&text[..text.len() - 1], 60.0),
+        Some('h') => (&text[..text.len() - 1], 3600.0),
+        Some('d') => (&text[..text.len() - 1], 86400.0),
+        _ => (text, 1.0),
+    };
+    number
+        .parse::<f64>()
+        .ok()
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds * multiplier).ok())
+        .ok_or_else(|| format!("invalid duration '{text}'"))
+}
+
+fn format_value(value: f64, unit: Unit) -> String {
+    match unit {
+        Unit::Percent => format!("{value:.1}%"),
+        Unit::Bytes => format_bytes(value.max(0.0) as u64),
+        Unit::BytesPerSecond => format_rate(value),
+    }
+}
+
+/// The data of one refresh the rules are evaluated against.
+pub struct AlertInput<'a> {
+    pub cpu: &'a CpuStats,
+    pub memory: &'a MemInfo,
+    pub disks: &'a [DiskInfo],
+    /// Current rates of the network interfaces
+    pub network: &'a [InterfaceThroughput],
+    pub processes: &'a [ProcessInfo],
+}
+
+/// Whether an event started or ended an alert.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertEventKind {
+    Fired,
+    Resolved,
+}
+
+impl AlertEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Fired => "fired",
+            Self::Resolved => "resolved",
+        }
+    }
+}
+
+/// An alert that fired or resolved.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AlertEvent {
+    pub time: DateTime<Local>,
+    pub kind: AlertEventKind,
+    pub rule: String,
+    /// The rule expression, e.g. `cpu.total > 90 for 30s`
+    pub expression: String,
+    pub severity: Severity,
+    /// The value of the metric at the time of the event
+    pub value: String,
+}
+
+impl AlertEvent {
+    /// Formats the event as a single line for display.
+    pub fn describe(&self) -> String {
+        let kind = match self.kind {
+            AlertEventKind::Fired => "FIRED",
+            AlertEventKind::Resolved => "resolved",
+        };
+        format!(
+            "{} {kind} {}: {}",
+            self.time.format("%Y-%m-%d %H:%M:%S"),
+            self.rule,
+            self.value
+        )
+    }
+}
+
+/// A rule whose alert is currently firing.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ActiveAlert {
+    pub rule: String,
+    pub severity: Severity,
+    pub since: DateTime<Local>,
+    /// The latest value of the metric
+    pub value: String,
+}
+
+impl fmt::Display for ActiveAlert {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}, since {})",
+            self.rule,
+            self.value,
+            self.since.format("%H:%M:%S")
+        )
+    }
+}
+
+/// The evaluation state of one rule.
+#[derive(Debug, Clone, Default)]
+struct RuleState {
+    /// Timestamp since which the condition holds
+    pending_since: Option<f64>,
+    /// Timestamp since which the condition of an active alert no longer holds
+    clear_since: Option<f64>,
+    /// Time the alert fired, while it is active
+    active_since: Option<DateTime<Local>>,
+    value: String,
+}
+
+/// AlertEngine evaluates the rules on every refresh and keeps the history of events.
+pub struct AlertEngine {
+    rules: Vec<AlertRule>,
+    states: Vec<RuleState>,
+    history: Vec<AlertEvent>,
+}
+
+impl AlertEngine {
+    pub fn new(rules: Vec<AlertRule>) -> Self {
+        Self {
+            states: vec![RuleState::default(); rules.len()],
+            rules,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn rules(&self) -> &[AlertRule] {
+        &self.rules
+    }
+
+    /// Forgets all state and history, e.g. after jumping within a replay.
+    pub fn reset(&mut self) {
+        self.states = vec![RuleState::default(); self.rules.len()];
+        self.history.clear();
+    }
+
+    /// Evaluates all rules against the data of a refresh at `timestamp`
+    /// (seconds since the Unix epoch) and returns the alerts that fired or resolved.
+    /// A metric that is not available counts as the condition not holding.
+    pub fn evaluate(&mut self, input: &AlertInput, timestamp: f64) -> Vec<AlertEvent> {
+        let mut events = Vec::new();
+        for (rule, state) in self.rules.iter().zip(self.states.iter_mut()) {
+            let (holds, value) = rule
+                .check(input)
+                .unwrap_or_else(|| (false, "no data".to_string()));
+            state.value = value;
+
+            let event_kind = if holds {
+                state.clear_since = None;
+                let since = *state.pending_since.get_or_insert(timestamp);
+                let fires = timestamp - since >= rule.duration.as_secs_f64();
+                if fires && state.active_since.is_none() {
+                    state.active_since = Some(local_time(timestamp));
+                    Some(AlertEventKind::Fired)
+                } else {
+                    None
+                }
+            } else {
+                state.pending_since = None;
+                match state.active_since {
+                    Some(_) => {
+                        let clear = *state.clear_since.get_or_insert(timestamp);
+                        if timestamp - clear >= rule.resolve_after.as_secs_f64() {
+                            state.active_since = None;
+                            state.clear_since = None;
+                            Some(AlertEventKind::Resolved)
+                        } else {
+                            None
+                        }
+                    }
+                    None => None,
+                }
+            };
+
+            if let Some(kind) = event_kind {
+                events.push(AlertEvent {
+                    time: local_time(timestamp),
+                    kind,
+                    rule: rule.name.clone(),
+                    expression: rule.expression.clone(),
+                    severity: rule.severity,
+                    value: state.value.clone(),
+                });
+            }
+        }
+
+        self.history.extend(events.iter().cloned());
+        if self.history.len() > MAX_EVENTS {
+            let excess = self.history.len() - MAX_EVENTS;
+            self.history.drain(..excess);
+        }
+        events
+    }
+
+    /// Returns the alerts that are currently firing, in the order of the rules.
+    pub fn active(&self) -> Vec<ActiveAlert> {
+        self.rules
+            .iter()
+            .zip(&self.states)
+            .filter_map(|(rule, state)| {
+                Some(ActiveAlert {
+                    rule: rule.name.clone(),
+                    severity: rule.severity,
+                    since: state.active_since?,
+                    value: state.value.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns all fired and resolved events, oldest first.
+    pub fn history(&self) -> &[AlertEvent] {
+        &self.history
+    }
+}
@@ -0,0 +1,318 @@
+//! This module lists the TCP and UDP sockets of the system, similar to `ss -tuanp`.
+//! Sockets are read from `net/tcp`, `net/tcp6`, `net/udp` and `net/udp6` below a configurable
+//! procfs root. The owning process of a socket is found by matching the socket inode with
+//! the `socket:[inode]` links in `<pid>/fd`. Sockets of processes of other users can only
+//! be resolved when the dashboard runs as root.
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+
+/// Transport protocol and IP version of a socket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Protocol {
+    Tcp,
+    Tcp6,
+    Udp,
+    Udp6,
+}
+
+impl Protocol {
+    /// File below `<proc>/net` that lists the sockets of this protocol.
+    fn file_name(&self) -> &'static str {
+        match self {
+            Protocol::Tcp => "tcp",
+            Protocol::Tcp6 => "tcp6",
+            Protocol::Udp => "udp",
+            Protocol::Udp6 => "udp6",
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        self.file_name()
+    }
+
+    fn is_udp(&self) -> bool {
+        matches!(self, Protocol::Udp | Protocol::Udp6)
+    }
+}
+
+/// A single socket with its owning process, if it could be resolved.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Connection {
+    pub protocol: Protocol,
+    pub local: SocketAddr,
+    pub remote: SocketAddr,
+    /// State as shown by `ss`, e.g. "LISTEN", "ESTABLISHED" or "UNCONN" for unconnected UDP sockets
+    pub state: String,
+    pub inode: u64,
+    pub pid: Option<u32>,
+    pub process: Option<String>,
+}
+
+/// Returns the name of a socket state from the hexadecimal state column.
+fn state_name(protocol: Protocol, state: u8) -> &'static str {
+    match (state, protocol.is_udp()) {
+        (0x01, _) => "ESTABLISHED",
+        (0x07, true) => "UNCONN",
+        (0x02, _) => "SYN_SENT",
+        (0x03, _) => "SYN_RECV",
+        (0x04, _) => "FIN_WAIT1",
+        (0x05, _) => "FIN_WAIT2",
+        (0x06, _) => "TIME_WAIT",
+        (0x07, _) => "CLOSE",
+        (0x08, _) => "CLOSE_WAIT",
+        (0x09, _) => "LAST_ACK",
+        (0x0A, _) => "LISTEN",
+        (0x0B, _) => "CLOSING",
+        (0x0C, _) => "NEW_SYN_RECV",
+        _ => "UNKNOWN",
+    }
+}
+
+// Parses an address like "0100007F:0035" (IPv4) or a 32 digit IPv6 address followed by the port.
+// The kernel prints the address as 32 bit words in host byte order, the port in network order.
+fn parse_address(field: &str) -> Option<SocketAddr> {
+    let (address, port) = field.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    let word = |hex: &str| u32::from_str_radix(hex, 16).ok().map(u32::to_ne_bytes);
+
+    let ip = match address.len() {
+        8 => IpAddr::V4(Ipv4Addr::from(word(address)?)),
+        32 => {
+            let mut bytes = [0u8; 16];
+            for (i, chunk) in bytes.chunks_mut(4).enumerate() {
+                chunk.copy_from_slice(&word(address.get(i * 8..i * 8 + 8)?)?);
+            }
+            IpAddr::V6(Ipv6Addr::from(bytes))
+        }
+        _ => return None,
+    };
+    Some(SocketAddr::new(ip, port))
+}
+
+/// Parses the content of a `/proc/net/{tcp,tcp6,udp,udp6}` file.
+/// The header line and malformed lines are skipped, the owning processes are not resolved.
+pub fn parse_proc_net(content: &str, protocol: Protocol) -> Vec<Connection> {
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            Some(Connection {
+                protocol,
+                local: parse_address(fields.get(1)?)?,
+                remote: parse_address(fields.get(2)?)?,
+                state: state_name(protocol, u8::from_str_radix(fields.get(3)?, 16).ok()?)
+                    .to_string(),
+                inode: fields.get(9)?.parse().ok()?,
+                pid: None,
+                process: None,
+            })
+        })
+        .collect()
+}
+
+/// ConnectionReader reads the sockets below a procfs root (usually `/proc`).
+pub struct ConnectionReader {
+    proc_root: PathBuf,
+}
+
+impl Default for ConnectionReader {
+    fn default() -> Self {
+        Self::new("/proc")
+    }
+}
+
+impl ConnectionReader {
+    pub fn new(proc_root: impl Into<PathBuf>) -> Self {
+        Self {
+            proc_root: proc_root.into(),
+        }
+    }
+
+    /// Reads all TCP and UDP sockets and resolves their owning processes.
+    /// Missing files (e.g. no IPv6 support) are skipped.
+    pub fn read_connections(&self) -> Vec<Connection> {
+        let mut connections = Vec::new();
+        for protocol in [Protocol::Tcp, Protocol::Tcp6, Protocol::Udp, Protocol::Udp6] {
+            let path = self.proc_root.join("net").join(protocol.file_name());
+            if let Ok(content) = fs::read_to_string(path) {
+                connections.extend(parse_proc_net(&content, protocol));
+            }
+        }
+
+        let owners = socket_owners(&self.proc_root);
+        for connection in &mut connections {
+            if let Some((pid, name)) = owners.get(&connection.inode) {
+                connection.pid = Some(*pid);
+                connection.process = Some(name.clone());
+            }
+        }
+        connections
+    }
+}
+
+// Maps socket inodes to the PID and name of the process holding them,
+// by reading the `socket:[inode]` links in every `<pid>/fd` directory.
+fn socket_owners(proc_root: &Path) -> HashMap<u64, (u32, String)> {
+    let mut owners = HashMap::new();
+    let Ok(entries) = fs::read_dir(proc_root) else {
+        return owners;
+    };
+    for entry in entries.flatten() {
+        let Some(pid) = entry
+            .file_name()
+            .to_str()
+            .and_then(|n| n.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        // fd directories of other users' processes are not readable without root
+        let Ok(fds) = fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        let name = fs::read_to_string(entry.path().join("comm"))
+            .map(|comm| comm.trim().to_string())
+            .unwrap_or_default();
+        for fd in fds.flatten() {
+            let Ok(target) = fs::read_link(fd.path()) else {
+                continue;
+            };
+            let inode = target
+                .to_str()
+                .and_then(|t| t.strip_prefix("socket:["))
+                .and_then(|t| t.strip_suffix(']'))
+                .and_then(|t| t.parse::<u64>().ok());
+            if let Some(inode) = inode {
+                owners.entry(inode).or_insert_with(|| (pid, name.clone()));
+            }
+        }
+    }
+    owners
+}
+
+/// Filter for the connections view.
+/// The filter text consists of space separated terms, all of which must match:
+/// - `state:<state>` matches the socket state, e.g. `state:listen`
+/// - `port:<port>` matches the local or remote port
+/// - any other term matches the process name or PID
+///
+/// # Example
+/// ```
+/// use linux_dashboard::backend::connections::ConnectionFilter;
+/// let filter = ConnectionFilter::parse("state:listen port:22 sshd");
+/// assert_eq!(filter.states, vec!["LISTEN"]);
+/// assert_eq!(filter.ports, vec![22]);
+/// assert_eq!(filter.processes, vec!["sshd"]);
+/// ```
+///
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConnectionFilter {
+    pub states: Vec<String>,
+    pub ports: Vec<u16>,
+    pub processes: Vec<String>,
+}
+
+impl ConnectionFilter {
+    pub fn parse(text: &str) -> Self {
+        let mut filter = Self::default();
+        for term in text.split_whitespace() {
+            if let Some(state) = term.strip_prefix("state:") {
+                filter.states.push(state.to_uppercase());
+            } else if let Some(port) = term.strip_prefix("port:").and_then(|p| p.parse().ok()) {
+                filter.ports.push(port);
+            } else {
+                filter.processes.push(term.to_lowercase());
+            }
+        }
+        filter
+    }
+
+    pub fn matches(&self, connection: &Connection) -> bool {
+        let state = self.states.iter().all(|s| connection.state == *s);
+        let port = self
+            .ports
+            .iter()
+            .all(|p| connection.local.port() == *p || connection.remote.port() == *p);
+        let process = self.processes.iter().all(|term| {
+            let name = connection.process.as_deref().unwrap_or_default();
+            name.to_lowercase().contains(term)
+                || connection.pid.is_some_and(|pid| pid.to_string() == *term)
+        });
+        state && port && process
+    }
+}
+
+/// Where a listening socket can be reached from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BindScope {
+    /// Bound to all addresses (0.0.0.0 or ::), reachable from the network
+    AllInterfaces,
+    /// Bound to a specific non-loopback address
+    Address,
+    /// Bound to loopback, only reachable from this host
+    Localhost,
+}
+
+impl BindScope {
+    pub fn of(address: &SocketAddr) -> Self {
+        let ip = address.ip();
+        if ip.is_unspecified() {
+            BindScope::AllInterfaces
+        } else if ip.is_loopback() {
+            BindScope::Localhost
+        } else {
+            BindScope::Address
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BindScope::AllInterfaces => "all interfaces",
+            BindScope::Address => "address",
+            BindScope::Localhost => "localhost",
+        }
+    }
+}
+
+/// A listening TCP socket or a bound, unconnected UDP socket.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ListeningPort {
+    pub protocol: Protocol,
+    pub address: SocketAddr,
+    pub scope: BindScope,
+    pub pid: Option<u32>,
+    pub process: Option<String>,
+}
+
+/// Returns the listening TCP and unconnected UDP sockets of `connections`.
+/// Sockets bound to all interfaces come first, then the ones bound to a specific address,
+/// then localhost; within a scope they are sorted by port.
+/// Duplicates (e.g. several `SO_REUSEPORT` sockets of one process) are listed once.
+pub fn listening_ports(connections: &[Connection]) -> Vec<ListeningPort> {
+    let mut ports: Vec<ListeningPort> = connections
+        .iter()
+        .filter(|connection| matches!(connection.state.as_str(), "LISTEN" | "UNCONN"))
+        .map(|connection| ListeningPort {
+            protocol: connection.protocol,
+            address: connection.local,
+            scope: BindScope::of(&connection.local),
+            pid: connection.pid,
+            process: connection.process.clone(),
+        })
+        .collect();
+    ports.sort_by_key(|port| {
+        (
+            port.scope,
+            port.address.port(),
+            port.protocol.as_str(),
+            port.pid,
+        )
+    });
+    ports.dedup();
+    ports
+}
@@ -17,7 +17,7 @@ pub fn byte_to_gib(bytes: u64) -> f64 {
 /// ```
 /// use linux_dashboard::backend::converter::format_bytes;
 /// assert_eq!(format_bytes(512), "512 B");
-/// assert_eq!(format_bytes(1048576), "1.00 MB");
+/// assert_eq!(format_bytes(1048576), "1.0 MB");
 /// ```
 ///
 pub fn format_bytes(bytes: u64) -> String {
@@ -36,3 +36,19 @@ pub fn format_bytes(bytes: u64) -> String {
         format!("{bytes} B")
     }
 }
+
+/// Formats a transfer rate in bytes per second into a human-readable string (B/s, KB/s, MB/s, GB/s).
+/// # Example
+/// ```
+/// use linux_dashboard::backend::converter::format_rate;
+/// assert_eq!(format_rate(512.0), "512 B/s");
+/// assert_eq!(format_rate(1536.0), "2 KB/s");
+/// assert_eq!(format_rate(1572864.0), "1.5 MB/s");
+/// ```
+///
+pub fn format_rate(bytes_per_second: f64) -> String {
+    format!(
+        "{}/s",
+        format_bytes(bytes_per_second.max(0.0).round() as u64)
+    )
+}
@@ -1,6 +1,56 @@
 //! This module fecthes CPU resource informations
 
 use crate::backend::system_info::SystemInfo;
+use serde::{Deserialize, Serialize};
+
+/// CPU usage of the system at one point in time.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CpuStats {
+    pub name: String,
+    /// Usage of all cores in percent
+    pub total_usage: f32,
+    /// Usage of every core in percent, in core order
+    pub cores: Vec<f32>,
+}
+
+impl CpuStats {
+    /// Collects the CPU usage from the system.
+    ///
+    /// # Example
+    /// ```
+    /// use linux_dashboard::backend::cpu::CpuStats;
+    /// use sysinfo::System;
+    /// let sys = System::new_all();
+    /// let stats = CpuStats::collect(&sys);
+    /// assert_eq!(stats.cores.len(), sys.cpus().len());
+    /// ```
+    ///
+    pub fn collect(sys: &impl SystemInfo) -> Self {
+        let cpus = sys.get_cpus();
+        Self {
+            name: cpus
+                .first()
+                .map(|cpu| cpu.brand.clone())
+                .unwrap_or_else(|| "Unknown CPU".to_string()),
+            total_usage: sys.global_cpu_usage(),
+            cores: cpus.iter().map(|cpu| cpu.usage).collect(),
+        }
+    }
+
+    /// Formats the usage of every core, one line per core.
+    pub fn format_cores(&self) -> String {
+        self.cores
+            .iter()
+            .enumerate()
+            .map(|(i, usage)| format!("CPU {:02}: {:>5.2}%\n", i, usage))
+            .collect::<String>()
+    }
+
+    /// Formats the total usage.
+    pub fn format_total(&self) -> String {
+        format!("Total Usage: {:.2}% ", self.total_usage)
+    }
+}
 
 /// Returns the full CPU-utilization of all cores formatted as string.  
 /// Shows CPU Core usage.  
@@ -15,11 +65,12 @@ use crate::backend::system_info::SystemInfo;
 /// ```
 ///
 pub fn format_cpu_usage(sys: &impl SystemInfo) -> String {
-    sys.get_cpus()
-        .iter()
-        .enumerate()
-        .map(|(i, cpu)| format!("CPU {:02}: {:>5.2}%\n", i, cpu.usage))
-        .collect::<String>()
+    let cores: Vec<f32> = sys.get_cpus().iter().map(|cpu| cpu.usage).collect();
+    CpuStats {
+        cores,
+        ..Default::default()
+    }
+    .format_cores()
 }
 
 /// This function returns the total CPU usage of the system as a formatted string
@@ -34,8 +85,11 @@ pub fn format_cpu_usage(sys: &impl SystemInfo) -> String {
 /// ```
 ///
 pub fn format_total_cpu_usage(sys: &impl SystemInfo) -> String {
-    let total_cpu_usage = format!("Total Usage: {:.2}% ", sys.global_cpu_usage());
-    total_cpu_usage
+    CpuStats {
+        total_usage: sys.global_cpu_usage(),
+        ..Default::default()
+    }
+    .format_total()
 }
 
 /// Returns the CPU name of the system
@@ -46,7 +100,8 @@ pub fn format_total_cpu_usage(sys: &impl SystemInfo) -> String {
 /// use sysinfo::System;
 /// let sys = System::new_all();
 /// let output = format_cpu_name(&sys);
-/// assert!(output.contains("CPU Name"));
+/// let expected = sys.cpus().first().map_or("Unknown CPU", |cpu| cpu.brand());
+/// assert_eq!(output, expected);
 /// ```
 ///
 pub fn format_cpu_name(sys: &impl SystemInfo) -> String {
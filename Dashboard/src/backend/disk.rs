@@ -1,8 +1,58 @@
 //! This module fetches Disk Resource Information
 
 use super::converter::byte_to_gib;
+use serde::{Deserialize, Serialize};
 use sysinfo::Disks;
 
+/// Space of a mounted disk in bytes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiskInfo {
+    pub name: String,
+    pub mount_point: String,
+    pub file_system: String,
+    pub total: u64,
+    pub available: u64,
+}
+
+/// Returns all mounted disks.
+///
+/// # Example
+/// ```
+/// use linux_dashboard::backend::disk::list_disks;
+/// for disk in list_disks() {
+///     assert!(disk.available <= disk.total);
+/// }
+/// ```
+///
+pub fn list_disks() -> Vec<DiskInfo> {
+    Disks::new_with_refreshed_list()
+        .list()
+        .iter()
+        .map(|disk| DiskInfo {
+            name: disk.name().to_string_lossy().to_string(),
+            mount_point: disk.mount_point().to_string_lossy().to_string(),
+            file_system: disk.file_system().to_string_lossy().to_string(),
+            total: disk.total_space(),
+            available: disk.available_space(),
+        })
+        .collect()
+}
+
+/// Formats the total and available space of the given disks, one line per disk.
+pub fn format_disks(disks: &[DiskInfo]) -> String {
+    disks
+        .iter()
+        .map(|disk| {
+            format!(
+                "[{:?}] Total Space: {:.2} GB | Available Space: {:.2} GB\n",
+                disk.name,
+                byte_to_gib(disk.total),
+                byte_to_gib(disk.available)
+            )
+        })
+        .collect()
+}
+
 /// Returns disk names, the total space of the disk and the available space of the disk formatted as a string.
 /// # Example
 /// ```
@@ -12,18 +62,5 @@ use sysinfo::Disks;
 /// ```
 ///
 pub fn format_disk_information() -> String {
-    let mut result = String::new();
-    let disks = Disks::new_with_refreshed_list();
-
-    for disk in disks.list() {
-        let disk_info = format!(
-            "[{:?}] Total Space: {:.2} GB | Available Space: {:.2} GB\n",
-            disk.name(),
-            byte_to_gib(disk.total_space()),
-            byte_to_gib(disk.available_space())
-        );
-        result.push_str(&disk_info);
-    }
-
-    result
+    format_disks(&list_disks())
 }
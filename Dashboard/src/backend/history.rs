@@ -0,0 +1,145 @@
+//! This module provides a time series with a retention window.
+//! Points are stored with real timestamps (seconds since the Unix epoch), so the meaning of
+//! the time axis does not depend on the refresh interval. Points older than the retention
+//! window are dropped, and older points are down-sampled into buckets so the memory use
+//! stays bounded even with short refresh intervals and long retention windows.
+use chrono::{DateTime, Local, TimeZone};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Number of newest points that are always kept at full resolution.
+pub const FULL_RESOLUTION_POINTS: usize = 120;
+
+/// Number of buckets the remaining part of the retention window is divided into.
+pub const MAX_BUCKETS: usize = 240;
+
+type DataPoint = (f64, f64); // Tuple for time and value
+
+/// A point that may represent the average of several merged samples.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct WeightedPoint {
+    time: f64,
+    value: f64,
+    weight: u32,
+}
+
+/// A series of (timestamp, value) points.
+/// At most `FULL_RESOLUTION_POINTS + MAX_BUCKETS + 1` points are kept after `compact`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TimeSeries {
+    points: Vec<WeightedPoint>,
+}
+
+impl TimeSeries {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a sample. Samples are expected in chronological order.
+    pub fn push(&mut self, time: f64, value: f64) {
+        self.points.push(WeightedPoint {
+            time,
+            value,
+            weight: 1,
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Returns the newest point.
+    pub fn last(&self) -> Option<DataPoint> {
+        self.points.last().map(|p| (p.time, p.value))
+    }
+
+    /// Returns all points as (timestamp, value) tuples, oldest first.
+    pub fn points(&self) -> Vec<DataPoint> {
+        self.points.iter().map(|p| (p.time, p.value)).collect()
+    }
+
+    /// Drops the points older than `now - retention` and down-samples the points
+    /// that are not among the newest `FULL_RESOLUTION_POINTS`:
+    /// points falling into the same bucket of `retention / MAX_BUCKETS` seconds
+    /// are merged into their weighted average.
+    pub fn compact(&mut self, now: f64, retention: Duration) {
+        let oldest = now - retention.as_secs_f64();
+        self.points.retain(|p| p.time >= oldest);
+
+        if self.points.len() <= FULL_RESOLUTION_POINTS {
+            return;
+        }
+        let bucket_width = (retention.as_secs_f64() / MAX_BUCKETS as f64).max(f64::EPSILON);
+        let split = self.points.len() - FULL_RESOLUTION_POINTS;
+        let recent = self.points.split_off(split);
+
+        let mut merged: Vec<WeightedPoint> = Vec::with_capacity(MAX_BUCKETS + 1);
+        for point in self.points.drain(..) {
+            let bucket = (point.time / bucket_width).floor();
+            match merged.last_mut() {
+                Some(last) if (last.time / bucket_width).floor() == bucket => {
+                    let weight = last.weight + point.weight;
+                    last.time = (last.time * last.weight as f64 + point.time * point.weight as f64)
+                        / weight as f64;
+                    last.value = (last.value * last.weight as f64
+                        + point.value * point.weight as f64)
+                        / weight as f64;
+                    last.weight = weight;
+                }
+                _ => merged.push(point),
+            }
+        }
+        merged.extend(recent);
+        self.points = merged;
+    }
+}
+
+/// Returns the current time as seconds since the Unix epoch.
+pub fn unix_now() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0)
+}
+
+/// Formats a retention window for display, e.g. "5 min", "1 h" or "7 d".
+///
+/// # Example
+/// ```
+/// use linux_dashboard::backend::history::format_retention;
+/// use std::time::Duration;
+/// assert_eq!(format_retention(Duration::from_secs(300)), "5 min");
+/// assert_eq!(format_retention(Duration::from_secs(3600)), "1 h");
+/// assert_eq!(format_retention(Duration::from_secs(7 * 86400)), "7 d");
+/// ```
+///
+pub fn format_retention(retention: Duration) -> String {
+    let secs = retention.as_secs();
+    if secs >= 86400 && secs.is_multiple_of(86400) {
+        format!("{} d", secs / 86400)
+    } else if secs >= 3600 && secs.is_multiple_of(3600) {
+        format!("{} h", secs / 3600)
+    } else if secs >= 60 {
+        format!("{} min", secs / 60)
+    } else {
+        format!("{secs} s")
+    }
+}
+
+/// Converts seconds since the Unix epoch into local time.
+///
+/// # Example
+/// ```
+/// use linux_dashboard::backend::history::local_time;
+/// assert_eq!(local_time(86400.5).timestamp(), 86400);
+/// ```
+///
+pub fn local_time(timestamp: f64) -> DateTime<Local> {
+    Local
+        .timestamp_opt(timestamp.floor() as i64, 0)
+        .single()
+        .unwrap_or_else(Local::now)
+}
@@ -2,27 +2,10 @@
 
 use std::env;
 
-use ratatui::prelude::Constraint;
-use ratatui::widgets::{Cell, Row, Table};
+use serde::{Deserialize, Serialize};
 
-/// Returns the system name of the Linux Distribution, kernel version of the Linux Distribution,  
-/// the OS Version Linux is currently on and the hostname of the system formatted as a string.  
-/// This function also returns a table with:  
-/// ->system name  
-/// -> kernel version  
-/// -> OS version  
-/// -> host name  
-/// of the Linux system.  
-/// It uses the sysinfo crate to fetch the system information and formats it into a table.  
-/// The table has two columns: one for the label and one for the value.  
-///
-/// # Example
-/// ```
-/// use linux_dashboard::backend::host::host_info_table;
-/// let output = host_info_table();
-/// assert!(output.contains("System Name"));
-/// ```
 /// Cached host information that doesn't change at runtime.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HostInfo {
     pub system_name: String,
     pub kernel_version: String,
@@ -30,47 +13,46 @@ pub struct HostInfo {
     pub host_name: String,
 }
 
+impl Default for HostInfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl HostInfo {
     pub fn new() -> Self {
         Self {
             system_name: sysinfo::System::name().unwrap_or_else(|| "Unknown System".to_string()),
             kernel_version: sysinfo::System::kernel_version()
                 .unwrap_or_else(|| "Unknown Kernel".to_string()),
-            os_version: sysinfo::System::os_version()
-                .unwrap_or_else(|| "Unknown OS".to_string()),
-            host_name: sysinfo::System::host_name()
-                .unwrap_or_else(|| "Unknown Host".to_string()),
+            os_version: sysinfo::System::os_version().unwrap_or_else(|| "Unknown OS".to_string()),
+            host_name: sysinfo::System::host_name().unwrap_or_else(|| "Unknown Host".to_string()),
         }
     }
-
-    pub fn to_table(&self) -> Table<'static> {
-        let rows = vec![
-            Row::new(vec![
-                Cell::from("System Name"),
-                Cell::from(self.system_name.clone()),
-            ]),
-            Row::new(vec![
-                Cell::from("Kernel Version"),
-                Cell::from(self.kernel_version.clone()),
-            ]),
-            Row::new(vec![
-                Cell::from("OS Version"),
-                Cell::from(self.os_version.clone()),
-            ]),
-            Row::new(vec![
-                Cell::from("Host Name"),
-                Cell::from(self.host_name.clone()),
-            ]),
-        ];
-        let widths = [Constraint::Length(20), Constraint::Length(30)];
-        Table::new(rows, widths)
-            .column_spacing(1)
-            .style(ratatui::style::Style::default().fg(ratatui::style::Color::White))
-    }
 }
 
-pub fn host_info_table() -> Table<'static> {
-    HostInfo::new().to_table()
+/// Formats the system uptime in seconds in its largest unit.
+///
+/// # Example
+/// ```
+/// use linux_dashboard::backend::host::format_uptime;
+/// assert_eq!(format_uptime(42), "Uptime: 42 seconds ");
+/// assert_eq!(format_uptime(7200), "Uptime: 2 hours ");
+/// ```
+///
+pub fn format_uptime(uptime: u64) -> String {
+    if uptime < 60 {
+        format!("Uptime: {uptime} seconds ")
+    } else if uptime < 3600 {
+        let minutes = uptime / 60;
+        format!("Uptime: {minutes} minutes ")
+    } else if uptime < 86400 {
+        let hours = uptime / 3600;
+        format!("Uptime: {hours} hours ")
+    } else {
+        let days = uptime / 86400;
+        format!("Uptime: {days} days ")
+    }
 }
 
 /// Returns the current user of the system
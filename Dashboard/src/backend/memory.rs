@@ -1,58 +1,195 @@
 //! This module fetches memory resource information
 
-use super::converter::byte_to_gib;
-use ratatui::prelude::Constraint;
-use ratatui::style::Color;
-use ratatui::style::Style;
-use ratatui::widgets::Cell;
-use ratatui::widgets::{Row, Table};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
 use sysinfo::System;
 
-/// Returns:  
-/// -> Total Memory...  
-/// -> Used Memory...  
-/// -> Total Swap Memory...  
-/// -> Used Swap Memory...  
-/// information in a table format.  
+/// Detailed memory breakdown as reported by `/proc/meminfo`.
+/// All values are stored in bytes, except the hugepage counters which are page counts.
+/// On systems without `/proc/meminfo` only the fields sysinfo knows about are filled,
+/// the remaining fields stay at zero.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemInfo {
+    pub total: u64,
+    pub free: u64,
+    pub available: u64,
+    pub buffers: u64,
+    pub cached: u64,
+    pub shared: u64,
+    pub slab_reclaimable: u64,
+    pub slab_unreclaimable: u64,
+    pub dirty: u64,
+    pub writeback: u64,
+    pub committed_as: u64,
+    pub commit_limit: u64,
+    pub hugepages_total: u64,
+    pub hugepages_free: u64,
+    pub hugepage_size: u64,
+    pub swap_total: u64,
+    pub swap_free: u64,
+}
+
+/// Category of a segment of the stacked RAM bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemorySegmentKind {
+    Used,
+    Buffers,
+    Cache,
+    Free,
+}
+
+impl MemorySegmentKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            MemorySegmentKind::Used => "Used",
+            MemorySegmentKind::Buffers => "Buffers",
+            MemorySegmentKind::Cache => "Cache",
+            MemorySegmentKind::Free => "Free",
+        }
+    }
+}
+
+/// One segment of the stacked RAM bar.
+pub struct MemorySegment {
+    pub kind: MemorySegmentKind,
+    pub bytes: u64,
+}
+
+impl MemInfo {
+    /// Reads `/proc/meminfo` and falls back to the values sysinfo provides
+    /// if the file is not available (e.g. on non-Linux systems).
+    pub fn collect(sys: &System) -> Self {
+        Self::read_from(Path::new("/proc/meminfo")).unwrap_or_else(|| Self::from_system(sys))
+    }
+
+    /// Reads and parses a meminfo file from the given path.
+    pub fn read_from(path: &Path) -> Option<Self> {
+        fs::read_to_string(path)
+            .ok()
+            .map(|content| parse_meminfo(&content))
+    }
+
+    /// Builds a reduced breakdown from sysinfo, used when `/proc/meminfo` cannot be read.
+    pub fn from_system(sys: &System) -> Self {
+        Self {
+            total: sys.total_memory(),
+            free: sys.free_memory(),
+            available: sys.available_memory(),
+            swap_total: sys.total_swap(),
+            swap_free: sys.free_swap(),
+            ..Self::default()
+        }
+    }
+
+    /// Page cache including reclaimable slab, the same way `free` reports "buff/cache"
+    /// without the buffers.
+    pub fn cache(&self) -> u64 {
+        self.cached + self.slab_reclaimable
+    }
+
+    /// Memory actually used by applications and the kernel, excluding
+    /// free memory, buffers and page cache.
+    pub fn used(&self) -> u64 {
+        self.total
+            .saturating_sub(self.free)
+            .saturating_sub(self.buffers)
+            .saturating_sub(self.cache())
+    }
+
+    pub fn swap_used(&self) -> u64 {
+        self.swap_total.saturating_sub(self.swap_free)
+    }
+
+    /// Returns the given value as percentage of the total memory.
+    pub fn percent_of_total(&self, bytes: u64) -> f64 {
+        percent(bytes, self.total)
+    }
+
+    /// Splits the total memory into the categories shown in the stacked bar.
+    /// The segments always add up to the total memory.
+    pub fn segments(&self) -> Vec<MemorySegment> {
+        vec![
+            MemorySegment {
+                kind: MemorySegmentKind::Used,
+                bytes: self.used(),
+            },
+            MemorySegment {
+                kind: MemorySegmentKind::Buffers,
+                bytes: self.buffers,
+            },
+            MemorySegment {
+                kind: MemorySegmentKind::Cache,
+                bytes: self.cache(),
+            },
+            MemorySegment {
+                kind: MemorySegmentKind::Free,
+                bytes: self.free.min(self.total),
+            },
+        ]
+    }
+}
+
+/// Returns `part` as percentage of `whole`, 0 if `whole` is 0.
+pub fn percent(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64 * 100.0
+    }
+}
+
+/// Parses the content of `/proc/meminfo`.
+/// Unknown keys are ignored, missing keys stay at zero.
+///
 /// # Example
 /// ```
-/// use linux_dashboard::backend::memory::ram_info_table;
-/// use sysinfo::System;
-/// let sys = System::new_all();
-/// let output = ram_info_table(&sys);
-/// assert!(output.contains("Total Memory"));
+/// use linux_dashboard::backend::memory::parse_meminfo;
+/// let info = parse_meminfo("MemTotal: 2048 kB\nMemFree: 1024 kB\nHugePages_Total: 4\n");
+/// assert_eq!(info.total, 2048 * 1024);
+/// assert_eq!(info.free, 1024 * 1024);
+/// assert_eq!(info.hugepages_total, 4);
 /// ```
 ///
-pub fn ram_info_table(sys: &System) -> Table<'static> {
-    let rows = vec![
-        Row::new(vec![
-            Cell::from("Total"),
-            Cell::from("Memory"),
-            Cell::from(format!("{:.2} GB", byte_to_gib(sys.total_memory()))),
-        ]),
-        Row::new(vec![
-            Cell::from("Used"),
-            Cell::from("Memory"),
-            Cell::from(format!("{:.2} GB", byte_to_gib(sys.used_memory()))),
-        ]),
-        Row::new(vec![
-            Cell::from("Total"),
-            Cell::from("Swap"),
-            Cell::from(format!("{:.2} GB", byte_to_gib(sys.total_swap()))),
-        ]),
-        Row::new(vec![
-            Cell::from("Used"),
-            Cell::from("Swap"),
-            Cell::from(format!("{:.2} GB", byte_to_gib(sys.used_swap()))),
-        ]),
-    ];
-    let widths = [
-        Constraint::Length(5),
-        Constraint::Length(6),
-        Constraint::Length(10),
-        Constraint::Length(10),
-    ];
-    Table::new(rows, widths)
-        .column_spacing(1)
-        .style(Style::default().fg(Color::White))
+pub fn parse_meminfo(content: &str) -> MemInfo {
+    let mut info = MemInfo::default();
+
+    for line in content.lines() {
+        let Some((key, rest)) = line.split_once(':') else {
+            continue;
+        };
+        let mut parts = rest.split_whitespace();
+        let Some(value) = parts.next().and_then(|v| v.parse::<u64>().ok()) else {
+            continue;
+        };
+        // Values with a "kB" suffix are converted to bytes, plain values are counters
+        let value = match parts.next() {
+            Some("kB") => value * 1024,
+            _ => value,
+        };
+
+        let field = match key.trim() {
+            "MemTotal" => &mut info.total,
+            "MemFree" => &mut info.free,
+            "MemAvailable" => &mut info.available,
+            "Buffers" => &mut info.buffers,
+            "Cached" => &mut info.cached,
+            "Shmem" => &mut info.shared,
+            "SReclaimable" => &mut info.slab_reclaimable,
+            "SUnreclaim" => &mut info.slab_unreclaimable,
+            "Dirty" => &mut info.dirty,
+            "Writeback" => &mut info.writeback,
+            "Committed_AS" => &mut info.committed_as,
+            "CommitLimit" => &mut info.commit_limit,
+            "HugePages_Total" => &mut info.hugepages_total,
+            "HugePages_Free" => &mut info.hugepages_free,
+            "Hugepagesize" => &mut info.hugepage_size,
+            "SwapTotal" => &mut info.swap_total,
+            "SwapFree" => &mut info.swap_free,
+            _ => continue,
+        };
+        *field = value;
+    }
+
+    info
 }
@@ -0,0 +1,243 @@
+//! This module keeps a rolling history of memory and swap usage
+//! together with the swap-in/swap-out and page fault rates from `/proc/vmstat`.
+//! The UI draws two charts from it: one for memory/swap usage and one for swap activity.
+use super::converter::byte_to_gib;
+use super::history::{TimeSeries, unix_now};
+use super::memory::MemInfo;
+use super::network::DEFAULT_RETENTION;
+use super::vmstat::VmStat;
+use serde::Serialize;
+use std::time::Duration;
+
+type DataPoint = (f64, f64); // Tuple for time in seconds and value
+type DataHistory = Vec<DataPoint>;
+
+/// Maximum number of data points kept per rate series.
+const MAX_POINTS: usize = 50;
+
+/// Rates derived from two consecutive `/proc/vmstat` samples, per second.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct VmRates {
+    pub swap_in: f64,
+    pub swap_out: f64,
+    pub page_faults: f64,
+    pub major_faults: f64,
+}
+
+/// Memory and swap usage in bytes at one point in time, e.g. read from the on-disk history.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct UsagePoint {
+    /// Seconds since the Unix epoch
+    pub timestamp: f64,
+    pub used: u64,
+    pub available: u64,
+    pub swap_used: u64,
+}
+
+/// Memory used, memory available and swap used in GB.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct UsageHistory {
+    used: TimeSeries,
+    available: TimeSeries,
+    swap_used: TimeSeries,
+}
+
+impl UsageHistory {
+    fn push(&mut self, point: &UsagePoint) {
+        self.used.push(point.timestamp, byte_to_gib(point.used));
+        self.available
+            .push(point.timestamp, byte_to_gib(point.available));
+        self.swap_used
+            .push(point.timestamp, byte_to_gib(point.swap_used));
+    }
+
+    fn compact(&mut self, now: f64, retention: Duration) {
+        for series in [&mut self.used, &mut self.available, &mut self.swap_used] {
+            series.compact(now, retention);
+        }
+    }
+}
+
+/// MemoryHistory records memory used/available and swap used over time,
+/// as well as the swap and page fault rates.
+/// Memory values are stored in GB, rates in pages (or faults) per second.
+/// The usage is kept for the retention window with real timestamps (seconds since the
+/// Unix epoch), like the network history, and can be seeded with an earlier history.
+/// The rates keep the last `MAX_POINTS` samples.
+/// Swap-out rates are stored as negative values so they are drawn below the axis,
+/// the same way the network chart draws the upload.
+pub struct MemoryHistory {
+    last_vmstat: Option<(f64, VmStat)>,
+    usage: UsageHistory,
+    /// The usage before the live history
+    stored_usage: UsageHistory,
+    retention: Duration,
+    last_update: f64,
+    swap_in: DataHistory,
+    swap_out: DataHistory,
+    major_faults: DataHistory,
+    latest_rates: VmRates,
+    memory_total: f64,
+}
+
+impl Default for MemoryHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryHistory {
+    /// Creates an empty history with the default retention window of the network chart.
+    pub fn new() -> Self {
+        Self {
+            last_vmstat: None,
+            usage: UsageHistory::default(),
+            stored_usage: UsageHistory::default(),
+            retention: DEFAULT_RETENTION,
+            last_update: unix_now(),
+            swap_in: Vec::new(),
+            swap_out: Vec::new(),
+            major_faults: Vec::new(),
+            latest_rates: VmRates::default(),
+            memory_total: 0.0,
+        }
+    }
+
+    /// Records a new sample taken now.
+    pub fn update(&mut self, mem: &MemInfo, vmstat: Option<VmStat>) {
+        self.update_at(mem, vmstat, unix_now());
+    }
+
+    /// Records a new sample taken at the given timestamp (seconds since the Unix epoch).
+    /// Rates are computed from the elapsed time since the previous vmstat sample,
+    /// so they are correct regardless of the refresh interval.
+    /// Counters that went backwards (e.g. after a counter reset) yield a rate of zero.
+    pub fn update_at(&mut self, mem: &MemInfo, vmstat: Option<VmStat>, time: f64) {
+        self.memory_total = byte_to_gib(mem.total);
+        self.last_update = time;
+        self.usage.push(&UsagePoint {
+            timestamp: time,
+            used: mem.used(),
+            available: mem.available,
+            swap_used: mem.swap_used(),
+        });
+        self.usage.compact(time, self.retention);
+
+        let Some(current) = vmstat else {
+            return;
+        };
+        if let Some((last_time, last)) = self.last_vmstat {
+            let elapsed = time - last_time;
+            if elapsed > 0.0 {
+                let rate = |new: u64, old: u64| new.saturating_sub(old) as f64 / elapsed;
+                self.latest_rates = VmRates {
+                    swap_in: rate(current.pswpin, last.pswpin),
+                    swap_out: rate(current.pswpout, last.pswpout),
+                    page_faults: rate(current.pgfault, last.pgfault),
+                    major_faults: rate(current.pgmajfault, last.pgmajfault),
+                };
+                push_point(&mut self.swap_in, (time, self.latest_rates.swap_in));
+                push_point(&mut self.swap_out, (time, -self.latest_rates.swap_out));
+                push_point(
+                    &mut self.major_faults,
+                    (time, self.latest_rates.major_faults),
+                );
+            }
+        }
+        self.last_vmstat = Some((time, current));
+    }
+
+    /// Replaces the usage before the live history, e.g. with the on-disk history.
+    /// Points not older than the live history are left out.
+    pub fn seed_history(&mut self, points: &[UsagePoint]) {
+        let live_start = self
+            .usage
+            .used
+            .points()
+            .first()
+            .map_or(f64::INFINITY, |(time, _)| *time);
+        self.stored_usage = UsageHistory::default();
+        for point in points.iter().filter(|point| point.timestamp < live_start) {
+            self.stored_usage.push(point);
+        }
+        self.stored_usage.compact(self.last_update, self.retention);
+    }
+
+    /// Sets the retention window of the usage history.
+    /// Shortening the window drops the points outside of it right away.
+    pub fn set_retention(&mut self, retention: Duration) {
+        self.retention = retention;
+        self.usage.compact(self.last_update, retention);
+        self.stored_usage.compact(self.last_update, retention);
+    }
+
+    pub fn retention(&self) -> Duration {
+        self.retention
+    }
+
+    /// Returns the rates computed from the two most recent vmstat samples.
+    pub fn latest_rates(&self) -> VmRates {
+        self.latest_rates
+    }
+
+    /// Returns the memory used history as (timestamp, GB) points, the seeded ones first.
+    pub fn used_history(&self) -> Vec<DataPoint> {
+        joined(&self.stored_usage.used, &self.usage.used)
+    }
+
+    /// Returns the memory available history as (timestamp, GB) points, the seeded ones first.
+    pub fn available_history(&self) -> Vec<DataPoint> {
+        joined(&self.stored_usage.available, &self.usage.available)
+    }
+
+    /// Returns the swap used history as (timestamp, GB) points, the seeded ones first.
+    pub fn swap_used_history(&self) -> Vec<DataPoint> {
+        joined(&self.stored_usage.swap_used, &self.usage.swap_used)
+    }
+
+    /// Returns the recorded swap-in rate history as (timestamp, pages/s) points.
+    pub fn swap_in_history(&self) -> &[DataPoint] {
+        &self.swap_in
+    }
+
+    /// Returns the recorded swap-out rate history as (timestamp, negative pages/s) points.
+    pub fn swap_out_history(&self) -> &[DataPoint] {
+        &self.swap_out
+    }
+
+    /// Returns the recorded major page fault rate history as (timestamp, faults/s) points.
+    pub fn major_faults_history(&self) -> &[DataPoint] {
+        &self.major_faults
+    }
+
+    /// Returns the total memory of the latest sample in GB.
+    pub fn memory_total(&self) -> f64 {
+        self.memory_total
+    }
+
+    /// Returns the timestamp of the latest sample, the end of the usage chart's time axis.
+    pub fn last_update(&self) -> f64 {
+        self.last_update
+    }
+
+    /// Returns the time span covered by the rate history, at least one second.
+    pub fn rate_time_bounds(&self) -> [f64; 2] {
+        let first = self.swap_in.first().map(|(t, _)| *t).unwrap_or(0.0);
+        let last = self.swap_in.last().map(|(t, _)| *t).unwrap_or(0.0);
+        [first, last.max(first + 1.0)]
+    }
+}
+
+fn joined(stored: &TimeSeries, live: &TimeSeries) -> Vec<DataPoint> {
+    let mut points = stored.points();
+    points.extend(live.points());
+    points
+}
+
+// Appends a point and drops the oldest one once MAX_POINTS is exceeded
+fn push_point(history: &mut DataHistory, point: DataPoint) {
+    history.push(point);
+    if history.len() > MAX_POINTS {
+        history.remove(0);
+    }
+}
@@ -1,11 +1,24 @@
 //! This module is part of the Linux Dashboard project and provides various backend functionalities
 //! such as fetching system information, CPU usage, memory statistics, and more.
+pub mod alerts;
+pub mod connections;
 pub mod converter;
 pub mod cpu;
 pub mod cpu_info;
 pub mod disk;
+pub mod history;
 pub mod host;
 pub mod memory;
+pub mod memory_history;
+pub mod netstat;
 pub mod network;
+pub mod network_source;
+pub mod oom;
+pub mod power;
+pub mod process_network;
 pub mod processes;
+pub mod sample;
+pub mod sensors;
+mod sysfs;
 pub mod system_info;
+pub mod vmstat;
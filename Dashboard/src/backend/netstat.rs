@@ -0,0 +1,242 @@
+//! This module reads the kernel's network protocol counters from `/proc/net/snmp`
+//! and `/proc/net/netstat` and keeps a history of their rates.
+//! Both files consist of pairs of lines: a header line with the counter names and
+//! a line with the values, each prefixed with the protocol (e.g. `Tcp:` or `TcpExt:`).
+//! All values are cumulative since boot, rates are computed from two samples.
+use super::history::TimeSeries;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+type DataPoint = (f64, f64); // Tuple for time in seconds and value
+
+/// Time window of the rate history.
+pub const RETENTION: Duration = Duration::from_secs(5 * 60);
+
+/// Selected TCP and UDP counters.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolStats {
+    pub tcp_active_opens: u64,
+    pub tcp_passive_opens: u64,
+    pub tcp_attempt_fails: u64,
+    pub tcp_estab_resets: u64,
+    pub tcp_out_rsts: u64,
+    pub tcp_out_segs: u64,
+    pub tcp_retrans_segs: u64,
+    pub tcp_in_errs: u64,
+    pub listen_overflows: u64,
+    pub listen_drops: u64,
+    pub udp_in_errors: u64,
+    pub udp_no_ports: u64,
+    pub udp_rcvbuf_errors: u64,
+    pub udp_sndbuf_errors: u64,
+}
+
+impl ProtocolStats {
+    /// Reads `/proc/net/snmp` and `/proc/net/netstat`,
+    /// returns `None` if `/proc/net/snmp` is not available.
+    pub fn read() -> Option<Self> {
+        Self::read_from(Path::new("/proc/net"))
+    }
+
+    /// Reads the `snmp` and `netstat` files from the given directory.
+    /// A missing `netstat` file leaves the extended counters at zero.
+    pub fn read_from(dir: &Path) -> Option<Self> {
+        let mut counters = parse_counter_table(&fs::read_to_string(dir.join("snmp")).ok()?);
+        if let Ok(netstat) = fs::read_to_string(dir.join("netstat")) {
+            counters.extend(parse_counter_table(&netstat));
+        }
+        Some(Self::from_counters(&counters))
+    }
+
+    /// Picks the counters from a map returned by `parse_counter_table`.
+    pub fn from_counters(counters: &HashMap<String, u64>) -> Self {
+        let get = |key: &str| counters.get(key).copied().unwrap_or(0);
+        Self {
+            tcp_active_opens: get("Tcp.ActiveOpens"),
+            tcp_passive_opens: get("Tcp.PassiveOpens"),
+            tcp_attempt_fails: get("Tcp.AttemptFails"),
+            tcp_estab_resets: get("Tcp.EstabResets"),
+            tcp_out_rsts: get("Tcp.OutRsts"),
+            tcp_out_segs: get("Tcp.OutSegs"),
+            tcp_retrans_segs: get("Tcp.RetransSegs"),
+            tcp_in_errs: get("Tcp.InErrs"),
+            listen_overflows: get("TcpExt.ListenOverflows"),
+            listen_drops: get("TcpExt.ListenDrops"),
+            udp_in_errors: get("Udp.InErrors"),
+            udp_no_ports: get("Udp.NoPorts"),
+            udp_rcvbuf_errors: get("Udp.RcvbufErrors"),
+            udp_sndbuf_errors: get("Udp.SndbufErrors"),
+        }
+    }
+}
+
+/// Parses a `/proc/net/snmp` or `/proc/net/netstat` file into a map of
+/// "Protocol.Counter" to value. Negative values (e.g. `Tcp.MaxConn` -1) are skipped.
+///
+/// # Example
+/// ```
+/// use linux_dashboard::backend::netstat::parse_counter_table;
+/// let counters = parse_counter_table("Tcp: MaxConn RetransSegs\nTcp: -1 42\n");
+/// assert_eq!(counters.get("Tcp.RetransSegs"), Some(&42));
+/// assert_eq!(counters.get("Tcp.MaxConn"), None);
+/// ```
+///
+pub fn parse_counter_table(content: &str) -> HashMap<String, u64> {
+    let mut counters = HashMap::new();
+    let lines: Vec<&str> = content.lines().collect();
+    for pair in lines.chunks(2) {
+        let [header, values] = pair else {
+            continue;
+        };
+        let (Some((protocol, names)), Some((value_protocol, values))) =
+            (header.split_once(':'), values.split_once(':'))
+        else {
+            continue;
+        };
+        if protocol != value_protocol {
+            continue;
+        }
+        for (name, value) in names.split_whitespace().zip(values.split_whitespace()) {
+            if let Ok(value) = value.parse::<u64>() {
+                counters.insert(format!("{protocol}.{name}"), value);
+            }
+        }
+    }
+    counters
+}
+
+/// Rates derived from two consecutive samples, per second.
+/// `retransmit_percent` is the share of sent TCP segments that were retransmissions.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct ProtocolRates {
+    pub retransmits: f64,
+    pub retransmit_percent: f64,
+    pub active_opens: f64,
+    pub passive_opens: f64,
+    pub resets: f64,
+    pub listen_overflows: f64,
+    pub udp_errors: f64,
+    pub buffer_errors: f64,
+}
+
+impl ProtocolRates {
+    /// Computes the rates between two samples taken `elapsed` seconds apart.
+    /// Counters that went backwards yield a rate of zero.
+    pub fn between(old: &ProtocolStats, new: &ProtocolStats, elapsed: f64) -> Self {
+        let delta = |new: u64, old: u64| new.saturating_sub(old) as f64;
+        let rate = |new: u64, old: u64| delta(new, old) / elapsed;
+        let sent = delta(new.tcp_out_segs, old.tcp_out_segs);
+        let retransmitted = delta(new.tcp_retrans_segs, old.tcp_retrans_segs);
+        Self {
+            retransmits: retransmitted / elapsed,
+            retransmit_percent: if sent > 0.0 {
+                retransmitted / sent * 100.0
+            } else {
+                0.0
+            },
+            active_opens: rate(new.tcp_active_opens, old.tcp_active_opens),
+            passive_opens: rate(new.tcp_passive_opens, old.tcp_passive_opens),
+            resets: rate(new.tcp_estab_resets, old.tcp_estab_resets)
+                + rate(new.tcp_out_rsts, old.tcp_out_rsts),
+            listen_overflows: rate(new.listen_overflows, old.listen_overflows),
+            udp_errors: rate(new.udp_in_errors, old.udp_in_errors),
+            buffer_errors: rate(new.udp_rcvbuf_errors, old.udp_rcvbuf_errors)
+                + rate(new.udp_sndbuf_errors, old.udp_sndbuf_errors),
+        }
+    }
+}
+
+/// ProtocolHistory records the protocol counters and the history of the
+/// retransmission, reset and error rates.
+pub struct ProtocolHistory {
+    start: Instant,
+    last: Option<(Instant, ProtocolStats)>,
+    latest_rates: ProtocolRates,
+    retransmit_percent: TimeSeries,
+    resets: TimeSeries,
+    errors: TimeSeries,
+}
+
+impl Default for ProtocolHistory {
+    fn default() -> Self {
+        Self::new(Instant::now())
+    }
+}
+
+impl ProtocolHistory {
+    /// Creates an empty history, time values are seconds since `start`.
+    pub fn new(start: Instant) -> Self {
+        Self {
+            start,
+            last: None,
+            latest_rates: ProtocolRates::default(),
+            retransmit_percent: TimeSeries::new(),
+            resets: TimeSeries::new(),
+            errors: TimeSeries::new(),
+        }
+    }
+
+    /// Records a new sample taken now.
+    pub fn update(&mut self, stats: Option<ProtocolStats>) {
+        self.update_at(stats, Instant::now());
+    }
+
+    /// Records a new sample taken at the given instant.
+    /// Rates are computed from the elapsed time since the previous sample.
+    pub fn update_at(&mut self, stats: Option<ProtocolStats>, now: Instant) {
+        let Some(current) = stats else {
+            return;
+        };
+        if let Some((last_time, last)) = self.last {
+            let elapsed = now.duration_since(last_time).as_secs_f64();
+            if elapsed > 0.0 {
+                let rates = ProtocolRates::between(&last, &current, elapsed);
+                let time = now.duration_since(self.start).as_secs_f64();
+                self.retransmit_percent.push(time, rates.retransmit_percent);
+                self.resets.push(time, rates.resets);
+                self.errors.push(
+                    time,
+                    rates.listen_overflows + rates.udp_errors + rates.buffer_errors,
+                );
+                for series in [
+                    &mut self.retransmit_percent,
+                    &mut self.resets,
+                    &mut self.errors,
+                ] {
+                    series.compact(time, RETENTION);
+                }
+                self.latest_rates = rates;
+            }
+        }
+        self.last = Some((now, current));
+    }
+
+    /// Returns the rates computed from the two most recent samples.
+    pub fn latest_rates(&self) -> ProtocolRates {
+        self.latest_rates
+    }
+
+    /// Returns the most recent counters.
+    pub fn latest_stats(&self) -> Option<ProtocolStats> {
+        self.last.map(|(_, stats)| stats)
+    }
+
+    /// Returns the recorded retransmission share as (seconds, percent) points.
+    pub fn retransmit_history(&self) -> Vec<DataPoint> {
+        self.retransmit_percent.points()
+    }
+
+    /// Returns the recorded reset rate history as (seconds, resets/s) points.
+    pub fn resets_history(&self) -> Vec<DataPoint> {
+        self.resets.points()
+    }
+
+    /// Returns the recorded error rate history (listen overflows, UDP receive and
+    /// buffer errors) as (seconds, errors/s) points.
+    pub fn errors_history(&self) -> Vec<DataPoint> {
+        self.errors.points()
+    }
+}
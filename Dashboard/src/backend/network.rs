@@ -1,81 +1,402 @@
 //! This module manages network interfaces and their data usage.
 /// It provides functionality to track network traffic, display usage statistics,  
-/// and visualize the data in a chart format.  
-use ratatui::style::Color;
-use ratatui::text::Line;
-use ratatui::text::Span;
-use ratatui::{
-    style::{Style, Stylize},
-    symbols,
-    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType},
-};
-use std::collections::HashMap;
-use sysinfo::Networks;
+/// and the data for the traffic chart.  
+use super::converter::format_rate;
+use super::history::{TimeSeries, unix_now};
+use super::network_source::{NetworkSource, SysinfoNetworks};
+use super::sysfs::{read_number, read_trimmed};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::net::IpAddr;
+use std::path::Path;
+use std::time::Duration;
+use sysinfo::NetworkData;
 
 type DataPoint = (f64, f64); // Tuple for time and value
-type DataHistory = Vec<DataPoint>; // History of data points for download and upload
-type NetworkHistory = (DataHistory, DataHistory); // Tuple for download and upload history
+type DataHistory = Vec<DataPoint>; // Data points for download or upload, used for charting
+type NetworkHistory = (TimeSeries, TimeSeries); // Tuple for download and upload history
 type NetworkHistoryMap = HashMap<String, NetworkHistory>; // Map for each interface to its download and upload history
 
+/// Retention windows the user can cycle through.
+pub const RETENTION_PRESETS: [Duration; 5] = [
+    Duration::from_secs(60),
+    Duration::from_secs(5 * 60),
+    Duration::from_secs(15 * 60),
+    Duration::from_secs(60 * 60),
+    Duration::from_secs(6 * 60 * 60),
+];
+
+/// Retention window used on startup.
+pub const DEFAULT_RETENTION: Duration = Duration::from_secs(5 * 60);
+
+/// Cumulative byte counters of an interface at one point in time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterfaceCounters {
+    pub received: u64,
+    pub transmitted: u64,
+}
+
+/// Download and upload rate of an interface in bytes per second.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct InterfaceRate {
+    pub download: f64,
+    pub upload: f64,
+}
+
+/// Current rates of an interface in bytes per second.
+/// The rates are `None` until the interface has been sampled twice.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InterfaceThroughput {
+    pub name: String,
+    pub download: Option<f64>,
+    pub upload: Option<f64>,
+}
+
+/// RateTracker turns cumulative interface counters into per-second rates.
+/// It remembers the counters and the timestamp of the previous sample of every interface.
+/// - The rate is the counter difference divided by the elapsed wall time.
+/// - A counter that is lower than before was reset (e.g. the interface restarted),
+///   the current value is then counted as the bytes transferred since the reset.
+/// - An interface seen for the first time has no rate yet.
+#[derive(Debug, Default)]
+pub struct RateTracker {
+    previous: HashMap<String, (f64, InterfaceCounters)>,
+}
+
+impl RateTracker {
+    /// Takes the counters of all interfaces at the given timestamp (in seconds)
+    /// and returns the rate of every interface since its previous sample.
+    /// Interfaces missing from `counters` are forgotten.
+    pub fn update(
+        &mut self,
+        timestamp: f64,
+        counters: &[(String, InterfaceCounters)],
+    ) -> Vec<(String, Option<InterfaceRate>)> {
+        let mut previous = std::mem::take(&mut self.previous);
+        let mut rates = Vec::with_capacity(counters.len());
+
+        for (interface, current) in counters {
+            let rate = previous.remove(interface).and_then(|(last_time, last)| {
+                let elapsed = timestamp - last_time;
+                (elapsed > 0.0).then(|| InterfaceRate {
+                    download: counter_delta(last.received, current.received) as f64 / elapsed,
+                    upload: counter_delta(last.transmitted, current.transmitted) as f64 / elapsed,
+                })
+            });
+            self.previous
+                .insert(interface.clone(), (timestamp, *current));
+            rates.push((interface.clone(), rate));
+        }
+        rates
+    }
+}
+
+// Difference between two samples of a cumulative counter, handling counter resets
+fn counter_delta(previous: u64, current: u64) -> u64 {
+    if current >= previous {
+        current - previous
+    } else {
+        current
+    }
+}
+
+/// What a series of the network chart shows, determines its color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeriesKind {
+    /// Download of the selected interface
+    Download,
+    /// Upload of the selected interface
+    Upload,
+    /// Traffic of the n-th interface of the all interfaces chart
+    Interface(usize),
+    /// Sum of all interfaces
+    Total,
+    /// Sum of the physical interfaces before the live history, e.g. from the on-disk history
+    Stored,
+}
+
+/// One line pair (download above, upload below the axis) in the network chart.
+/// Upload values are negative.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartSeries {
+    pub name: String,
+    pub kind: SeriesKind,
+    pub download: DataHistory,
+    pub upload: DataHistory,
+}
+
+/// Returns true for the loopback interface and virtual bridges/links
+/// (`lo`, `docker0`, `veth*`, `br-*`, `virbr*`).
+///
+/// # Example
+/// ```
+/// use linux_dashboard::backend::network::is_virtual_interface;
+/// assert!(is_virtual_interface("veth3a1f"));
+/// assert!(!is_virtual_interface("eth0"));
+/// ```
+///
+pub fn is_virtual_interface(name: &str) -> bool {
+    name == "lo"
+        || name == "docker0"
+        || name.starts_with("veth")
+        || name.starts_with("br-")
+        || name.starts_with("virbr")
+}
+
+// Key for matching data points of different interfaces at the same point in time
+fn time_key(x: f64) -> i64 {
+    (x * 1000.0).round() as i64
+}
+
+/// How long an interface stays highlighted after its error or drop counters increased.
+const FAULT_HIGHLIGHT: Duration = Duration::from_secs(30);
+
+/// Link information read from `/sys/class/net/<interface>`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LinkInfo {
+    pub operstate: String,
+    /// Link speed in Mbit/s, `None` for virtual interfaces or when the link is down
+    pub speed_mbps: Option<u64>,
+    pub rx_dropped: u64,
+    pub tx_dropped: u64,
+}
+
+/// Reads the link information of an interface below the given sysfs root (usually `/sys`).
+/// Missing files are reported as "unknown" state, no speed and zero drops.
+pub fn read_link_info(sysfs_root: &Path, interface: &str) -> LinkInfo {
+    let dir = sysfs_root.join("class/net").join(interface);
+    LinkInfo {
+        operstate: read_trimmed(&dir.join("operstate")).unwrap_or_else(|| "unknown".to_string()),
+        // virtual interfaces report -1 or fail to read the speed
+        speed_mbps: read_number(&dir.join("speed"))
+            .filter(|speed| *speed > 0.0)
+            .map(|speed| speed as u64),
+        rx_dropped: read_number(&dir.join("statistics/rx_dropped")).unwrap_or(0.0) as u64,
+        tx_dropped: read_number(&dir.join("statistics/tx_dropped")).unwrap_or(0.0) as u64,
+    }
+}
+
+/// Details of a network interface: addresses, link state and cumulative packet counters.
+/// `errors_rising` and `drops_rising` are set if the counters increased recently.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InterfaceDetails {
+    pub name: String,
+    pub ipv4: Vec<String>,
+    pub ipv6: Vec<String>,
+    pub mac: String,
+    pub mtu: u64,
+    pub link: LinkInfo,
+    pub rx_packets: u64,
+    pub tx_packets: u64,
+    pub rx_errors: u64,
+    pub tx_errors: u64,
+    pub errors_rising: bool,
+    pub drops_rising: bool,
+}
+
+impl InterfaceDetails {
+    /// Builds the details from the sysinfo data and the link information of an interface.
+    pub fn new(name: &str, data: &NetworkData, link: LinkInfo) -> Self {
+        let mut ipv4 = Vec::new();
+        let mut ipv6 = Vec::new();
+        for network in data.ip_networks() {
+            match network.addr {
+                IpAddr::V4(_) => ipv4.push(network.to_string()),
+                IpAddr::V6(_) => ipv6.push(network.to_string()),
+            }
+        }
+        Self {
+            name: name.to_string(),
+            ipv4,
+            ipv6,
+            mac: data.mac_address().to_string(),
+            mtu: data.mtu(),
+            link,
+            rx_packets: data.total_packets_received(),
+            tx_packets: data.total_packets_transmitted(),
+            rx_errors: data.total_errors_on_received(),
+            tx_errors: data.total_errors_on_transmitted(),
+            errors_rising: false,
+            drops_rising: false,
+        }
+    }
+
+    pub fn total_errors(&self) -> u64 {
+        self.rx_errors + self.tx_errors
+    }
+
+    pub fn total_drops(&self) -> u64 {
+        self.link.rx_dropped + self.link.tx_dropped
+    }
+}
+
+/// Remembers the error and drop counters of every interface and when they last increased.
+#[derive(Default)]
+struct FaultTracker {
+    previous: HashMap<String, (u64, u64)>,
+    errors_increased: HashMap<String, f64>,
+    drops_increased: HashMap<String, f64>,
+}
+
+impl FaultTracker {
+    // Compares the counters with the previous sample and marks the details
+    // if the errors or drops increased within FAULT_HIGHLIGHT.
+    // `now` is a timestamp in seconds.
+    fn track(&mut self, details: &mut InterfaceDetails, now: f64) {
+        let errors = details.total_errors();
+        let drops = details.total_drops();
+        if let Some((previous_errors, previous_drops)) =
+            self.previous.insert(details.name.clone(), (errors, drops))
+        {
+            if errors > previous_errors {
+                self.errors_increased.insert(details.name.clone(), now);
+            }
+            if drops > previous_drops {
+                self.drops_increased.insert(details.name.clone(), now);
+            }
+        }
+        let recent = |map: &HashMap<String, f64>| {
+            map.get(&details.name)
+                .is_some_and(|time| now - time < FAULT_HIGHLIGHT.as_secs_f64())
+        };
+        details.errors_rising = recent(&self.errors_increased);
+        details.drops_rising = recent(&self.drops_increased);
+    }
+}
+
 /// NetworkManager manages network interfaces and their data usage.
 /// It tracks received and transmitted data, updates network history, and provides a chart for visualization.
 /// It also allows selecting a specific network interface to display its traffic data.
 /// The struct contains fields for networks, previous data, network history, scaled data for charting,
-/// a time counter for the chart, and the currently selected interface.
-pub struct NetworkManager {
-    networks: Networks,
+/// the retention window of the history, and the currently selected interface.
+/// The history stores real timestamps, so the chart's time axis keeps its meaning
+/// when the refresh interval changes.
+/// The interface data is read from a `NetworkSource`, by default sysinfo and sysfs.
+pub struct NetworkManager<S: NetworkSource = SysinfoNetworks> {
+    source: S,
 
-    previous_received: HashMap<String, u64>,
-    previous_transmitted: HashMap<String, u64>,
+    rate_tracker: RateTracker,
     network_history: NetworkHistoryMap,
-    // Vec<()> because the data changes over time
-    // f64 for time and the value for upload/download
-    scaled_download: Vec<(f64, f64)>,
-    scaled_upload: Vec<(f64, f64)>,
-    time_counter: f64,
+    /// Download and upload summed over the physical interfaces before the live history
+    stored_history: NetworkHistory,
+    retention: Duration,
+    /// Retention windows `cycle_retention` steps through, shortest first
+    retention_presets: Vec<Duration>,
+    last_update: f64,
     selected_interface: String,
+    show_all_interfaces: bool,
+    hide_virtual: bool,
+    interface_details: Vec<InterfaceDetails>,
+    throughput: Vec<InterfaceThroughput>,
+    fault_tracker: FaultTracker,
 }
 
 impl Default for NetworkManager {
     fn default() -> Self {
-        let networks = Networks::new_with_refreshed_list();
-        let mut network_history = HashMap::new();
+        Self::new(SysinfoNetworks::default())
+    }
+}
 
-        // initializes every found interface with an empty history
-        for (interface_name, _) in networks.iter() {
-            network_history.insert(interface_name.to_string(), (Vec::new(), Vec::new()));
-        }
+impl<S: NetworkSource> NetworkManager<S> {
+    /// Creates a manager reading from the given source.
+    /// Every interface the source reports right away starts with an empty history,
+    /// the first one is selected.
+    pub fn new(mut source: S) -> Self {
+        source.refresh();
+        let mut interfaces: Vec<String> = source
+            .interfaces()
+            .into_iter()
+            .map(|sample| sample.details.name)
+            .collect();
+        interfaces.sort();
 
-        let selected_interface = networks
+        // initializes every found interface with an empty history
+        let network_history = interfaces
             .iter()
-            .next()
-            .map(|(name, _)| name.to_string())
-            .unwrap_or_default();
+            .map(|name| (name.clone(), (TimeSeries::new(), TimeSeries::new())))
+            .collect();
+        let selected_interface = interfaces.first().cloned().unwrap_or_default();
 
-        // create a new instance of NetworkManager with the initialized values
         Self {
-            networks: Networks::new_with_refreshed_list(),
-            previous_received: HashMap::new(),
-            previous_transmitted: HashMap::new(),
+            source,
+            rate_tracker: RateTracker::default(),
             network_history,
-            scaled_download: Vec::new(),
-            scaled_upload: Vec::new(),
-            time_counter: 0.0,
+            stored_history: (TimeSeries::new(), TimeSeries::new()),
+            retention: DEFAULT_RETENTION,
+            retention_presets: RETENTION_PRESETS.to_vec(),
+            last_update: unix_now(),
             selected_interface,
+            show_all_interfaces: false,
+            hide_virtual: true,
+            interface_details: Vec::new(),
+            throughput: Vec::new(),
+            fault_tracker: FaultTracker::default(),
         }
     }
-}
 
-impl NetworkManager {
+    /// Returns the source the interfaces are read from.
+    pub fn source_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+
     /// This setter-method is used to change the currently selected interface
     /// and update the history for this interface.
     pub fn set_selected_interface(&mut self, interface: String) {
         self.selected_interface = interface;
     }
 
-    /// This getter-method returns the keys of the network history map,
+    /// This getter-method returns the keys of the network history map, sorted by name.
     pub fn network_history_keys(&self) -> Vec<String> {
-        self.network_history.keys().cloned().collect()
+        let mut keys: Vec<String> = self.network_history.keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+
+    /// Selects the next interface in alphabetical order, wrapping around after the last one.
+    pub fn select_next_interface(&mut self) {
+        let interfaces = self.network_history_keys();
+        if interfaces.is_empty() {
+            return;
+        }
+        let next = interfaces
+            .iter()
+            .position(|name| *name == self.selected_interface)
+            .map_or(0, |current| (current + 1) % interfaces.len());
+        self.selected_interface = interfaces[next].clone();
+    }
+
+    // Creates an empty history for interfaces that appeared since the last refresh
+    // (e.g. a VPN tunnel, a Docker bridge or a USB network card) and retires the history
+    // of interfaces that disappeared. If the selected interface disappeared, or nothing was
+    // selected yet, the first remaining interface is selected.
+    fn sync_interfaces(&mut self, interfaces: &[&str]) {
+        self.network_history
+            .retain(|name, _| interfaces.contains(&name.as_str()));
+        for name in interfaces {
+            self.network_history
+                .entry(name.to_string())
+                .or_insert_with(|| (TimeSeries::new(), TimeSeries::new()));
+        }
+
+        if !self.network_history.contains_key(&self.selected_interface) {
+            self.selected_interface = self
+                .network_history_keys()
+                .into_iter()
+                .next()
+                .unwrap_or_default();
+        }
+    }
+
+    /// Returns the download and upload history of an interface.
+    /// Upload values are stored as negative values.
+    pub fn history(&self, interface: &str) -> Option<&(TimeSeries, TimeSeries)> {
+        self.network_history.get(interface)
+    }
+
+    /// This getter-method returns the details of all interfaces from the last refresh,
+    /// sorted by interface name.
+    pub fn interface_details(&self) -> &[InterfaceDetails] {
+        &self.interface_details
     }
 
     /// This getter-method returns the currently selected interface.
@@ -83,194 +404,338 @@ impl NetworkManager {
         &self.selected_interface
     }
 
-    // The update_network_data method updates the network history for the currently selected interface.  
-    // It takes the received and transmitted data differences and updates the history.  
-    // The time_counter is incremented to keep track of the time for the chart.  
-    // It also ensures that the history does not exceed 50 data points.  
-    // This method is called whenever new network data is available.  
+    // The update_network_data method updates the network history for the given interface.
+    // It takes the download and upload rates in bytes per second and stores them with the
+    // given timestamp (seconds since the Unix epoch). All interfaces of one refresh share
+    // the same timestamp.
+    // Points outside the retention window are dropped and older points are down-sampled,
+    // so the history stays bounded regardless of refresh interval and retention.
+    // This method is called whenever new network data is available.
     pub fn update_network_data(
         &mut self,
-        received_diff: u64,
-        transmitted_diff: u64,
+        download: f64,
+        upload: f64,
         interface: &str,
+        timestamp: f64,
     ) {
-        self.time_counter += 1.0;
-        let download = received_diff as f64;
-        let upload = transmitted_diff as f64;
+        self.last_update = self.last_update.max(timestamp);
 
         if let Some((download_history, upload_history)) = self.network_history.get_mut(interface) {
-            download_history.push((self.time_counter, download));
-            // Upload als negative Werte speichern, damit er im Diagramm nach unten verläuft
-            upload_history.push((self.time_counter, -upload));
-
-            // Behalte nur die letzten 50 Datenpunkte
-            if download_history.len() > 50 {
-                download_history.remove(0);
-                upload_history.remove(0);
-            }
+            download_history.push(timestamp, download);
+            // Upload is stored as negative values so it is drawn below the axis
+            upload_history.push(timestamp, -upload);
+
+            download_history.compact(timestamp, self.retention);
+            upload_history.compact(timestamp, self.retention);
         }
     }
 
-    // Creates a chart widget for the network traffic of the selected interface.
-    // It uses the network history to plot the download and upload data.
-    // The chart is scaled based on the maximum value found in the data.
-    // The unit is determined based on the maximum value to display it in a human-readable format.
-    // The chart is styled with colors for download (green) and upload (red).
-    // The x-axis represents time, and the y-axis represents the data rate in the appropriate unit.
-    pub fn get_network_widget(&mut self) -> Chart<'_> {
-        let empty = (Vec::new(), Vec::new());
-        let (download_data, upload_data) = self
-            .network_history
-            .get(&self.selected_interface)
-            .unwrap_or(&empty);
-
-        // Determine the maximum value to scale the chart.
-        // The max value is determined by iterating over both download and upload data.
-        // The "_" placeholder is used to ignore the timestamp, as it is not relevant for the maximum value.
-        // The body of the closure is called with value.abs() to ensure we always
-        // have a positive value, as upload values are stored as negative.
-        let max_value = download_data
-            .iter()
-            .chain(upload_data.iter())
-            .map(|(_, value)| value.abs())
-            .max_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
-            .unwrap_or(0.0);
-
-        // unit is a &'static str, as the values are string literals and the lifetime is static,
-        // meaning the values should be displayed as long as the program itself runs.
-        let (unit, scale_factor) = if max_value > 1024.0 * 1024.0 * 1024.0 {
-            ("GB/s", 1024.0 * 1024.0 * 1024.0)
-        } else if max_value > 1024.0 * 1024.0 {
-            ("MB/s", 1024.0 * 1024.0)
-        } else if max_value > 1024.0 {
-            ("KB/s", 1024.0)
-        } else {
-            ("B/s", 1.0)
-        };
+    /// Drops the history, the rates and the details of all interfaces,
+    /// e.g. before feeding samples from another point in time.
+    /// The selected interface and the view settings are kept.
+    pub fn clear_history(&mut self) {
+        self.rate_tracker = RateTracker::default();
+        self.network_history.clear();
+        self.stored_history = (TimeSeries::new(), TimeSeries::new());
+        self.interface_details.clear();
+        self.throughput.clear();
+        self.fault_tracker = FaultTracker::default();
+        self.last_update = 0.0;
+    }
 
-        // Updates the scaled_download and scaled_upload fields with the scaled data.
-        // The data is scaled by dividing each value by the scale_factor.
-        // This ensures that the chart displays the data in a human-readable format.
-        self.scaled_download = download_data
+    /// Switches to the next retention window, by default of `RETENTION_PRESETS`.
+    /// Shortening the window drops the points outside of it right away.
+    pub fn cycle_retention(&mut self) {
+        let presets = &self.retention_presets;
+        let current = presets
             .iter()
-            .map(|(x, y)| (*x, y / scale_factor))
+            .position(|r| *r == self.retention)
+            .unwrap_or(0);
+        self.set_retention(presets[(current + 1) % presets.len()]);
+    }
+
+    /// Adds retention windows to cycle through, e.g. the ranges of the on-disk history.
+    pub fn add_retention_presets(&mut self, retentions: &[Duration]) {
+        self.retention_presets.extend_from_slice(retentions);
+        self.retention_presets.sort();
+        self.retention_presets.dedup();
+    }
+
+    /// Replaces the history before the live one with the given download and upload rates
+    /// in bytes per second, summed over the physical interfaces, e.g. read from the
+    /// on-disk history. Points not older than the live history are left out.
+    /// The chart shows them as a series of their own, as they are not per interface.
+    pub fn seed_history(&mut self, points: &[(f64, f64, f64)]) {
+        let live_start = self
+            .network_history
+            .values()
+            .filter_map(|(download, _)| download.points().first().map(|(time, _)| *time))
+            .fold(f64::INFINITY, f64::min);
+        let (mut download, mut upload) = (TimeSeries::new(), TimeSeries::new());
+        for (time, rx, tx) in points.iter().filter(|(time, ..)| *time < live_start) {
+            download.push(*time, *rx);
+            upload.push(*time, -tx);
+        }
+        download.compact(self.last_update, self.retention);
+        upload.compact(self.last_update, self.retention);
+        self.stored_history = (download, upload);
+    }
+
+    /// Sets the retention window of the history.
+    pub fn set_retention(&mut self, retention: Duration) {
+        self.retention = retention;
+        for (download, upload) in self.network_history.values_mut() {
+            download.compact(self.last_update, retention);
+            upload.compact(self.last_update, retention);
+        }
+    }
+
+    pub fn retention(&self) -> Duration {
+        self.retention
+    }
+
+    /// Toggles between the chart of the selected interface and the overlay of all interfaces.
+    pub fn toggle_all_interfaces(&mut self) {
+        self.show_all_interfaces = !self.show_all_interfaces;
+    }
+
+    /// Toggles whether loopback and virtual interfaces are hidden in the overlay.
+    pub fn toggle_hide_virtual(&mut self) {
+        self.hide_virtual = !self.hide_virtual;
+    }
+
+    pub fn is_showing_all_interfaces(&self) -> bool {
+        self.show_all_interfaces
+    }
+
+    pub fn is_hiding_virtual(&self) -> bool {
+        self.hide_virtual
+    }
+
+    /// Returns the timestamp of the latest refresh (seconds since the Unix epoch),
+    /// the end of the chart's time axis.
+    pub fn last_update(&self) -> f64 {
+        self.last_update
+    }
+
+    /// Returns the interfaces shown in the overlay, sorted by name.
+    /// Loopback and virtual interfaces are left out if the filter is active.
+    pub fn overlay_interfaces(&self) -> Vec<String> {
+        let mut interfaces: Vec<String> = self
+            .network_history
+            .keys()
+            .filter(|name| !self.hide_virtual || !is_virtual_interface(name))
+            .cloned()
             .collect();
-        self.scaled_upload = upload_data
+        interfaces.sort();
+        interfaces
+    }
+
+    /// Returns the unscaled series for the chart in bytes per second: only the selected
+    /// interface in the single interface mode, otherwise every overlay interface and
+    /// the aggregate total. A seeded history comes last.
+    pub fn chart_series(&self) -> Vec<ChartSeries> {
+        let mut series = self.live_chart_series();
+        let (download, upload) = &self.stored_history;
+        if !download.is_empty() {
+            series.push(ChartSeries {
+                name: "Stored total".to_string(),
+                kind: SeriesKind::Stored,
+                download: download.points(),
+                upload: upload.points(),
+            });
+        }
+        series
+    }
+
+    fn live_chart_series(&self) -> Vec<ChartSeries> {
+        if !self.show_all_interfaces {
+            let (download, upload) = self
+                .network_history
+                .get(&self.selected_interface)
+                .map(|(download, upload)| (download.points(), upload.points()))
+                .unwrap_or_default();
+            return vec![
+                ChartSeries {
+                    name: "▼ Download".to_string(),
+                    kind: SeriesKind::Download,
+                    download,
+                    upload: Vec::new(),
+                },
+                ChartSeries {
+                    name: "▲ Upload".to_string(),
+                    kind: SeriesKind::Upload,
+                    download: Vec::new(),
+                    upload,
+                },
+            ];
+        }
+
+        let interfaces = self.overlay_interfaces();
+        let mut series: Vec<ChartSeries> = interfaces
             .iter()
-            .map(|(x, y)| (*x, y / scale_factor))
+            .enumerate()
+            .filter_map(|(i, name)| {
+                let (download, upload) = self.network_history.get(name)?;
+                Some(ChartSeries {
+                    name: name.clone(),
+                    kind: SeriesKind::Interface(i),
+                    download: download.points(),
+                    upload: upload.points(),
+                })
+            })
             .collect();
 
-        let datasets = vec![
-            Dataset::default()
-                .name("▼ Download")
-                .marker(symbols::Marker::Braille)
-                .graph_type(GraphType::Line)
-                .style(Style::default().green())
-                .data(&self.scaled_download),
-            Dataset::default()
-                .name("▲ Upload")
-                .marker(symbols::Marker::Braille)
-                .graph_type(GraphType::Line)
-                .style(Style::default().red())
-                .data(&self.scaled_upload),
-        ];
-
-        let x_axis = Axis::default()
-            .title("Time")
-            .style(Style::default().white())
-            .bounds([self.time_counter - 50.0, self.time_counter])
-            .labels(Vec::<String>::new());
-
-        let y_axis = Axis::default()
-            .title(unit)
-            .style(Style::default().white())
-            .bounds([
-                -(max_value / scale_factor).ceil(),
-                (max_value / scale_factor).ceil(),
-            ])
-            .labels(Vec::<String>::new());
-
-        Chart::new(datasets)
-            .block(
-                Block::default()
-                    .title(format!(
-                        "Network Traffic - Interface: {}",
-                        self.selected_interface.clone()
-                    ))
-                    // .title_bottom("'n' - change network interface")
-                    .title_bottom(Line::from(vec![
-                        Span::styled("i", Style::default().fg(Color::Yellow)),
-                        Span::raw(" - to change network interface"),
-                    ]))
-                    .borders(Borders::ALL),
-            )
-            .x_axis(x_axis)
-            .y_axis(y_axis)
-    }
-
-    // formats the network data as a string for display.
-    // It iterates over all network interfaces and calculates the received and transmitted data.
-    // The data is formatted based on the size (B, KB, MB, GB)
-    // and appended to the result string.
-    pub fn format_network(&mut self) -> String {
-        let mut data_transfer = String::new();
-        self.networks.refresh(true);
-        let mut network_updates = Vec::new();
-
-        // Iteriere über alle Interfaces und sammle deren Messwerte.
-        for (interface_name, data) in self.networks.iter() {
-            let received = data.total_received();
-            let transmitted = data.total_transmitted();
-
-            let received_diff = received
-                - self
-                    .previous_received
-                    .get(&interface_name.to_string())
-                    .unwrap_or(&0);
-            let transmitted_diff =
-                transmitted - self.previous_transmitted.get(interface_name).unwrap_or(&0);
-
-            self.previous_received
-                .insert(interface_name.to_string(), received);
-            self.previous_transmitted
-                .insert(interface_name.to_string(), transmitted);
-
-            // saved to network_updates for later processing
-            // This is done to avoid multiple println! calls in the loop,
-            // which can be inefficient and clutter the output.
-            // Instead, we collect the updates and process them after the loop.
-            // This also allows us to format the output string in a more controlled manner.
-            network_updates.push((interface_name.to_string(), received_diff, transmitted_diff));
-
-            // Creates the formatted string for the current interface
-            let network_info = if received_diff < 1024 && transmitted_diff < 1024 {
-                format!(
-                    "{interface_name}: {received_diff} B/s (down), {transmitted_diff} B/s (up)\n"
-                )
-            } else if received_diff < 1024 * 1024 && transmitted_diff < 1024 * 1024 {
-                let down_kb = received_diff / 1024;
-                let up_kb = transmitted_diff / 1024;
-                format!("{interface_name}: {down_kb} KB/s (down), {up_kb} KB/s (up)\n")
-            } else if received_diff < 1024 * 1024 * 1024 && transmitted_diff < 1024 * 1024 * 1024 {
-                let down_mb = received_diff / (1024 * 1024);
-                let up_mb = transmitted_diff / (1024 * 1024);
-                format!("{interface_name}: {down_mb} MB/s (down), {up_mb} MB/s (up)\n")
-            } else {
-                let down_gb = received_diff / (1024 * 1024 * 1024);
-                let up_gb = transmitted_diff / (1024 * 1024 * 1024);
-                format!("{interface_name}: {down_gb} GB/s (down), {up_gb} GB/s (up)\n")
-            };
-            data_transfer.push_str(&network_info);
+        // The total is summed per point in time, interfaces that appeared later
+        // simply contribute nothing to the earlier points.
+        let mut total: BTreeMap<i64, (f64, f64, f64)> = BTreeMap::new();
+        for entry in &series {
+            for (x, y) in &entry.download {
+                total.entry(time_key(*x)).or_insert((*x, 0.0, 0.0)).1 += y;
+            }
+            for (x, y) in &entry.upload {
+                total.entry(time_key(*x)).or_insert((*x, 0.0, 0.0)).2 += y;
+            }
         }
+        series.push(ChartSeries {
+            name: "Total".to_string(),
+            kind: SeriesKind::Total,
+            download: total.values().map(|(x, down, _)| (*x, *down)).collect(),
+            upload: total.values().map(|(x, _, up)| (*x, *up)).collect(),
+        });
+        series
+    }
+
+    /// Returns the unit and scale factor the chart currently uses, e.g. ("MB/s", 1048576.0).
+    pub fn chart_scale(&self) -> (&'static str, f64) {
+        chart_unit(max_chart_value(&self.chart_series()))
+    }
+
+    /// Samples all interfaces now and updates the rates, the history and the details.
+    pub fn refresh(&mut self) {
+        self.refresh_at(unix_now())
+    }
 
-        // Update the network history for each interface
-        // This is done after collecting all updates to avoid modifying the history while iterating.
-        // This ensures that the history is updated only once per interface,
-        // which is more efficient and avoids potential issues with concurrent modifications.
-        for (interface, received_diff, transmitted_diff) in network_updates {
-            self.update_network_data(received_diff, transmitted_diff, &interface);
+    /// Like `refresh`, but uses the given timestamp (seconds since the Unix epoch)
+    /// instead of the current time.
+    pub fn refresh_at(&mut self, timestamp: f64) {
+        self.source.refresh();
+        // Set even if no interface has a rate yet, the chart ends at the latest refresh
+        self.last_update = timestamp;
+        let mut interface_details = Vec::new();
+
+        // Collect the cumulative counters of all interfaces.
+        // All interfaces of one refresh share the same point in time.
+        let mut counters = Vec::new();
+        for sample in self.source.interfaces() {
+            let mut details = sample.details;
+            counters.push((details.name.clone(), sample.counters));
+            self.fault_tracker.track(&mut details, timestamp);
+            interface_details.push(details);
         }
+        counters.sort_by(|a, b| a.0.cmp(&b.0));
+        let names: Vec<&str> = counters.iter().map(|(name, _)| name.as_str()).collect();
+        self.sync_interfaces(&names);
+
+        // Rates are computed from the elapsed time since the previous refresh,
+        // so they are per second regardless of the refresh interval.
+        // The history is updated after collecting all counters to avoid modifying it while
+        // iterating, so it is updated only once per interface and refresh.
+        let mut throughput = Vec::new();
+        for (interface, rate) in self.rate_tracker.update(timestamp, &counters) {
+            if let Some(rate) = rate {
+                self.update_network_data(rate.download, rate.upload, &interface, timestamp);
+            }
+            throughput.push(InterfaceThroughput {
+                name: interface,
+                download: rate.map(|rate| rate.download),
+                upload: rate.map(|rate| rate.upload),
+            });
+        }
+        interface_details.sort_by(|a, b| a.name.cmp(&b.name));
+        self.interface_details = interface_details;
+        self.throughput = throughput;
+    }
+
+    /// Returns the rates of all interfaces from the latest refresh, sorted by name.
+    pub fn throughput(&self) -> &[InterfaceThroughput] {
+        &self.throughput
+    }
+
+    // Refreshes the interfaces and formats the rates as a string for display.
+    pub fn format_network(&mut self) -> String {
+        self.format_network_at(unix_now())
+    }
+
+    /// Like `format_network`, but uses the given timestamp (seconds since the Unix epoch)
+    /// for the refresh instead of the current time.
+    pub fn format_network_at(&mut self, timestamp: f64) -> String {
+        self.refresh_at(timestamp);
+        format_throughput(&self.throughput)
+    }
+}
+
+/// Formats the download and upload rate of every interface, one line per interface.
+/// Interfaces without a previous sample are shown as "measuring...".
+///
+/// # Example
+/// ```
+/// use linux_dashboard::backend::network::{InterfaceThroughput, format_throughput};
+/// let throughput = vec![InterfaceThroughput {
+///     name: "eth0".to_string(),
+///     download: None,
+///     upload: None,
+/// }];
+/// assert_eq!(format_throughput(&throughput), "eth0: measuring...\n");
+/// ```
+///
+pub fn format_throughput(throughput: &[InterfaceThroughput]) -> String {
+    throughput
+        .iter()
+        .map(|interface| match (interface.download, interface.upload) {
+            (Some(download), Some(upload)) => format!(
+                "{}: {} (down), {} (up)\n",
+                interface.name,
+                format_rate(download),
+                format_rate(upload)
+            ),
+            // The first sample of an interface has nothing to compare against
+            _ => format!("{}: measuring...\n", interface.name),
+        })
+        .collect()
+}
+
+// Returns the largest absolute value of all series.
+// value.abs() is used as upload values are stored as negative.
+fn max_chart_value(series: &[ChartSeries]) -> f64 {
+    series
+        .iter()
+        .flat_map(|entry| entry.download.iter().chain(entry.upload.iter()))
+        .map(|(_, value)| value.abs())
+        .max_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+        .unwrap_or(0.0)
+}
 
-        data_transfer
+/// Returns the unit and the scale factor for a chart whose largest value is `max_value`
+/// bytes per second.
+///
+/// # Example
+/// ```
+/// use linux_dashboard::backend::network::chart_unit;
+/// assert_eq!(chart_unit(512.0), ("B/s", 1.0));
+/// assert_eq!(chart_unit(3.0 * 1024.0 * 1024.0), ("MB/s", 1024.0 * 1024.0));
+/// ```
+///
+pub fn chart_unit(max_value: f64) -> (&'static str, f64) {
+    // unit is a &'static str, as the values are string literals and the lifetime is static,
+    // meaning the values should be displayed as long as the program itself runs.
+    if max_value > 1024.0 * 1024.0 * 1024.0 {
+        ("GB/s", 1024.0 * 1024.0 * 1024.0)
+    } else if max_value > 1024.0 * 1024.0 {
+        ("MB/s", 1024.0 * 1024.0)
+    } else if max_value > 1024.0 {
+        ("KB/s", 1024.0)
+    } else {
+        ("B/s", 1.0)
     }
 }
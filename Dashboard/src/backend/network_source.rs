@@ -0,0 +1,97 @@
+//! Documentation for the NetworkSource Trait and its Implementation
+///
+/// ''' pub trait NetworkSource '''
+/// Like the SystemInfo trait for the CPU, this trait hides where the network data comes from.
+/// A source is refreshed once per tick and then returns a sample of every interface:
+/// the cumulative byte counters used for the rates and the details shown in the details view.
+///
+/// ''' pub struct SysinfoNetworks '''
+/// The implementation used by the dashboard. It reads the counters and addresses with
+/// sysinfo::Networks and the link information from sysfs.
+///
+/// ''' pub struct SampleNetworks '''
+/// A source that returns the interfaces handed to it, e.g. from a recorded session.
+/// The terminal UI uses it for live data as well, so live and replayed sessions
+/// pass through the same code.
+///
+/// The NetworkManager is generic over this trait, so in tests it can be driven with
+/// scripted counter sequences instead of the counters of the machine running the tests.
+use super::network::{InterfaceCounters, InterfaceDetails, read_link_info};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use sysinfo::Networks;
+
+/// The state of one interface at the time of the last refresh.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InterfaceSample {
+    pub counters: InterfaceCounters,
+    pub details: InterfaceDetails,
+}
+
+pub trait NetworkSource {
+    /// Reads the current state of all interfaces.
+    fn refresh(&mut self);
+    /// Returns the interfaces found by the last refresh.
+    fn interfaces(&self) -> Vec<InterfaceSample>;
+}
+
+/// Network source backed by sysinfo and the sysfs tree below `sysfs_root` (usually `/sys`).
+pub struct SysinfoNetworks {
+    networks: Networks,
+    sysfs_root: PathBuf,
+}
+
+impl Default for SysinfoNetworks {
+    fn default() -> Self {
+        Self::new("/sys")
+    }
+}
+
+impl SysinfoNetworks {
+    pub fn new(sysfs_root: impl Into<PathBuf>) -> Self {
+        Self {
+            networks: Networks::new_with_refreshed_list(),
+            sysfs_root: sysfs_root.into(),
+        }
+    }
+}
+
+impl NetworkSource for SysinfoNetworks {
+    fn refresh(&mut self) {
+        self.networks.refresh(true);
+    }
+
+    fn interfaces(&self) -> Vec<InterfaceSample> {
+        self.networks
+            .iter()
+            .map(|(name, data)| InterfaceSample {
+                counters: InterfaceCounters {
+                    received: data.total_received(),
+                    transmitted: data.total_transmitted(),
+                },
+                details: InterfaceDetails::new(name, data, read_link_info(&self.sysfs_root, name)),
+            })
+            .collect()
+    }
+}
+
+/// Network source returning the interfaces of the last `set_interfaces` call.
+#[derive(Debug, Default)]
+pub struct SampleNetworks {
+    interfaces: Vec<InterfaceSample>,
+}
+
+impl SampleNetworks {
+    /// Replaces the interfaces returned from now on.
+    pub fn set_interfaces(&mut self, interfaces: Vec<InterfaceSample>) {
+        self.interfaces = interfaces;
+    }
+}
+
+impl NetworkSource for SampleNetworks {
+    fn refresh(&mut self) {}
+
+    fn interfaces(&self) -> Vec<InterfaceSample> {
+        self.interfaces.clone()
+    }
+}
@@ -0,0 +1,231 @@
+//! This module detects OOM kills.
+//! It watches the system wide `oom_kill` counter from `/proc/vmstat` and the `oom_kill`
+//! counters of cgroup v2 `memory.events` files. Whenever a counter increases, an event
+//! is recorded. The victim is guessed from the processes that disappeared since the
+//! previous refresh: the OOM killer usually picks the process using the most memory,
+//! so the largest disappeared process is reported.
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Maximum number of events kept in the history.
+const MAX_EVENTS: usize = 100;
+
+/// How deep the cgroup hierarchy is searched for `memory.events` files.
+const CGROUP_SCAN_DEPTH: usize = 2;
+
+/// A process as seen by the OOM tracker: PID, name and resident memory in bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TrackedProcess {
+    pub pid: u32,
+    pub name: String,
+    pub memory: u64,
+}
+
+/// A detected OOM kill.
+/// `source` is either "system" for the `/proc/vmstat` counter or the cgroup path.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OomEvent {
+    pub time: DateTime<Local>,
+    pub source: String,
+    pub kills: u64,
+    pub victim: Option<TrackedProcess>,
+}
+
+impl OomEvent {
+    /// Formats the event as a single line for display.
+    pub fn describe(&self) -> String {
+        let victim = self
+            .victim
+            .as_ref()
+            .map(|p| format!("{} (PID {})", p.name, p.pid))
+            .unwrap_or_else(|| "unknown process".to_string());
+        format!(
+            "{} OOM kill [{}]: {}{}",
+            self.time.format("%Y-%m-%d %H:%M:%S"),
+            self.source,
+            victim,
+            if self.kills > 1 {
+                format!(" (+{} more)", self.kills - 1)
+            } else {
+                String::new()
+            }
+        )
+    }
+}
+
+/// OomTracker keeps the last seen counters and process list and records OOM events.
+pub struct OomTracker {
+    cgroup_root: PathBuf,
+    system_count: Option<u64>,
+    cgroup_counts: HashMap<String, u64>,
+    processes: HashMap<u32, TrackedProcess>,
+    events: Vec<OomEvent>,
+}
+
+impl Default for OomTracker {
+    fn default() -> Self {
+        Self::new("/sys/fs/cgroup")
+    }
+}
+
+impl OomTracker {
+    /// Creates a tracker reading cgroup `memory.events` files below the given root.
+    pub fn new(cgroup_root: impl Into<PathBuf>) -> Self {
+        Self {
+            cgroup_root: cgroup_root.into(),
+            system_count: None,
+            cgroup_counts: HashMap::new(),
+            processes: HashMap::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Returns all recorded events, oldest first.
+    pub fn events(&self) -> &[OomEvent] {
+        &self.events
+    }
+
+    /// Returns the most recent event.
+    pub fn latest_event(&self) -> Option<&OomEvent> {
+        self.events.last()
+    }
+
+    /// Reads the cgroup counters from disk and updates the tracker.
+    pub fn refresh(&mut self, system_count: Option<u64>, processes: Vec<TrackedProcess>) {
+        let cgroup_counts = read_cgroup_oom_counts(&self.cgroup_root);
+        self.update(system_count, &cgroup_counts, processes, Local::now());
+    }
+
+    /// Updates the tracker with a new sample.
+    /// The first sample only initializes the counters, events are recorded for increments
+    /// seen in later samples. A counter that went backwards is treated as reset.
+    pub fn update(
+        &mut self,
+        system_count: Option<u64>,
+        cgroup_counts: &HashMap<String, u64>,
+        processes: Vec<TrackedProcess>,
+        time: DateTime<Local>,
+    ) {
+        let current: HashMap<u32, TrackedProcess> =
+            processes.into_iter().map(|p| (p.pid, p)).collect();
+
+        // processes that existed in the previous sample but are gone now, largest first
+        let mut disappeared: Vec<TrackedProcess> = self
+            .processes
+            .values()
+            .filter(|p| !current.contains_key(&p.pid))
+            .cloned()
+            .collect();
+        disappeared.sort_by_key(|p| std::cmp::Reverse(p.memory));
+        let mut disappeared = disappeared.into_iter();
+
+        let system_kills = match (self.system_count, system_count) {
+            (Some(previous), Some(count)) if count > previous => count - previous,
+            _ => 0,
+        };
+        if system_count.is_some() {
+            self.system_count = system_count;
+        }
+
+        let mut cgroup_kills: Vec<(String, u64)> = cgroup_counts
+            .iter()
+            .filter_map(|(cgroup, count)| {
+                let previous = self.cgroup_counts.get(cgroup)?;
+                (count > previous).then(|| (cgroup.clone(), count - previous))
+            })
+            .collect();
+        self.cgroup_counts = cgroup_counts.clone();
+
+        // memory.events is hierarchical, a kill is also counted in every parent cgroup.
+        // Only the deepest cgroup with an increment is reported.
+        let sources: Vec<String> = cgroup_kills.iter().map(|(c, _)| c.clone()).collect();
+        cgroup_kills.retain(|(cgroup, _)| {
+            !sources
+                .iter()
+                .any(|other| other != cgroup && is_descendant(other, cgroup))
+        });
+        cgroup_kills.sort();
+
+        // Kills in a cgroup are also counted system wide,
+        // only the remainder is reported as a system event.
+        let explained: u64 = cgroup_kills.iter().map(|(_, kills)| kills).sum();
+        if system_kills > explained {
+            cgroup_kills.push(("system".to_string(), system_kills - explained));
+        }
+
+        for (source, kills) in cgroup_kills {
+            self.events.push(OomEvent {
+                time,
+                source,
+                kills,
+                victim: disappeared.next(),
+            });
+        }
+        if self.events.len() > MAX_EVENTS {
+            let excess = self.events.len() - MAX_EVENTS;
+            self.events.drain(..excess);
+        }
+
+        self.processes = current;
+    }
+}
+
+// Returns true if `path` is a cgroup below `parent`
+fn is_descendant(path: &str, parent: &str) -> bool {
+    parent == "/" || path.starts_with(&format!("{parent}/"))
+}
+
+/// Parses the `oom_kill` counter from the content of a cgroup `memory.events` file.
+///
+/// # Example
+/// ```
+/// use linux_dashboard::backend::oom::parse_memory_events;
+/// let content = "low 0\nhigh 0\nmax 12\noom 3\noom_kill 2\n";
+/// assert_eq!(parse_memory_events(content), Some(2));
+/// ```
+///
+pub fn parse_memory_events(content: &str) -> Option<u64> {
+    content.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        match (parts.next(), parts.next()) {
+            (Some("oom_kill"), Some(value)) => value.parse().ok(),
+            _ => None,
+        }
+    })
+}
+
+/// Collects the `oom_kill` counters of all `memory.events` files below the cgroup root,
+/// keyed by the cgroup path relative to the root ("/" for the root itself).
+pub fn read_cgroup_oom_counts(root: &Path) -> HashMap<String, u64> {
+    let mut counts = HashMap::new();
+    scan_cgroup(root, root, 0, &mut counts);
+    counts
+}
+
+fn scan_cgroup(root: &Path, dir: &Path, depth: usize, counts: &mut HashMap<String, u64>) {
+    if let Some(count) = fs::read_to_string(dir.join("memory.events"))
+        .ok()
+        .and_then(|content| parse_memory_events(&content))
+    {
+        let name = dir
+            .strip_prefix(root)
+            .map(|p| format!("/{}", p.display()))
+            .unwrap_or_else(|_| dir.display().to_string());
+        counts.insert(name, count);
+    }
+
+    if depth >= CGROUP_SCAN_DEPTH {
+        return;
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+            scan_cgroup(root, &entry.path(), depth + 1, counts);
+        }
+    }
+}
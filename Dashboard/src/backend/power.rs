@@ -0,0 +1,206 @@
+//! This module reads battery and AC adapter status from `class/power_supply` in sysfs.
+//! The sysfs root is configurable so the reader can be tested with fixture files.
+use super::sysfs::{read_number, read_trimmed};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Charging state of a battery as reported by the `status` file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatteryState {
+    Charging,
+    Discharging,
+    Full,
+    NotCharging,
+    Unknown,
+}
+
+impl BatteryState {
+    fn parse(status: &str) -> Self {
+        match status {
+            "Charging" => BatteryState::Charging,
+            "Discharging" => BatteryState::Discharging,
+            "Full" => BatteryState::Full,
+            "Not charging" => BatteryState::NotCharging,
+            _ => BatteryState::Unknown,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BatteryState::Charging => "Charging",
+            BatteryState::Discharging => "Discharging",
+            BatteryState::Full => "Full",
+            BatteryState::NotCharging => "Not charging",
+            BatteryState::Unknown => "Unknown",
+        }
+    }
+}
+
+/// Status of a single battery.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Battery {
+    pub name: String,
+    /// Charge in percent (0-100)
+    pub capacity: f64,
+    pub state: BatteryState,
+    /// Current charge or discharge rate in watts
+    pub power_watts: Option<f64>,
+    /// Estimated time until empty (discharging) or full (charging)
+    #[serde(
+        rename = "time_remaining_seconds",
+        serialize_with = "serialize_seconds",
+        deserialize_with = "deserialize_seconds"
+    )]
+    pub time_remaining: Option<Duration>,
+}
+
+// Exports the remaining time as whole seconds instead of serde's `{secs, nanos}`.
+fn serialize_seconds<S: serde::Serializer>(
+    duration: &Option<Duration>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    duration.map(|d| d.as_secs()).serialize(serializer)
+}
+
+fn deserialize_seconds<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Duration>, D::Error> {
+    Ok(Option::<u64>::deserialize(deserializer)?.map(Duration::from_secs))
+}
+
+/// Status of all power supplies: the batteries and whether an AC adapter is online.
+/// `ac_online` is `None` if no AC adapter was found.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PowerStatus {
+    pub batteries: Vec<Battery>,
+    pub ac_online: Option<bool>,
+}
+
+impl PowerStatus {
+    /// The battery panel is only shown on systems with at least one battery.
+    pub fn has_battery(&self) -> bool {
+        !self.batteries.is_empty()
+    }
+}
+
+/// PowerSupplyReader reads the power supplies below a sysfs root (usually `/sys`).
+pub struct PowerSupplyReader {
+    sysfs_root: PathBuf,
+}
+
+impl Default for PowerSupplyReader {
+    fn default() -> Self {
+        Self::new("/sys")
+    }
+}
+
+impl PowerSupplyReader {
+    pub fn new(sysfs_root: impl Into<PathBuf>) -> Self {
+        Self {
+            sysfs_root: sysfs_root.into(),
+        }
+    }
+
+    /// Reads all batteries and AC adapters.
+    /// Devices that report `scope` "Device" (e.g. wireless mice) are ignored.
+    pub fn read_status(&self) -> PowerStatus {
+        let mut status = PowerStatus::default();
+        let Ok(entries) = fs::read_dir(self.sysfs_root.join("class/power_supply")) else {
+            return status;
+        };
+        let mut dirs: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+        dirs.sort();
+
+        for dir in dirs {
+            if read_trimmed(&dir.join("scope")).as_deref() == Some("Device") {
+                continue;
+            }
+            match read_trimmed(&dir.join("type")).as_deref() {
+                Some("Battery") => {
+                    if let Some(battery) = read_battery(&dir) {
+                        status.batteries.push(battery);
+                    }
+                }
+                Some("Mains") => {
+                    let online = read_number(&dir.join("online")).unwrap_or(0.0) > 0.0;
+                    status.ac_online = Some(status.ac_online.unwrap_or(false) || online);
+                }
+                _ => {}
+            }
+        }
+        status
+    }
+}
+
+// Reads a battery directory. Energy based batteries report µWh and µW,
+// charge based batteries report µAh and µA and need the voltage to calculate watts.
+fn read_battery(dir: &Path) -> Option<Battery> {
+    let name = dir.file_name()?.to_string_lossy().to_string();
+    if read_number(&dir.join("present")) == Some(0.0) {
+        return None;
+    }
+    let state = BatteryState::parse(&read_trimmed(&dir.join("status")).unwrap_or_default());
+    let voltage = read_number(&dir.join("voltage_now")).map(|v| v / 1_000_000.0);
+
+    let (now, full, rate) = match read_number(&dir.join("energy_now")) {
+        Some(energy_now) => (
+            Some(energy_now),
+            read_number(&dir.join("energy_full")),
+            read_number(&dir.join("power_now")),
+        ),
+        // charge_* values are converted to energy using the current voltage
+        None => {
+            let to_energy = |v: f64| voltage.map(|volt| v * volt);
+            (
+                read_number(&dir.join("charge_now")).and_then(to_energy),
+                read_number(&dir.join("charge_full")).and_then(to_energy),
+                read_number(&dir.join("current_now")).and_then(to_energy),
+            )
+        }
+    };
+    // values are in micro units
+    let now = now.map(|v| v / 1_000_000.0);
+    let full = full.map(|v| v / 1_000_000.0);
+    let power_watts = rate.map(|v| v.abs() / 1_000_000.0).filter(|w| *w > 0.0);
+
+    let capacity = read_number(&dir.join("capacity"))
+        .or_else(|| Some(now? / full? * 100.0))
+        .unwrap_or(0.0)
+        .clamp(0.0, 100.0);
+
+    // Firmware may report negative or NaN values, they give no time
+    let time_remaining = match (state, now, full, power_watts) {
+        (BatteryState::Discharging, Some(now), _, Some(watts)) => {
+            Duration::try_from_secs_f64(now / watts * 3600.0).ok()
+        }
+        (BatteryState::Charging, Some(now), Some(full), Some(watts)) if full > now => {
+            Duration::try_from_secs_f64((full - now) / watts * 3600.0).ok()
+        }
+        _ => None,
+    };
+
+    Some(Battery {
+        name,
+        capacity,
+        state,
+        power_watts,
+        time_remaining,
+    })
+}
+
+/// Formats a duration as hours and minutes, e.g. "2h 05m".
+///
+/// # Example
+/// ```
+/// use linux_dashboard::backend::power::format_time_remaining;
+/// use std::time::Duration;
+/// assert_eq!(format_time_remaining(Duration::from_secs(7500)), "2h 05m");
+/// ```
+///
+pub fn format_time_remaining(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
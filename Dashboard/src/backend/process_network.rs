@@ -0,0 +1,201 @@
+//! This module estimates the network throughput of processes.
+//! Linux does not account network traffic per process, so two methods are used:
+//! - Processes in their own network namespace (containers, sandboxes) are attributed the
+//!   traffic of the interfaces in that namespace, read from `<pid>/net/dev`.
+//!   All processes of a namespace share the same value.
+//! - Other processes that own at least one TCP/UDP socket are attributed the rate of their
+//!   `rchar`/`wchar` counters from `<pid>/io`. This includes file I/O and is only a proxy.
+//!
+//! The method is reported with every value, so the process table can label it.
+//! Processes whose counters are not readable (other users without root) get no value.
+use super::connections::Connection;
+use super::network::{InterfaceCounters, RateTracker};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How the throughput of a process was determined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttributionMethod {
+    /// Traffic of the process' own network namespace
+    Namespace,
+    /// Read/write I/O of a process owning sockets, an estimate
+    IoProxy,
+}
+
+impl AttributionMethod {
+    /// Short label shown next to the value in the process table.
+    pub fn label(&self) -> &'static str {
+        match self {
+            AttributionMethod::Namespace => "netns",
+            AttributionMethod::IoProxy => "io~",
+        }
+    }
+}
+
+/// Estimated throughput of a process in bytes per second.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ProcessBandwidth {
+    pub download: f64,
+    pub upload: f64,
+    pub method: AttributionMethod,
+}
+
+impl ProcessBandwidth {
+    pub fn total(&self) -> f64 {
+        self.download + self.upload
+    }
+}
+
+/// Parses the content of a `net/dev` file and sums the counters of all interfaces
+/// except loopback.
+///
+/// # Example
+/// ```
+/// use linux_dashboard::backend::process_network::parse_net_dev;
+/// let content = "Inter-|   Receive |  Transmit\n face |bytes packets|bytes packets\n    lo: 100 1 0 0 0 0 0 0 100 1 0 0 0 0 0 0\n  eth0: 2000 10 0 0 0 0 0 0 3000 12 0 0 0 0 0 0\n";
+/// let counters = parse_net_dev(content);
+/// assert_eq!((counters.received, counters.transmitted), (2000, 3000));
+/// ```
+///
+pub fn parse_net_dev(content: &str) -> InterfaceCounters {
+    let mut counters = InterfaceCounters::default();
+    for line in content.lines().skip(2) {
+        let Some((name, values)) = line.split_once(':') else {
+            continue;
+        };
+        if name.trim() == "lo" {
+            continue;
+        }
+        let values: Vec<u64> = values
+            .split_whitespace()
+            .filter_map(|v| v.parse().ok())
+            .collect();
+        if values.len() >= 9 {
+            counters.received += values[0];
+            counters.transmitted += values[8];
+        }
+    }
+    counters
+}
+
+// Reads the rchar/wchar counters of `<pid>/io` as received/transmitted bytes.
+fn read_io_counters(dir: &Path) -> Option<InterfaceCounters> {
+    let content = fs::read_to_string(dir.join("io")).ok()?;
+    let mut counters = InterfaceCounters::default();
+    for line in content.lines() {
+        match line.split_once(':') {
+            Some(("rchar", value)) => counters.received = value.trim().parse().ok()?,
+            Some(("wchar", value)) => counters.transmitted = value.trim().parse().ok()?,
+            _ => {}
+        }
+    }
+    Some(counters)
+}
+
+fn read_namespace(dir: &Path) -> Option<String> {
+    fs::read_link(dir.join("ns/net"))
+        .ok()
+        .map(|target| target.to_string_lossy().to_string())
+}
+
+/// ProcessNetworkTracker estimates the throughput of all processes below a procfs root
+/// (usually `/proc`). Processes in the network namespace of the dashboard (`self`) are
+/// treated as host processes. If that namespace is not readable, no process is attributed
+/// namespace traffic, otherwise every process would get the traffic of the whole host.
+pub struct ProcessNetworkTracker {
+    proc_root: PathBuf,
+    rate_tracker: RateTracker,
+}
+
+impl Default for ProcessNetworkTracker {
+    fn default() -> Self {
+        Self::new("/proc")
+    }
+}
+
+impl ProcessNetworkTracker {
+    pub fn new(proc_root: impl Into<PathBuf>) -> Self {
+        Self {
+            proc_root: proc_root.into(),
+            rate_tracker: RateTracker::default(),
+        }
+    }
+
+    /// Samples the counters at the given timestamp (seconds since the Unix epoch) and returns
+    /// the throughput since the previous sample per PID.
+    /// `connections` are the current sockets, used to find the processes owning sockets.
+    /// Processes seen for the first time have no value yet.
+    pub fn update(
+        &mut self,
+        timestamp: f64,
+        connections: &[Connection],
+    ) -> HashMap<u32, ProcessBandwidth> {
+        let host_namespace = read_namespace(&self.proc_root.join("self"));
+        let socket_owners: HashSet<u32> = connections
+            .iter()
+            .filter_map(|connection| connection.pid)
+            .collect();
+
+        // The counters are keyed by "ns:<namespace>" or "io:<pid>",
+        // so a namespace shared by several processes is sampled once.
+        let mut counters: Vec<(String, InterfaceCounters)> = Vec::new();
+        let mut sources: Vec<(u32, String, AttributionMethod)> = Vec::new();
+        let Ok(entries) = fs::read_dir(&self.proc_root) else {
+            return HashMap::new();
+        };
+        for entry in entries.flatten() {
+            let Some(pid) = entry
+                .file_name()
+                .to_str()
+                .and_then(|n| n.parse::<u32>().ok())
+            else {
+                continue;
+            };
+            let dir = entry.path();
+
+            let namespace = host_namespace
+                .as_ref()
+                .and_then(|host| read_namespace(&dir).filter(|ns| ns != host));
+            if let Some(namespace) = namespace {
+                let key = format!("ns:{namespace}");
+                if !counters.iter().any(|(k, _)| *k == key) {
+                    let Ok(content) = fs::read_to_string(dir.join("net/dev")) else {
+                        continue;
+                    };
+                    counters.push((key.clone(), parse_net_dev(&content)));
+                }
+                sources.push((pid, key, AttributionMethod::Namespace));
+            } else if socket_owners.contains(&pid)
+                && let Some(io) = read_io_counters(&dir)
+            {
+                let key = format!("io:{pid}");
+                counters.push((key.clone(), io));
+                sources.push((pid, key, AttributionMethod::IoProxy));
+            }
+        }
+
+        let rates: HashMap<String, _> = self
+            .rate_tracker
+            .update(timestamp, &counters)
+            .into_iter()
+            .filter_map(|(key, rate)| Some((key, rate?)))
+            .collect();
+        sources
+            .into_iter()
+            .filter_map(|(pid, key, method)| {
+                let rate = rates.get(&key)?;
+                Some((
+                    pid,
+                    ProcessBandwidth {
+                        download: rate.download,
+                        upload: rate.upload,
+                        method,
+                    },
+                ))
+            })
+            .collect()
+    }
+}
@@ -1,13 +1,10 @@
-//! This module provides a table of processes with relevant information.  
-//! This module fetches and formats process information from the system,  
+//! This module lists the processes of the system with relevant information,  
 //! including PID, name, status, CPU usage, and memory usage.  
 //! It allows sorting of processes based on various criteria such as CPU usage, memory usage, PID, and name.  
-use crate::backend::converter::format_bytes;
-use ratatui::{
-    style::{Color, Style},
-    widgets::{Cell, Row},
-};
-use sysinfo::{Pid, Process, Signal, System};
+use crate::backend::process_network::ProcessBandwidth;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use sysinfo::{Pid, Signal, System};
 
 /// Enum for the sort order of processes  
 /// This enum defines different sorting criteria for the process list,  
@@ -26,6 +23,8 @@ pub enum SortOrder {
     PidDesc,
     NameAsc,
     NameDesc,
+    NetworkAsc,
+    NetworkDesc,
 }
 /// Manually implements Default for SortOrder.  
 /// This makes it possible to define a default sort order,  
@@ -36,108 +35,117 @@ impl Default for SortOrder {
         SortOrder::CpuDesc // Standard-Sortierung nach CPU-Auslastung absteigend
     }
 }
-// s is short for string slice (the result is better performance bc, the Compiler is told to not look at the whole string, but only the relevant part).
-// It then gets converted into a String to return a new owned String.
-// Short: Truncates a string to a maximum length and appends "..." if it exceeds that length.
-fn truncate_string(s: &str, max_length: usize) -> String {
-    let char_count = s.chars().count();
-    if char_count <= max_length {
-        s.to_string()
-    } else {
-        let truncated: String = s.chars().take(max_length - 3).collect();
-        format!("{truncated}...")
-    }
-}
-
-/// This function creates a vector of rows representing the processes in the system.
-/// It sorts the processes based on the specified `SortOrder` and formats them into rows for display.
-/// # Arguments
-/// * `sys` - A reference to the `System` instance containing process information.
-/// * `sort_order` - The `SortOrder` enum that specifies how to sort the processes.
-/// # Returns
-/// A vector of `Row` instances, each representing a process with its PID, name, status, CPU usage, and memory usage.
-/// The rows are styled with a default style and the header row is styled with a yellow foreground color.
-pub fn create_process_rows(sys: &System, sort_order: SortOrder) -> Vec<Row<'static>> {
-    create_process_rows_filtered(sys, sort_order, "")
+/// A process with the values shown in the process table.
+/// `network` is the estimated throughput, `None` if it could not be estimated.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    pub status: String,
+    pub cpu_usage: f32,
+    pub memory: u64,
+    pub network: Option<ProcessBandwidth>,
 }
 
-pub fn create_process_rows_filtered(
-    sys: &System,
+/// Returns the processes whose name contains `filter` (case insensitive),
+/// sorted by the given sort order. This is the order of the rows in the process table.
+/// Processes without a network estimate sort as zero throughput.
+pub fn sort_processes(
+    processes: &[ProcessInfo],
     sort_order: SortOrder,
     filter: &str,
-) -> Vec<Row<'static>> {
+) -> Vec<ProcessInfo> {
     let filter_lower = filter.to_lowercase();
-    let mut processes: Vec<(&Pid, &Process)> = sys
-        .processes()
+    let mut processes: Vec<ProcessInfo> = processes
         .iter()
-        .filter(|(_, p)| {
-            filter_lower.is_empty()
-                || p.name()
-                    .to_string_lossy()
-                    .to_lowercase()
-                    .contains(&filter_lower)
-        })
+        .filter(|p| filter_lower.is_empty() || p.name.to_lowercase().contains(&filter_lower))
+        .cloned()
         .collect();
     match sort_order {
         SortOrder::CpuAsc => {
             processes.sort_by(|a, b| {
-                a.1.cpu_usage()
-                    .partial_cmp(&b.1.cpu_usage())
+                a.cpu_usage
+                    .partial_cmp(&b.cpu_usage)
                     .unwrap_or(std::cmp::Ordering::Equal)
             });
         }
         SortOrder::CpuDesc => {
             processes.sort_by(|a, b| {
-                a.1.cpu_usage()
-                    .partial_cmp(&b.1.cpu_usage())
+                a.cpu_usage
+                    .partial_cmp(&b.cpu_usage)
                     .unwrap_or(std::cmp::Ordering::Equal)
                     .reverse()
             });
         }
         SortOrder::MemoryAsc => {
-            processes.sort_by_key(|a| a.1.memory());
+            processes.sort_by_key(|a| a.memory);
         }
         SortOrder::MemoryDesc => {
-            processes.sort_by_key(|a| std::cmp::Reverse(a.1.memory()));
+            processes.sort_by_key(|a| std::cmp::Reverse(a.memory));
         }
-        // Add PidAsc, PidDesc, NameAsc, NameDesc similarly
         SortOrder::PidAsc => {
-            processes.sort_by_key(|a| a.0); // a.0 is the Pid
+            processes.sort_by_key(|a| a.pid);
         }
         SortOrder::PidDesc => {
-            processes.sort_by_key(|a| std::cmp::Reverse(a.0));
+            processes.sort_by_key(|a| std::cmp::Reverse(a.pid));
         }
         SortOrder::NameAsc => {
-            processes.sort_by_key(|a| a.1.name().to_ascii_lowercase()); // a.1.name() is the process name
+            processes.sort_by_key(|a| a.name.to_ascii_lowercase());
         }
         SortOrder::NameDesc => {
-            processes.sort_by_key(|a| std::cmp::Reverse(a.1.name().to_ascii_lowercase()));
+            processes.sort_by_key(|a| std::cmp::Reverse(a.name.to_ascii_lowercase()));
+        }
+        SortOrder::NetworkAsc | SortOrder::NetworkDesc => {
+            let total = |p: &ProcessInfo| p.network.map_or(0.0, |b| b.total());
+            processes.sort_by(|a, b| {
+                let order = total(a)
+                    .partial_cmp(&total(b))
+                    .unwrap_or(std::cmp::Ordering::Equal);
+                if sort_order == SortOrder::NetworkDesc {
+                    order.reverse()
+                } else {
+                    order
+                }
+            });
         }
     }
+    processes
+}
 
-    // create header row
-    let header = Row::new(vec![
-        Cell::from("PID"),
-        Cell::from("Name"),
-        Cell::from("Status"),
-        Cell::from("CPU (%)"),
-        Cell::from("Memory"),
-    ])
-    .style(Style::default().fg(Color::Yellow));
+/// Returns the position of a process in the (filtered and sorted) process table,
+/// not counting the header row, or `None` if the process is not listed.
+pub fn process_position(
+    processes: &[ProcessInfo],
+    sort_order: SortOrder,
+    filter: &str,
+    pid: u32,
+) -> Option<usize> {
+    sort_processes(processes, sort_order, filter)
+        .iter()
+        .position(|process| process.pid == pid)
+}
 
-    // converts the process data into rows for the table
-    // Each row contains the PID, truncated name, status, CPU usage, and memory usage.
-    let mut rows = vec![header]; // adds header in the first row
-    rows.extend(processes.iter().map(|(pid, process)| {
-        Row::new(vec![
-            Cell::from(pid.to_string()),
-            Cell::from(truncate_string(&process.name().to_string_lossy(), 30)),
-            Cell::from(format!("{:?}", process.status())),
-            Cell::from(format!("{:.2}", process.cpu_usage())),
-            Cell::from(format_bytes(process.memory())),
-        ])
-    }));
-    rows
+/// Returns the processes whose name contains `filter` (case insensitive), sorted by `sort_order`.
+/// The network estimate of every process is taken from `bandwidth`.
+pub fn list_processes(
+    sys: &System,
+    sort_order: SortOrder,
+    filter: &str,
+    bandwidth: &HashMap<u32, ProcessBandwidth>,
+) -> Vec<ProcessInfo> {
+    let processes: Vec<ProcessInfo> = sys
+        .processes()
+        .iter()
+        .map(|(pid, process)| ProcessInfo {
+            pid: pid.as_u32(),
+            name: process.name().to_string_lossy().to_string(),
+            status: format!("{:?}", process.status()),
+            cpu_usage: process.cpu_usage(),
+            memory: process.memory(),
+            network: bandwidth.get(&pid.as_u32()).copied(),
+        })
+        .collect();
+    sort_processes(&processes, sort_order, filter)
 }
 
 /// Attempts to kill a process by PID.
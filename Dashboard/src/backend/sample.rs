@@ -0,0 +1,118 @@
+//! This module bundles everything the dashboard reads from the system in one refresh
+//! into a `Sample`. The terminal UI is driven by samples only, so a sample collected live
+//! and a sample read back from a recording are shown the same way.
+use super::{
+    connections::{Connection, ConnectionReader},
+    cpu::CpuStats,
+    disk::{DiskInfo, list_disks},
+    history::unix_now,
+    memory::MemInfo,
+    netstat::ProtocolStats,
+    network_source::{InterfaceSample, NetworkSource, SysinfoNetworks},
+    oom::{TrackedProcess, read_cgroup_oom_counts},
+    power::{PowerStatus, PowerSupplyReader},
+    process_network::ProcessNetworkTracker,
+    processes::{ProcessInfo, SortOrder, list_processes},
+    sensors::{Sensor, SensorReader},
+    vmstat::VmStat,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use sysinfo::System;
+
+/// The data of all subsystems at one refresh.
+/// Counters are cumulative, the rates are computed by the histories the sample is fed to.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Sample {
+    /// Seconds since the Unix epoch
+    pub timestamp: f64,
+    pub uptime_seconds: u64,
+    pub cpu: CpuStats,
+    pub memory: MemInfo,
+    /// Missing in recordings made before disks were recorded
+    #[serde(default)]
+    pub disks: Vec<DiskInfo>,
+    pub vmstat: Option<VmStat>,
+    pub protocols: Option<ProtocolStats>,
+    pub interfaces: Vec<InterfaceSample>,
+    /// All processes, unsorted, with their estimated network throughput
+    pub processes: Vec<ProcessInfo>,
+    pub connections: Vec<Connection>,
+    /// `oom_kill` counters of the cgroups, keyed by cgroup path
+    pub oom_cgroup_counts: HashMap<String, u64>,
+    pub sensors: Vec<Sensor>,
+    pub power: PowerStatus,
+}
+
+impl Sample {
+    /// Returns the processes in the form the OOM tracker remembers them.
+    pub fn tracked_processes(&self) -> Vec<TrackedProcess> {
+        self.processes
+            .iter()
+            .map(|process| TrackedProcess {
+                pid: process.pid,
+                name: process.name.clone(),
+                memory: process.memory,
+            })
+            .collect()
+    }
+}
+
+/// SampleCollector reads a sample from a refreshed `System` and the readers of the
+/// other subsystems. It keeps the state needed between two samples, e.g. the counters
+/// of the per-process network estimation.
+pub struct SampleCollector {
+    networks: SysinfoNetworks,
+    connection_reader: ConnectionReader,
+    process_network: ProcessNetworkTracker,
+    sensor_reader: SensorReader,
+    power_reader: PowerSupplyReader,
+    cgroup_root: PathBuf,
+}
+
+impl Default for SampleCollector {
+    fn default() -> Self {
+        Self {
+            networks: SysinfoNetworks::default(),
+            connection_reader: ConnectionReader::default(),
+            process_network: ProcessNetworkTracker::default(),
+            sensor_reader: SensorReader::default(),
+            power_reader: PowerSupplyReader::default(),
+            cgroup_root: PathBuf::from("/sys/fs/cgroup"),
+        }
+    }
+}
+
+impl SampleCollector {
+    /// Collects a sample now. `sys` should have been refreshed just before.
+    pub fn collect(&mut self, sys: &System) -> Sample {
+        self.collect_at(sys, unix_now())
+    }
+
+    /// Like `collect`, but uses the given timestamp (seconds since the Unix epoch)
+    /// instead of the current time.
+    pub fn collect_at(&mut self, sys: &System, timestamp: f64) -> Sample {
+        self.networks.refresh();
+        // The sockets are read once and shared by the connections view,
+        // the listening ports panel and the per-process network estimation.
+        let connections = self.connection_reader.read_connections();
+        let bandwidth = self.process_network.update(timestamp, &connections);
+
+        Sample {
+            timestamp,
+            uptime_seconds: System::uptime(),
+            cpu: CpuStats::collect(sys),
+            memory: MemInfo::collect(sys),
+            disks: list_disks(),
+            vmstat: VmStat::read(),
+            protocols: ProtocolStats::read(),
+            interfaces: self.networks.interfaces(),
+            processes: list_processes(sys, SortOrder::PidAsc, "", &bandwidth),
+            connections,
+            oom_cgroup_counts: read_cgroup_oom_counts(&self.cgroup_root),
+            sensors: self.sensor_reader.read_sensors(),
+            power: self.power_reader.read_status(),
+        }
+    }
+}
@@ -0,0 +1,217 @@
+//! This module reads temperature and fan sensors from sysfs.
+//! Sensors are read from `class/hwmon` (CPU package and cores, NVMe, chipset, fans)
+//! and `class/thermal` (thermal zones) below a configurable sysfs root,
+//! so the reader can be pointed at a fake directory tree in tests.
+use super::sysfs::{read_number, read_trimmed};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Kind of a sensor, determines the unit of the values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SensorKind {
+    /// Temperature in degree Celsius
+    Temperature,
+    /// Fan speed in RPM
+    Fan,
+}
+
+/// How close a sensor value is to its thresholds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SensorLevel {
+    Normal,
+    /// At or above the high threshold, or at or below the low threshold
+    High,
+    /// At or above the critical threshold
+    Critical,
+}
+
+/// A single sensor reading with its optional thresholds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sensor {
+    pub chip: String,
+    pub label: String,
+    pub kind: SensorKind,
+    pub value: f64,
+    /// Lowest value without a problem, the minimum speed of a fan
+    #[serde(default)]
+    pub low: Option<f64>,
+    pub high: Option<f64>,
+    pub critical: Option<f64>,
+}
+
+impl Sensor {
+    /// Returns the level of the current value compared to the thresholds.
+    pub fn level(&self) -> SensorLevel {
+        match (self.high, self.critical) {
+            (_, Some(critical)) if self.value >= critical => SensorLevel::Critical,
+            (Some(high), _) if self.value >= high => SensorLevel::High,
+            _ if self.low.is_some_and(|low| self.value <= low) => SensorLevel::High,
+            _ => SensorLevel::Normal,
+        }
+    }
+
+    /// Formats a value in the unit of this sensor.
+    pub fn format_value(&self, value: f64) -> String {
+        match self.kind {
+            SensorKind::Temperature => format!("{value:.1}°C"),
+            SensorKind::Fan => format!("{value:.0} RPM"),
+        }
+    }
+}
+
+/// SensorReader reads all sensors below a sysfs root (usually `/sys`).
+pub struct SensorReader {
+    sysfs_root: PathBuf,
+}
+
+impl Default for SensorReader {
+    fn default() -> Self {
+        Self::new("/sys")
+    }
+}
+
+impl SensorReader {
+    pub fn new(sysfs_root: impl Into<PathBuf>) -> Self {
+        Self {
+            sysfs_root: sysfs_root.into(),
+        }
+    }
+
+    /// Reads all hwmon sensors followed by all thermal zones.
+    /// Missing directories or unreadable files are skipped.
+    pub fn read_sensors(&self) -> Vec<Sensor> {
+        let mut sensors = self.read_hwmon();
+        sensors.extend(self.read_thermal_zones());
+        sensors
+    }
+
+    fn read_hwmon(&self) -> Vec<Sensor> {
+        let mut sensors = Vec::new();
+        for dir in sorted_entries(&self.sysfs_root.join("class/hwmon"), "hwmon") {
+            let chip = read_trimmed(&dir.join("name")).unwrap_or_else(|| file_name(&dir));
+
+            for index in channel_indices(&dir, "temp") {
+                let prefix = format!("temp{index}");
+                let Some(value) = read_number(&dir.join(format!("{prefix}_input"))) else {
+                    continue;
+                };
+                sensors.push(Sensor {
+                    chip: chip.clone(),
+                    label: read_trimmed(&dir.join(format!("{prefix}_label")))
+                        .unwrap_or_else(|| prefix.clone()),
+                    kind: SensorKind::Temperature,
+                    // hwmon reports temperatures in millidegree Celsius
+                    value: value / 1000.0,
+                    low: None,
+                    high: read_number(&dir.join(format!("{prefix}_max"))).map(|v| v / 1000.0),
+                    critical: read_number(&dir.join(format!("{prefix}_crit"))).map(|v| v / 1000.0),
+                });
+            }
+
+            for index in channel_indices(&dir, "fan") {
+                let prefix = format!("fan{index}");
+                let Some(value) = read_number(&dir.join(format!("{prefix}_input"))) else {
+                    continue;
+                };
+                sensors.push(Sensor {
+                    chip: chip.clone(),
+                    label: read_trimmed(&dir.join(format!("{prefix}_label")))
+                        .unwrap_or_else(|| prefix.clone()),
+                    kind: SensorKind::Fan,
+                    value,
+                    // A fan at full speed is fine, a fan below its minimum has stalled.
+                    // A minimum of 0 is not set, fans that may stop report it.
+                    low: read_number(&dir.join(format!("{prefix}_min"))).filter(|min| *min > 0.0),
+                    high: None,
+                    critical: None,
+                });
+            }
+        }
+        sensors
+    }
+
+    fn read_thermal_zones(&self) -> Vec<Sensor> {
+        let mut sensors = Vec::new();
+        for dir in sorted_entries(&self.sysfs_root.join("class/thermal"), "thermal_zone") {
+            let Some(value) = read_number(&dir.join("temp")) else {
+                continue;
+            };
+
+            // Trip points define the thresholds: "critical" is the critical temperature,
+            // the lowest "hot" or "passive" trip point is used as the high threshold.
+            let mut high: Option<f64> = None;
+            let mut critical: Option<f64> = None;
+            for index in 0.. {
+                let Some(trip_type) = read_trimmed(&dir.join(format!("trip_point_{index}_type")))
+                else {
+                    break;
+                };
+                let Some(temp) = read_number(&dir.join(format!("trip_point_{index}_temp"))) else {
+                    continue;
+                };
+                let temp = temp / 1000.0;
+                match trip_type.as_str() {
+                    "critical" => critical = Some(temp),
+                    "hot" | "passive" => high = Some(high.map_or(temp, |h: f64| h.min(temp))),
+                    _ => {}
+                }
+            }
+
+            sensors.push(Sensor {
+                chip: file_name(&dir),
+                label: read_trimmed(&dir.join("type")).unwrap_or_else(|| file_name(&dir)),
+                kind: SensorKind::Temperature,
+                value: value / 1000.0,
+                low: None,
+                high,
+                critical,
+            });
+        }
+        sensors
+    }
+}
+
+// Returns the directories in `dir` whose name starts with `prefix`, sorted naturally
+// so that hwmon10 comes after hwmon2.
+fn sorted_entries(dir: &Path, prefix: &str) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<(u32, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let index = name.strip_prefix(prefix)?.parse().ok()?;
+            Some((index, entry.path()))
+        })
+        .collect();
+    paths.sort();
+    paths.into_iter().map(|(_, path)| path).collect()
+}
+
+// Returns the sorted channel numbers of all `<kind>N_input` files in a hwmon directory.
+fn channel_indices(dir: &Path, kind: &str) -> Vec<u32> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut indices: Vec<u32> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.strip_prefix(kind)?
+                .strip_suffix("_input")?
+                .parse()
+                .ok()
+        })
+        .collect();
+    indices.sort();
+    indices
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
@@ -0,0 +1,18 @@
+//! This module contains helper functions for reading single value files
+//! from sysfs and procfs.
+use std::fs;
+use std::path::Path;
+
+/// Reads a file and returns its trimmed content, or `None` if the file
+/// cannot be read or is empty.
+pub(crate) fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Reads a file containing a single number.
+pub(crate) fn read_number(path: &Path) -> Option<f64> {
+    read_trimmed(path)?.parse().ok()
+}
@@ -0,0 +1,69 @@
+//! This module reads the kernel's virtual memory counters from `/proc/vmstat`.
+//! All values are cumulative since boot, rates have to be computed from the difference
+//! between two samples.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Selected counters from `/proc/vmstat`.
+/// Swap counters are in pages, fault counters are event counts.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VmStat {
+    pub pswpin: u64,
+    pub pswpout: u64,
+    pub pgfault: u64,
+    pub pgmajfault: u64,
+    pub oom_kill: u64,
+}
+
+impl VmStat {
+    /// Reads `/proc/vmstat`, returns `None` if the file is not available.
+    pub fn read() -> Option<Self> {
+        Self::read_from(Path::new("/proc/vmstat"))
+    }
+
+    /// Reads and parses a vmstat file from the given path.
+    pub fn read_from(path: &Path) -> Option<Self> {
+        fs::read_to_string(path)
+            .ok()
+            .map(|content| parse_vmstat(&content))
+    }
+}
+
+/// Parses the content of `/proc/vmstat`.
+/// Unknown keys are ignored, missing keys stay at zero.
+///
+/// # Example
+/// ```
+/// use linux_dashboard::backend::vmstat::parse_vmstat;
+/// let stat = parse_vmstat("pswpin 10\npswpout 20\npgmajfault 3\n");
+/// assert_eq!(stat.pswpin, 10);
+/// assert_eq!(stat.pswpout, 20);
+/// assert_eq!(stat.pgmajfault, 3);
+/// ```
+///
+pub fn parse_vmstat(content: &str) -> VmStat {
+    let mut stat = VmStat::default();
+
+    for line in content.lines() {
+        let mut parts = line.split_whitespace();
+        let (Some(key), Some(value)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        let Ok(value) = value.parse::<u64>() else {
+            continue;
+        };
+
+        match key {
+            "pswpin" => stat.pswpin = value,
+            "pswpout" => stat.pswpout = value,
+            "pgfault" => stat.pgfault = value,
+            "pgmajfault" => stat.pgmajfault = value,
+            "oom_kill" => stat.oom_kill = value,
+            _ => {}
+        }
+    }
+
+    stat
+}
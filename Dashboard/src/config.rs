@@ -0,0 +1,248 @@
+//! This module reads the configuration file of the dashboard.
+//! The file is TOML and lives at `$XDG_CONFIG_HOME/linux_dashboard/config.toml`,
+//! falling back to `~/.config/linux_dashboard/config.toml`. Every setting is optional,
+//! a missing file is the same as an empty one.
+//!
+//! ```toml
+//! [[alerts]]
+//! rule = "cpu.total > 90 for 30s"
+//!
+//! [[alerts]]
+//! name = "Root disk almost full"
+//! rule = "disk./.used > 95%"
+//! severity = "critical"
+//! resolve_after = "5m"
+//!
+//! [[actions]]
+//! type = "command"
+//! command = "curl -d \"$DASHBOARD_ALERT_RULE: $DASHBOARD_ALERT_VALUE\" https://ntfy.sh/team"
+//! on = ["fired"]
+//!
+//! [history]
+//! enabled = true
+//! tiers = ["24h@10s", "7d@1m"]
+//! ```
+use crate::actions::{ActionKind, AlertAction};
+use crate::backend::alerts::{AlertEventKind, AlertRule, Severity, parse_duration};
+use crate::store::{DEFAULT_TIERS, Tier};
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The settings read from the configuration file.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub alerts: Vec<AlertConfig>,
+    pub actions: Vec<ActionConfig>,
+    pub history: HistoryConfig,
+}
+
+/// An `[[alerts]]` entry of the configuration file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlertConfig {
+    /// Shown instead of the rule expression
+    pub name: Option<String>,
+    /// The rule expression, see `AlertRule::parse`
+    pub rule: String,
+    #[serde(default)]
+    pub severity: Severity,
+    /// How long the condition has to be false before the alert resolves, e.g. `5m`
+    pub resolve_after: Option<String>,
+}
+
+/// The kinds of actions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActionType {
+    Command,
+    Log,
+    Notify,
+}
+
+/// An `[[actions]]` entry of the configuration file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ActionConfig {
+    #[serde(rename = "type")]
+    pub kind: ActionType,
+    /// The shell command of a `command` action
+    pub command: Option<String>,
+    /// The file of a `log` action, `~/` is the home directory
+    pub path: Option<String>,
+    /// The events the action runs for, both fired and resolved by default
+    pub on: Option<Vec<AlertEventKind>>,
+    /// Only run for alerts of this severity
+    pub severity: Option<Severity>,
+    /// Minimum time between two runs for the same alert, e.g. `10m`
+    pub cooldown: Option<String>,
+    pub max_per_hour: Option<usize>,
+}
+
+/// The `[history]` section of the configuration file.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HistoryConfig {
+    /// Whether the dashboard writes the history store and shows it
+    pub enabled: bool,
+    /// Directory of the store, `~/` is the home directory
+    pub path: Option<String>,
+    /// Tiers written as `RETENTION@RESOLUTION`, `DEFAULT_TIERS` if missing
+    pub tiers: Option<Vec<String>>,
+}
+
+impl Config {
+    /// Returns the path of the configuration file, `None` if no home directory is known.
+    pub fn default_path() -> Option<PathBuf> {
+        let config_home = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(config_home.join("linux_dashboard").join("config.toml"))
+    }
+
+    /// Reads the configuration file at the default path, if there is one.
+    pub fn load_default() -> Result<Self, String> {
+        match Self::default_path() {
+            Some(path) => Self::load_optional(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Reads the configuration file at `path`, a missing file gives the default configuration.
+    pub fn load_optional(path: &Path) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text).map_err(|e| format!("{}: {e}", path.display())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("cannot read {}: {e}", path.display())),
+        }
+    }
+
+    /// Reads the configuration file at `path`.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text =
+            fs::read_to_string(path).map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+        Self::parse(&text).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    /// Parses the content of a configuration file. The alert rules are checked as well,
+    /// so a typo in a rule is reported at startup instead of the rule never firing.
+    ///
+    /// # Example
+    /// ```
+    /// use linux_dashboard::config::Config;
+    /// let config = Config::parse("[[alerts]]\nrule = \"mem.available < 1G\"").unwrap();
+    /// assert_eq!(config.alert_rules().unwrap().len(), 1);
+    /// assert!(Config::parse("[[alerts]]\nrule = \"mem.available < lots\"").is_err());
+    /// ```
+    ///
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let config: Self = toml::from_str(text).map_err(|e| e.to_string())?;
+        config.alert_rules()?;
+        config.alert_actions()?;
+        config.history_tiers()?;
+        Ok(config)
+    }
+
+    /// Returns the parsed alert rules, in the order of the file.
+    pub fn alert_rules(&self) -> Result<Vec<AlertRule>, String> {
+        self.alerts
+            .iter()
+            .map(|alert| {
+                let mut rule = AlertRule::parse(&alert.rule)?;
+                if let Some(name) = &alert.name {
+                    rule.name = name.clone();
+                }
+                rule.severity = alert.severity;
+                if let Some(resolve_after) = &alert.resolve_after {
+                    rule.resolve_after = parse_duration(resolve_after)
+                        .map_err(|e| format!("alert '{}': resolve_after: {e}", rule.name))?;
+                }
+                Ok(rule)
+            })
+            .collect()
+    }
+
+    /// Returns the parsed alert actions, in the order of the file.
+    pub fn alert_actions(&self) -> Result<Vec<AlertAction>, String> {
+        self.actions
+            .iter()
+            .map(|config| {
+                let kind = match (config.kind, &config.command, &config.path) {
+                    (ActionType::Command, Some(command), None) => {
+                        ActionKind::Command(command.clone())
+                    }
+                    (ActionType::Log, None, Some(path)) => ActionKind::Log(expand_home(path)),
+                    (ActionType::Notify, None, None) => ActionKind::Notify,
+                    (ActionType::Command, _, _) => {
+                        return Err("a command action needs 'command' and no 'path'".to_string());
+                    }
+                    (ActionType::Log, _, _) => {
+                        return Err("a log action needs 'path' and no 'command'".to_string());
+                    }
+                    (ActionType::Notify, _, _) => {
+                        return Err("a notify action takes no 'command' or 'path'".to_string());
+                    }
+                };
+                let mut action = AlertAction::new(kind);
+                if let Some(on) = &config.on {
+                    action.on = on.clone();
+                }
+                action.severity = config.severity;
+                if let Some(cooldown) = &config.cooldown {
+                    action.cooldown =
+                        parse_duration(cooldown).map_err(|e| format!("cooldown: {e}"))?;
+                }
+                if let Some(max_per_hour) = config.max_per_hour {
+                    action.max_per_hour = max_per_hour;
+                }
+                Ok(action)
+            })
+            .collect()
+    }
+
+    /// Returns the directory of the history store: the configured path, otherwise
+    /// `$XDG_DATA_HOME/linux_dashboard/history` falling back to
+    /// `~/.local/share/linux_dashboard/history`. `None` if no home directory is known.
+    pub fn history_dir(&self) -> Option<PathBuf> {
+        if let Some(path) = &self.history.path {
+            return Some(expand_home(path));
+        }
+        let data_home = std::env::var_os("XDG_DATA_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME").map(|home| Path::new(&home).join(".local").join("share"))
+            })?;
+        Some(data_home.join("linux_dashboard").join("history"))
+    }
+
+    /// Returns the parsed tiers of the history store.
+    pub fn history_tiers(&self) -> Result<Vec<Tier>, String> {
+        match &self.history.tiers {
+            Some(tiers) if tiers.is_empty() => Err("history: no tiers".to_string()),
+            Some(tiers) => tiers
+                .iter()
+                .map(|tier| Tier::parse(tier).map_err(|e| format!("history: {e}")))
+                .collect(),
+            None => Ok(DEFAULT_TIERS.to_vec()),
+        }
+    }
+}
+
+/// Replaces a leading `~/` with the home directory.
+///
+/// # Example
+/// ```
+/// use linux_dashboard::config::expand_home;
+/// assert_eq!(expand_home("/var/log/alerts.log").to_str(), Some("/var/log/alerts.log"));
+/// ```
+///
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => Path::new(&home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
@@ -1,3 +1,15 @@
+pub mod actions;
+pub mod agent;
+pub mod args;
 #[allow(non_snake_case)]
 pub mod backend;
+pub mod config;
+pub mod metrics;
+pub mod notify;
+pub mod record;
+pub mod refresh;
+pub mod replay;
+pub mod serve;
+pub mod snapshot;
+pub mod store;
 pub mod ui;
@@ -1,13 +1,198 @@
 use color_eyre::Result;
+use color_eyre::eyre::eyre;
+use linux_dashboard::agent::{self, AgentOptions, AttachOptions};
+use linux_dashboard::config::Config;
+use linux_dashboard::record::{self, RecordOptions, Recording};
+use linux_dashboard::replay;
+use linux_dashboard::serve::{self, ServeOptions};
+use linux_dashboard::snapshot::{self, SnapshotOptions};
+use linux_dashboard::store::{self, CollectOptions, HistoryStore};
 use linux_dashboard::ui::app;
 use ratatui::{Terminal, backend::CrosstermBackend, crossterm::terminal};
 use std::io::stdout;
+use std::path::Path;
+use tokio::sync::mpsc;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    // `--config FILE` replaces the default configuration file, in any mode
+    let config_path = match args.iter().position(|a| a == "--config") {
+        Some(i) if i + 1 < args.len() => {
+            let path = args.remove(i + 1);
+            args.remove(i);
+            Some(path)
+        }
+        Some(_) => return Err(eyre!("--config expects a file")),
+        None => None,
+    };
+    let load_config = || match &config_path {
+        Some(path) => Config::load(Path::new(path)),
+        None => Config::load_default(),
+    };
+    match args.first().map(String::as_str) {
+        // Headless mode: print a one-shot report instead of starting the UI
+        Some("snapshot") => {
+            if args.iter().any(|a| a == "--help" || a == "-h") {
+                println!("{}", snapshot::USAGE);
+                return Ok(());
+            }
+            let options = SnapshotOptions::from_args(&args[1..])
+                .map_err(|e| eyre!("{e}\n{}", snapshot::USAGE))?;
+            let report =
+                tokio::task::spawn_blocking(move || snapshot::collect_report(&options)).await?;
+            print!("{report}");
+            return Ok(());
+        }
+        // Exporter mode: serve the metrics to Prometheus until stopped
+        Some("serve") => {
+            if args.iter().any(|a| a == "--help" || a == "-h") {
+                println!("{}", serve::USAGE);
+                return Ok(());
+            }
+            let options =
+                ServeOptions::from_args(&args[1..]).map_err(|e| eyre!("{e}\n{}", serve::USAGE))?;
+            serve::serve(options, print_messages()).await?;
+            return Ok(());
+        }
+        // Record mode: write samples to a file until Ctrl-C is pressed
+        Some("record") => {
+            if args.iter().any(|a| a == "--help" || a == "-h") {
+                println!("{}", record::USAGE);
+                return Ok(());
+            }
+            let options = RecordOptions::from_args(&args[1..])
+                .map_err(|e| eyre!("{e}\n{}", record::USAGE))?;
+            record::record(options).await?;
+            return Ok(());
+        }
+        // Collect mode: write the history store without the UI until Ctrl-C is pressed
+        Some("collect") => {
+            if args.iter().any(|a| a == "--help" || a == "-h") {
+                println!("{}", store::USAGE);
+                return Ok(());
+            }
+            let options = CollectOptions::from_args(&args[1..])
+                .map_err(|e| eyre!("{e}\n{}", store::USAGE))?;
+            let config = load_config().map_err(|e| eyre!(e))?;
+            let dir = config
+                .history_dir()
+                .ok_or_else(|| eyre!("no home directory for the history store"))?;
+            let tiers = config.history_tiers().map_err(|e| eyre!(e))?;
+            let history = HistoryStore::open(&dir, &tiers)
+                .map_err(|e| eyre!("cannot open the history store {}: {e}", dir.display()))?;
+            store::collect(options, history).await?;
+            return Ok(());
+        }
+        // Agent mode: sample the system once for all attached viewers until Ctrl-C is pressed
+        Some("agent") => {
+            if args.iter().any(|a| a == "--help" || a == "-h") {
+                println!("{}", agent::USAGE);
+                return Ok(());
+            }
+            let options =
+                AgentOptions::from_args(&args[1..]).map_err(|e| eyre!("{e}\n{}", agent::USAGE))?;
+            let config = load_config().map_err(|e| eyre!(e))?;
+            let alert_rules = config.alert_rules().map_err(|e| eyre!(e))?;
+            let alert_actions = config.alert_actions().map_err(|e| eyre!(e))?;
+            let history =
+                if config.history.enabled {
+                    let dir = config
+                        .history_dir()
+                        .ok_or_else(|| eyre!("no home directory for the history store"))?;
+                    let tiers = config.history_tiers().map_err(|e| eyre!(e))?;
+                    Some(HistoryStore::open(&dir, &tiers).map_err(|e| {
+                        eyre!("cannot open the history store {}: {e}", dir.display())
+                    })?)
+                } else {
+                    None
+                };
+            let token = agent::load_token(options.token_file.as_deref()).map_err(|e| eyre!(e))?;
+            agent::agent(
+                options,
+                token,
+                alert_rules,
+                alert_actions,
+                history,
+                print_messages(),
+            )
+            .await?;
+            return Ok(());
+        }
+        // Attach mode: show the samples of a running agent instead of sampling the system
+        Some("attach") => {
+            if args.iter().any(|a| a == "--help" || a == "-h") {
+                println!("{}", agent::ATTACH_USAGE);
+                return Ok(());
+            }
+            let options = AttachOptions::from_args(&args[1..])
+                .map_err(|e| eyre!("{e}\n{}", agent::ATTACH_USAGE))?;
+            let token = agent::load_token(options.token_file.as_deref()).map_err(|e| eyre!(e))?;
+            let config = load_config().map_err(|e| eyre!(e))?;
+            let connection = agent::connect(&options.address, token.as_deref())
+                .await
+                .map_err(|e| {
+                    eyre!(
+                        "cannot attach to the agent on {}: {e}\nStart one with `linux_dashboard agent`",
+                        options.address
+                    )
+                })?;
+            terminal::enable_raw_mode()?;
+            let terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+            let app_result =
+                app::run_attach(terminal, options.address, token, connection, &config).await;
+            terminal::disable_raw_mode()?;
+            return app_result;
+        }
+        // Replay mode: drive the UI from a recording instead of the live system
+        Some("replay") => {
+            let [_, path] = args.as_slice() else {
+                return Err(eyre!("{}", replay::USAGE));
+            };
+            if path == "--help" || path == "-h" {
+                println!("{}", replay::USAGE);
+                return Ok(());
+            }
+            let config = load_config().map_err(|e| eyre!(e))?;
+            let recording = Recording::open(Path::new(path))
+                .map_err(|e| eyre!("cannot read the recording {path}: {e}"))?;
+            terminal::enable_raw_mode()?;
+            let terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+            let app_result = app::run_replay(terminal, recording, &config);
+            terminal::disable_raw_mode()?;
+            return app_result;
+        }
+        Some(other) => {
+            return Err(eyre!(
+                "unknown command '{other}'\n{}\n{}\n{}\n{}\n{}\n{}\n{}",
+                snapshot::USAGE,
+                serve::USAGE,
+                record::USAGE,
+                replay::USAGE,
+                store::USAGE,
+                agent::USAGE,
+                agent::ATTACH_USAGE
+            ));
+        }
+        None => {}
+    }
+
+    let config = load_config().map_err(|e| eyre!(e))?;
     terminal::enable_raw_mode()?;
     let terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
-    let app_result = app::run_ui(terminal).await;
+    let app_result = app::run_ui(terminal, &config).await;
     terminal::disable_raw_mode()?;
     app_result
 }
+
+/// Returns a channel whose messages are printed to stderr, for the problems of the
+/// headless modes that do not stop them.
+fn print_messages() -> mpsc::UnboundedSender<String> {
+    let (messages, mut receiver) = mpsc::unbounded_channel::<String>();
+    tokio::spawn(async move {
+        while let Some(message) = receiver.recv().await {
+            eprintln!("{message}");
+        }
+    });
+    messages
+}
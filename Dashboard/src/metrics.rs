@@ -0,0 +1,279 @@
+//! This module encodes the dashboard data in the Prometheus text exposition format.
+//! The metrics mirror the panels of the terminal UI: CPU usage per core, memory and swap,
+//! disk space, network rates per interface and the top processes by CPU usage.
+//! All metric names start with `dashboard_`, sizes are in bytes and usages in percent.
+use crate::backend::{
+    cpu::CpuStats,
+    disk::{DiskInfo, list_disks},
+    memory::MemInfo,
+    network::{InterfaceDetails, InterfaceThroughput, NetworkManager},
+    processes::{ProcessInfo, SortOrder, list_processes},
+};
+use std::collections::HashMap;
+use std::fmt::Write;
+use sysinfo::System;
+
+/// Content type of the Prometheus text exposition format.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Reads one of the counters of an interface.
+type InterfaceCounter = fn(&InterfaceDetails) -> u64;
+
+/// The data exported on one scrape.
+#[derive(Debug, Clone, Default)]
+pub struct MetricsSample {
+    pub cpu: CpuStats,
+    pub memory: MemInfo,
+    pub disks: Vec<DiskInfo>,
+    pub network: Vec<InterfaceThroughput>,
+    pub interfaces: Vec<InterfaceDetails>,
+    /// The top processes by CPU usage
+    pub processes: Vec<ProcessInfo>,
+}
+
+impl MetricsSample {
+    /// Collects the sample from a refreshed system and network manager.
+    /// Only the `top` processes with the highest CPU usage are kept.
+    pub fn collect(sys: &System, network_manager: &NetworkManager, top: usize) -> Self {
+        let mut processes = list_processes(sys, SortOrder::CpuDesc, "", &HashMap::new());
+        processes.truncate(top);
+        Self {
+            cpu: CpuStats::collect(sys),
+            memory: MemInfo::collect(sys),
+            disks: list_disks(),
+            network: network_manager.throughput().to_vec(),
+            interfaces: network_manager.interface_details().to_vec(),
+            processes,
+        }
+    }
+
+    /// Encodes the sample in the Prometheus text exposition format.
+    ///
+    /// # Example
+    /// ```
+    /// use linux_dashboard::metrics::MetricsSample;
+    /// let text = MetricsSample::default().encode();
+    /// assert!(text.contains("# TYPE dashboard_memory_total_bytes gauge"));
+    /// ```
+    ///
+    pub fn encode(&self) -> String {
+        let mut out = MetricsWriter::default();
+
+        out.family(
+            "dashboard_cpu_usage_percent",
+            "gauge",
+            "Usage of all cores in percent.",
+        );
+        out.sample(
+            "dashboard_cpu_usage_percent",
+            &[],
+            self.cpu.total_usage as f64,
+        );
+        out.family(
+            "dashboard_cpu_core_usage_percent",
+            "gauge",
+            "Usage of a single core in percent.",
+        );
+        for (core, usage) in self.cpu.cores.iter().enumerate() {
+            out.sample(
+                "dashboard_cpu_core_usage_percent",
+                &[("core", &core.to_string())],
+                *usage as f64,
+            );
+        }
+
+        let memory = &self.memory;
+        for (name, help, bytes) in [
+            ("total", "Total memory in bytes.", memory.total),
+            ("used", "Used memory in bytes.", memory.used()),
+            ("available", "Available memory in bytes.", memory.available),
+            ("free", "Free memory in bytes.", memory.free),
+            (
+                "buffers",
+                "Memory used by buffers in bytes.",
+                memory.buffers,
+            ),
+            (
+                "cached",
+                "Memory used by the page cache in bytes.",
+                memory.cached,
+            ),
+        ] {
+            let metric = format!("dashboard_memory_{name}_bytes");
+            out.family(&metric, "gauge", help);
+            out.sample(&metric, &[], bytes as f64);
+        }
+        for (name, help, bytes) in [
+            ("total", "Total swap space in bytes.", memory.swap_total),
+            ("used", "Used swap space in bytes.", memory.swap_used()),
+        ] {
+            let metric = format!("dashboard_swap_{name}_bytes");
+            out.family(&metric, "gauge", help);
+            out.sample(&metric, &[], bytes as f64);
+        }
+
+        out.family(
+            "dashboard_disk_total_bytes",
+            "gauge",
+            "Size of a mounted disk in bytes.",
+        );
+        for disk in &self.disks {
+            out.sample(
+                "dashboard_disk_total_bytes",
+                &disk_labels(disk),
+                disk.total as f64,
+            );
+        }
+        out.family(
+            "dashboard_disk_available_bytes",
+            "gauge",
+            "Available space of a mounted disk in bytes.",
+        );
+        for disk in &self.disks {
+            out.sample(
+                "dashboard_disk_available_bytes",
+                &disk_labels(disk),
+                disk.available as f64,
+            );
+        }
+
+        // Interfaces without a rate yet (first sample) are left out instead of reported as 0
+        out.family(
+            "dashboard_network_receive_bytes_per_second",
+            "gauge",
+            "Download rate of an interface in bytes per second.",
+        );
+        for interface in &self.network {
+            if let Some(download) = interface.download {
+                out.sample(
+                    "dashboard_network_receive_bytes_per_second",
+                    &[("interface", &interface.name)],
+                    download,
+                );
+            }
+        }
+        out.family(
+            "dashboard_network_transmit_bytes_per_second",
+            "gauge",
+            "Upload rate of an interface in bytes per second.",
+        );
+        for interface in &self.network {
+            if let Some(upload) = interface.upload {
+                out.sample(
+                    "dashboard_network_transmit_bytes_per_second",
+                    &[("interface", &interface.name)],
+                    upload,
+                );
+            }
+        }
+        let counters: [(&str, &str, InterfaceCounter); 4] = [
+            ("receive_packets", "Packets received", |i| i.rx_packets),
+            ("transmit_packets", "Packets transmitted", |i| i.tx_packets),
+            ("receive_errors", "Receive errors", |i| i.rx_errors),
+            ("transmit_errors", "Transmit errors", |i| i.tx_errors),
+        ];
+        for (name, help, value) in counters {
+            let metric = format!("dashboard_network_{name}_total");
+            out.family(
+                &metric,
+                "counter",
+                &format!("{help} by an interface since boot."),
+            );
+            for interface in &self.interfaces {
+                out.sample(
+                    &metric,
+                    &[("interface", &interface.name)],
+                    value(interface) as f64,
+                );
+            }
+        }
+
+        out.family(
+            "dashboard_process_cpu_usage_percent",
+            "gauge",
+            "CPU usage of a top process in percent.",
+        );
+        for process in &self.processes {
+            let pid = process.pid.to_string();
+            out.sample(
+                "dashboard_process_cpu_usage_percent",
+                &[("pid", &pid), ("name", &process.name)],
+                process.cpu_usage as f64,
+            );
+        }
+        out.family(
+            "dashboard_process_memory_bytes",
+            "gauge",
+            "Resident memory of a top process in bytes.",
+        );
+        for process in &self.processes {
+            let pid = process.pid.to_string();
+            out.sample(
+                "dashboard_process_memory_bytes",
+                &[("pid", &pid), ("name", &process.name)],
+                process.memory as f64,
+            );
+        }
+
+        out.text
+    }
+}
+
+fn disk_labels(disk: &DiskInfo) -> [(&str, &str); 3] {
+    [
+        ("device", &disk.name),
+        ("mount_point", &disk.mount_point),
+        ("file_system", &disk.file_system),
+    ]
+}
+
+/// Appends metric families and samples to the exposition text.
+#[derive(Default)]
+struct MetricsWriter {
+    text: String,
+}
+
+impl MetricsWriter {
+    fn family(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(self.text, "# HELP {name} {help}");
+        let _ = writeln!(self.text, "# TYPE {name} {kind}");
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) {
+        self.text.push_str(name);
+        if !labels.is_empty() {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(key, value)| format!("{key}=\"{}\"", escape_label(value)))
+                .collect();
+            let _ = write!(self.text, "{{{}}}", labels.join(","));
+        }
+        let _ = writeln!(self.text, " {}", format_value(value));
+    }
+}
+
+/// Escapes a label value: backslash, double quote and line feed must be escaped.
+///
+/// # Example
+/// ```
+/// use linux_dashboard::metrics::escape_label;
+/// assert_eq!(escape_label("a \"b\"\\"), "a \\\"b\\\"\\\\");
+/// ```
+///
+pub fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Formats a sample value, Prometheus spells infinity as `+Inf` and `-Inf`.
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,69 @@
+//! This module sends freedesktop desktop notifications with `notify-send` from libnotify,
+//! which talks to the notification server over the D-Bus session bus.
+//! Without `notify-send` or a session bus, e.g. on a headless server, sending fails with an error.
+use std::ffi::OsStr;
+use std::io;
+use std::process::{Command, Stdio};
+
+/// The program that sends the notifications.
+pub const NOTIFY_SEND: &str = "notify-send";
+
+/// Urgency levels of the notification specification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Urgency {
+    Low,
+    Normal,
+    Critical,
+}
+
+impl Urgency {
+    /// Returns the name `notify-send` expects.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Normal => "normal",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+/// A desktop notification.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub summary: String,
+    pub body: String,
+    pub urgency: Urgency,
+}
+
+/// Sends the notification with `notify-send` and waits until it exited.
+pub fn send_notification(notification: &Notification) -> io::Result<()> {
+    send_notification_with(NOTIFY_SEND, notification)
+}
+
+/// Like `send_notification`, but runs `program` with the arguments of `notify-send`.
+pub fn send_notification_with(
+    program: impl AsRef<OsStr>,
+    notification: &Notification,
+) -> io::Result<()> {
+    let program = program.as_ref();
+    let output = Command::new(program)
+        .arg("--app-name=linux_dashboard")
+        .arg(format!("--urgency={}", notification.urgency.as_str()))
+        // The summary may start with a dash
+        .arg("--")
+        .arg(&notification.summary)
+        .arg(&notification.body)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .map_err(|e| io::Error::new(e.kind(), format!("cannot run {}: {e}", program.display())))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(io::Error::other(match stderr.trim() {
+        "" => format!("{} {}", program.display(), output.status),
+        message => format!("{} {}: {message}", program.display(), output.status),
+    }))
+}
@@ -0,0 +1,537 @@
+//! This module records the dashboard data to a file and reads recordings back.
+//! A recording is a gzip compressed JSON lines file: the first line is a `RecordingHeader`,
+//! every following line one `Sample` with its timestamp.
+//! Most processes and connections stay the same from one sample to the next, so a sample
+//! line lists them by their index in the previous sample and only writes out the entries
+//! that are new or changed.
+//! The samples are written in chunks of `CHUNK_DURATION`. Every chunk is a gzip member of
+//! its own that starts with a complete sample, so a replay can start reading at any chunk
+//! and only keeps an index of the chunks in memory. A chunk is flushed when it is complete,
+//! a recording that was interrupted, e.g. by a crash or a power loss, can still be read up
+//! to the last sample the compressor wrote out.
+use crate::args::Args;
+use crate::backend::host::HostInfo;
+use crate::backend::sample::{Sample, SampleCollector};
+use crate::refresh::spawn_refresh_task;
+use flate2::Compression;
+use flate2::bufread::{GzDecoder, MultiGzDecoder};
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::mem;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use sysinfo::System;
+use tokio::sync::{mpsc, watch};
+
+/// Version of the file format, recordings of newer versions are rejected.
+/// Version 1 wrote every sample out completely.
+pub const RECORDING_VERSION: u32 = 2;
+
+/// Recorded time covered by one chunk of a recording.
+pub const CHUNK_DURATION: Duration = Duration::from_secs(60);
+
+/// The lists of a sample that are written as changes to the previous sample.
+const LIST_FIELDS: [&str; 2] = ["processes", "connections"];
+
+/// First line of a recording.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingHeader {
+    pub version: u32,
+    /// The host the recording was made on
+    pub host: HostInfo,
+    /// Refresh interval of the recorder in milliseconds
+    pub interval_ms: u64,
+}
+
+impl RecordingHeader {
+    /// Creates the header of a recording of this host.
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            version: RECORDING_VERSION,
+            host: HostInfo::new(),
+            interval_ms: interval.as_millis() as u64,
+        }
+    }
+
+    /// Returns the number of samples in one chunk.
+    fn chunk_size(&self) -> usize {
+        (CHUNK_DURATION.as_millis() as u64 / self.interval_ms.max(1)).max(1) as usize
+    }
+}
+
+/// The lists of the previous sample line, which the entries of the next line refer to.
+#[derive(Default)]
+struct PreviousLists([Vec<Value>; LIST_FIELDS.len()]);
+
+impl PreviousLists {
+    /// Encodes a sample, the list entries found in the previous sample are replaced
+    /// by their index there.
+    fn encode(&mut self, sample: &Sample) -> io::Result<Value> {
+        let mut value = serde_json::to_value(sample)?;
+        for (field, previous) in LIST_FIELDS.iter().zip(&mut self.0) {
+            let Some(Value::Array(entries)) = value.get_mut(*field) else {
+                continue;
+            };
+            let current = mem::take(entries);
+            // The lists are sorted the same way, an entry is usually next to the one before
+            let mut next = 0;
+            for entry in &current {
+                let index = if previous.get(next) == Some(entry) {
+                    Some(next)
+                } else {
+                    previous.iter().position(|old| old == entry)
+                };
+                entries.push(match index {
+                    Some(index) => {
+                        next = index + 1;
+                        Value::from(index)
+                    }
+                    None => entry.clone(),
+                });
+            }
+            *previous = current;
+        }
+        Ok(value)
+    }
+
+    /// Decodes a sample line, replacing the indices by the entries of the previous sample.
+    fn decode(&mut self, line: &str) -> Result<Sample, String> {
+        let mut value: Value = serde_json::from_str(line).map_err(|e| e.to_string())?;
+        for (field, previous) in LIST_FIELDS.iter().zip(&mut self.0) {
+            let Some(Value::Array(entries)) = value.get_mut(*field) else {
+                previous.clear();
+                continue;
+            };
+            for entry in entries.iter_mut() {
+                if let Some(index) = entry.as_u64() {
+                    *entry = previous
+                        .get(index as usize)
+                        .cloned()
+                        .ok_or_else(|| format!("{field} refer to the missing entry {index}"))?;
+                }
+            }
+            previous.clone_from(entries);
+        }
+        serde_json::from_value(value).map_err(|e| e.to_string())
+    }
+}
+
+/// Writes a recording to `W`.
+pub struct Recorder<W: Write> {
+    /// The gzip member of the current chunk, only `None` while a chunk is finished
+    encoder: Option<GzEncoder<W>>,
+    chunk_size: usize,
+    /// Samples written to the current chunk
+    chunk_len: usize,
+    previous: PreviousLists,
+}
+
+impl Recorder<BufWriter<File>> {
+    /// Creates the file at `path`, an existing file is overwritten.
+    pub fn create(path: &Path, header: &RecordingHeader) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?), header)
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    /// Starts a recording by writing the header, which is a gzip member of its own.
+    pub fn new(writer: W, header: &RecordingHeader) -> io::Result<Self> {
+        let mut encoder = GzEncoder::new(writer, Compression::default());
+        serde_json::to_writer(&mut encoder, header)?;
+        encoder.write_all(b"\n")?;
+        let writer = encoder.finish()?;
+        Ok(Self {
+            encoder: Some(GzEncoder::new(writer, Compression::default())),
+            chunk_size: header.chunk_size(),
+            chunk_len: 0,
+            previous: PreviousLists::default(),
+        })
+    }
+
+    /// Appends a sample. A chunk is flushed once it is complete.
+    pub fn write_sample(&mut self, sample: &Sample) -> io::Result<()> {
+        if self.chunk_len == self.chunk_size {
+            let mut writer = self.take_encoder()?.finish()?;
+            writer.flush()?;
+            self.encoder = Some(GzEncoder::new(writer, Compression::default()));
+            self.chunk_len = 0;
+        }
+        if self.chunk_len == 0 {
+            // A chunk starts with a complete sample
+            self.previous = PreviousLists::default();
+        }
+        let line = self.previous.encode(sample)?;
+        let encoder = self.encoder.as_mut().ok_or_else(finished)?;
+        serde_json::to_writer(&mut *encoder, &line)?;
+        encoder.write_all(b"\n")?;
+        self.chunk_len += 1;
+        Ok(())
+    }
+
+    /// Ends the recording and returns the writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.take_encoder()?.finish()
+    }
+
+    fn take_encoder(&mut self) -> io::Result<GzEncoder<W>> {
+        self.encoder.take().ok_or_else(finished)
+    }
+}
+
+// A failed write left the recorder without an encoder
+fn finished() -> io::Error {
+    io::Error::other("the recording was ended by an earlier error")
+}
+
+/// The file of a recording.
+pub trait RecordingInput: BufRead + Seek + Send {}
+
+impl<T: BufRead + Seek + Send> RecordingInput for T {}
+
+/// A chunk of a recording, where reading can start.
+#[derive(Debug, Clone, Copy)]
+struct Chunk {
+    /// Position in the file, the chunk at 0 starts with the header
+    offset: u64,
+    /// Index of its first sample
+    first: usize,
+}
+
+/// Reads the lines of a recording from the start of a chunk on.
+enum ChunkReader {
+    Compressed(BufReader<MultiGzDecoder<Box<dyn RecordingInput>>>),
+    Plain(Box<dyn RecordingInput>),
+}
+
+impl ChunkReader {
+    fn into_input(self) -> Box<dyn RecordingInput> {
+        match self {
+            Self::Compressed(reader) => reader.into_inner().into_inner(),
+            Self::Plain(input) => input,
+        }
+    }
+
+    fn read_line(&mut self, line: &mut String) -> io::Result<usize> {
+        match self {
+            Self::Compressed(reader) => reader.read_line(line),
+            Self::Plain(input) => input.read_line(line),
+        }
+    }
+}
+
+/// The position of the next sample `ChunkReader` reads.
+struct ReadPosition {
+    reader: ChunkReader,
+    /// Index of the next sample
+    next: usize,
+    /// True before the header of the chunk at the start of the file was skipped
+    before_header: bool,
+    previous: PreviousLists,
+}
+
+/// A recording read from a file.
+/// Only the time of every sample and an index of the chunks are kept in memory,
+/// the samples are read from the file when they are needed.
+pub struct Recording {
+    pub header: RecordingHeader,
+    timeline: Vec<f64>,
+    chunks: Vec<Chunk>,
+    compressed: bool,
+    /// `None` if the file could not be positioned
+    position: Option<ReadPosition>,
+}
+
+impl Recording {
+    /// Reads the recording at `path`.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+
+    /// Reads a recording. Uncompressed JSON lines are accepted as well,
+    /// e.g. a recording unpacked with `gunzip`, reading them always starts at the beginning.
+    /// A truncated last sample is skipped, any other invalid line is an error.
+    pub fn read(mut input: impl RecordingInput + 'static) -> io::Result<Self> {
+        let compressed = input.fill_buf()?.starts_with(&[0x1f, 0x8b]);
+        let mut input: Box<dyn RecordingInput> = Box::new(input);
+        let mut scan = Scan::default();
+        if compressed {
+            // Every gzip member is a chunk
+            loop {
+                let offset = input.stream_position()?;
+                if input.fill_buf()?.is_empty() {
+                    break;
+                }
+                let mut member = BufReader::new(GzDecoder::new(&mut input));
+                if !scan.chunk(&mut member, offset)? {
+                    break;
+                }
+            }
+        } else {
+            scan.chunk(&mut input, 0)?;
+        }
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+        let header = scan
+            .header
+            .ok_or_else(|| invalid("the recording is empty".to_string()))?;
+        if header.version > RECORDING_VERSION {
+            return Err(invalid(format!(
+                "recording version {} is not supported, the newest supported version is {RECORDING_VERSION}",
+                header.version
+            )));
+        }
+
+        let mut recording = Self {
+            header,
+            timeline: scan.timeline,
+            chunks: scan.chunks,
+            compressed,
+            position: Some(ReadPosition {
+                reader: ChunkReader::Plain(input),
+                next: usize::MAX,
+                before_header: false,
+                previous: PreviousLists::default(),
+            }),
+        };
+        if recording.chunks.is_empty() {
+            recording.chunks.push(Chunk {
+                offset: 0,
+                first: 0,
+            });
+        }
+        Ok(recording)
+    }
+
+    /// Returns the number of samples.
+    pub fn len(&self) -> usize {
+        self.timeline.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.timeline.is_empty()
+    }
+
+    /// Returns the recorded time of every sample in seconds since the Unix epoch.
+    /// A sample recorded after the clock was set back is placed at the time of the sample
+    /// before it, so the timeline never goes backwards.
+    pub fn timeline(&self) -> &[f64] {
+        &self.timeline
+    }
+
+    /// Reads the samples in `range`. Reading continues where the last call ended
+    /// if the range is ahead in the same chunk, otherwise at the chunk of the range.
+    pub fn samples(&mut self, range: Range<usize>) -> io::Result<Vec<Sample>> {
+        let range = range.start..range.end.min(self.len());
+        if range.is_empty() {
+            return Ok(Vec::new());
+        }
+        let chunk = self.chunks[self.chunks.partition_point(|c| c.first <= range.start) - 1];
+        let mut position = self.position.take().ok_or_else(|| {
+            io::Error::other("the recording cannot be read after an earlier error")
+        })?;
+        if !(chunk.first..=range.start).contains(&position.next) {
+            let mut input = position.reader.into_input();
+            input.seek(SeekFrom::Start(chunk.offset))?;
+            position = ReadPosition {
+                reader: if self.compressed {
+                    ChunkReader::Compressed(BufReader::new(MultiGzDecoder::new(input)))
+                } else {
+                    ChunkReader::Plain(input)
+                },
+                next: chunk.first,
+                before_header: chunk.offset == 0,
+                previous: PreviousLists::default(),
+            };
+        }
+
+        let mut samples = Vec::with_capacity(range.len());
+        let mut line = String::new();
+        while position.next < range.end {
+            line.clear();
+            if position.reader.read_line(&mut line)? == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "the recording ended early",
+                ));
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+            if mem::take(&mut position.before_header) {
+                continue;
+            }
+            let sample = position.previous.decode(&line).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("sample {}: {e}", position.next),
+                )
+            })?;
+            if position.next >= range.start {
+                samples.push(sample);
+            }
+            position.next += 1;
+        }
+        self.position = Some(position);
+        Ok(samples)
+    }
+}
+
+impl fmt::Debug for Recording {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Recording")
+            .field("header", &self.header)
+            .field("samples", &self.len())
+            .field("chunks", &self.chunks)
+            .finish_non_exhaustive()
+    }
+}
+
+/// State of the first pass over a recording, which builds the index.
+#[derive(Default)]
+struct Scan {
+    header: Option<RecordingHeader>,
+    timeline: Vec<f64>,
+    chunks: Vec<Chunk>,
+    /// Number of lines read
+    lines: usize,
+    /// An invalid line, which is only an error if more lines follow,
+    /// a truncated last sample is skipped
+    error: Option<io::Error>,
+}
+
+/// The part of a sample line the scan reads.
+#[derive(Deserialize)]
+struct Timestamp {
+    timestamp: f64,
+}
+
+impl Scan {
+    /// Reads the lines of the chunk at `offset`.
+    /// Returns false at the end of an interrupted recording.
+    fn chunk(&mut self, reader: &mut impl BufRead, offset: u64) -> io::Result<bool> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+        let first = self.timeline.len();
+        let mut line = String::new();
+        let mut complete = true;
+        loop {
+            line.clear();
+            match reader.read_line(&mut line) {
+                Ok(0) => break,
+                Ok(_) if line.trim().is_empty() => continue,
+                Ok(_) => {}
+                // The compressed stream of an interrupted recording ends without a trailer
+                Err(_) if self.lines > 0 => {
+                    complete = false;
+                    break;
+                }
+                Err(e) => return Err(e),
+            }
+            self.lines += 1;
+            if let Some(error) = self.error.take() {
+                return Err(error);
+            }
+            if self.header.is_none() {
+                self.header = Some(
+                    serde_json::from_str(&line)
+                        .map_err(|e| invalid(format!("invalid recording header: {e}")))?,
+                );
+                continue;
+            }
+            match serde_json::from_str::<Timestamp>(&line) {
+                Ok(sample) => {
+                    let latest = self.timeline.last().copied().unwrap_or(f64::MIN);
+                    self.timeline.push(sample.timestamp.max(latest));
+                }
+                Err(e) => self.error = Some(invalid(format!("line {}: {e}", self.lines))),
+            }
+        }
+        if self.timeline.len() > first {
+            self.chunks.push(Chunk { offset, first });
+        }
+        Ok(complete)
+    }
+}
+
+/// Options of the record mode.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordOptions {
+    pub path: PathBuf,
+    /// Time between two samples
+    pub interval: Duration,
+}
+
+/// Usage text of the record mode.
+pub const USAGE: &str = "Usage: linux_dashboard record FILE [--interval MS]
+  FILE             file the samples are written to, gzip compressed JSON lines
+  --interval MS    refresh interval in milliseconds (default 1000)";
+
+impl RecordOptions {
+    /// Parses the arguments following `record`.
+    /// Returns an error message for a missing file, unknown arguments or invalid values.
+    ///
+    /// # Example
+    /// ```
+    /// use linux_dashboard::record::RecordOptions;
+    /// let args = vec!["night.jsonl.gz".to_string()];
+    /// let options = RecordOptions::from_args(&args).unwrap();
+    /// assert_eq!(options.interval.as_millis(), 1000);
+    /// ```
+    ///
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut path = None;
+        let mut interval = Duration::from_millis(1000);
+        let mut args = Args::new(args);
+        while let Some(arg) = args.next() {
+            match arg {
+                "--interval" => interval = args.interval("--interval")?,
+                other if other.starts_with("--") => {
+                    return Err(format!("unknown argument '{other}'"));
+                }
+                other if path.is_none() => path = Some(PathBuf::from(other)),
+                other => return Err(format!("unexpected argument '{other}'")),
+            }
+        }
+        Ok(Self {
+            path: path.ok_or_else(|| "missing the file to record to".to_string())?,
+            interval,
+        })
+    }
+}
+
+/// Records samples until Ctrl-C is pressed or writing fails.
+/// The system is refreshed by the same background task the terminal UI uses.
+pub async fn record(options: RecordOptions) -> io::Result<()> {
+    let mut recorder = Recorder::create(&options.path, &RecordingHeader::new(options.interval))?;
+    let mut collector = SampleCollector::default();
+    let sys = Arc::new(Mutex::new(System::new_all()));
+    {
+        let s = sys.lock().unwrap_or_else(|e| e.into_inner());
+        recorder.write_sample(&collector.collect(&s))?;
+    }
+    println!(
+        "Recording to {}, press Ctrl-C to stop",
+        options.path.display()
+    );
+
+    let (error_tx, mut error_rx) = mpsc::unbounded_channel();
+    let (interval_tx, interval_rx) = watch::channel(options.interval.as_millis() as u64);
+    let refresh = spawn_refresh_task(sys, interval_rx, move |s| {
+        if let Err(e) = recorder.write_sample(&collector.collect(s)) {
+            let _ = error_tx.send(e);
+        }
+    });
+
+    let result = tokio::select! {
+        result = tokio::signal::ctrl_c() => result,
+        Some(e) = error_rx.recv() => Err(e),
+    };
+    // Stopping the task drops the recorder, which writes the end of the compressed stream
+    drop(interval_tx);
+    let _ = refresh.await;
+    result
+}
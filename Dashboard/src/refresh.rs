@@ -0,0 +1,42 @@
+//! This module contains the background task that refreshes the system data.
+//! It is shared by the terminal UI and the metrics server, so both see the numbers
+//! sampled the same way.
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use sysinfo::System;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+/// Spawns a tokio task that refreshes `sys` every `interval_rx` milliseconds.
+/// `on_refresh` is called with the refreshed system after every refresh, while the
+/// lock is held, e.g. to sample additional data at the same point in time.
+/// A new interval sent through the channel takes effect right away.
+/// The task ends when the sender of the channel is dropped.
+pub fn spawn_refresh_task<F>(
+    sys: Arc<Mutex<System>>,
+    interval_rx: watch::Receiver<u64>,
+    mut on_refresh: F,
+) -> JoinHandle<()>
+where
+    F: FnMut(&System) + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut rx = interval_rx;
+        loop {
+            let interval = *rx.borrow();
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_millis(interval)) => {
+                    let mut s = sys.lock().unwrap_or_else(|e| e.into_inner());
+                    s.refresh_all();
+                    on_refresh(&s);
+                }
+                result = rx.changed() => {
+                    if result.is_err() {
+                        // Sender dropped, exit
+                        break;
+                    }
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,151 @@
+//! This module steps through a recording for the replay mode of the terminal UI.
+//! A cursor moves through the recorded time at an adjustable speed, every sample the
+//! cursor passes is due to be shown. The cursor can be paused and moved back and forth.
+//! The samples are read from the recording when they are due.
+use crate::backend::sample::Sample;
+use crate::record::{Recording, RecordingHeader};
+use std::ops::Range;
+use std::time::Duration;
+
+/// Usage text of the replay mode.
+pub const USAGE: &str = "Usage: linux_dashboard replay FILE
+  FILE             recording written by the record mode";
+
+/// Playback speeds the user can step through.
+pub const SPEEDS: [f64; 9] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0, 16.0, 32.0, 64.0];
+
+/// The playback state of a recording.
+pub struct Playback {
+    recording: Recording,
+    /// Number of samples up to the cursor
+    position: usize,
+    /// Recorded time of the cursor, seconds since the Unix epoch
+    cursor: f64,
+    paused: bool,
+    speed: usize,
+    /// Why reading the recording failed, the playback stops then
+    error: Option<String>,
+}
+
+impl Playback {
+    /// Starts the playback at the first sample at normal speed.
+    pub fn new(recording: Recording) -> Self {
+        let cursor = recording.timeline().first().copied().unwrap_or(0.0);
+        Self {
+            recording,
+            position: 0,
+            cursor,
+            paused: false,
+            speed: SPEEDS.iter().position(|speed| *speed == 1.0).unwrap_or(0),
+            error: None,
+        }
+    }
+
+    pub fn header(&self) -> &RecordingHeader {
+        &self.recording.header
+    }
+
+    /// Returns the number of samples in the recording.
+    pub fn len(&self) -> usize {
+        self.recording.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.recording.is_empty()
+    }
+
+    /// Reads the samples in `range` from the recording. If reading fails, the playback
+    /// is paused and no samples are returned, `error` tells why.
+    pub fn samples(&mut self, range: Range<usize>) -> Vec<Sample> {
+        match self.recording.samples(range) {
+            Ok(samples) => samples,
+            Err(e) => {
+                self.error = Some(e.to_string());
+                self.paused = true;
+                Vec::new()
+            }
+        }
+    }
+
+    /// Returns why reading the recording failed.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Returns the number of samples up to the cursor, the last of them is shown.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the recorded time of the cursor in seconds since the Unix epoch.
+    pub fn cursor(&self) -> f64 {
+        self.cursor
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Returns true once the cursor reached the last sample.
+    pub fn is_finished(&self) -> bool {
+        self.position == self.recording.len()
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    pub fn speed(&self) -> f64 {
+        SPEEDS[self.speed]
+    }
+
+    pub fn faster(&mut self) {
+        self.speed = (self.speed + 1).min(SPEEDS.len() - 1);
+    }
+
+    pub fn slower(&mut self) {
+        self.speed = self.speed.saturating_sub(1);
+    }
+
+    /// Moves the cursor forward by `elapsed` wall clock time times the speed and returns
+    /// the indices of the samples that became due. The playback pauses at the end.
+    pub fn advance(&mut self, elapsed: Duration) -> Range<usize> {
+        if !self.paused {
+            self.move_cursor(elapsed.as_secs_f64() * self.speed());
+        }
+        let start = self.position;
+        self.position = self.samples_until(self.cursor);
+        if self.is_finished() {
+            self.paused = true;
+        }
+        start..self.position
+    }
+
+    /// Moves the cursor by `seconds` (backwards if negative) within the recording.
+    /// The histories shown in the UI are rebuilt from scratch after a jump, the returned
+    /// range holds the samples within `window` before the cursor to rebuild them with.
+    pub fn seek(&mut self, seconds: f64, window: Duration) -> Range<usize> {
+        self.move_cursor(seconds);
+        self.position = self.samples_until(self.cursor);
+        let start = self
+            .samples_until(self.cursor - window.as_secs_f64())
+            .min(self.position.saturating_sub(1));
+        start..self.position
+    }
+
+    // Moves the cursor, limited to the time span of the recording
+    fn move_cursor(&mut self, seconds: f64) {
+        let timeline = self.recording.timeline();
+        let (Some(first), Some(last)) = (timeline.first(), timeline.last()) else {
+            return;
+        };
+        self.cursor = (self.cursor + seconds).min(*last).max(*first);
+    }
+
+    // Returns the number of samples recorded at or before `time`
+    fn samples_until(&self, time: f64) -> usize {
+        self.recording
+            .timeline()
+            .partition_point(|timestamp| *timestamp <= time)
+    }
+}
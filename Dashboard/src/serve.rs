@@ -0,0 +1,265 @@
+//! This module implements the `serve` mode, a Prometheus exporter.
+//! The background refresh task of the terminal UI samples the system every interval and
+//! encodes the metrics once, a scrape of `GET /metrics` only returns the latest page.
+//! The HTTP server is deliberately minimal: one request per connection, no keep-alive.
+//! A client has `REQUEST_TIMEOUT` to send its request and at most `MAX_CONNECTIONS` are
+//! served at the same time, so idle clients cannot use up the tasks and file descriptors.
+use crate::args::Args;
+use crate::backend::network::NetworkManager;
+use crate::metrics::{CONTENT_TYPE, MetricsSample};
+use crate::refresh::spawn_refresh_task;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use sysinfo::System;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{Semaphore, mpsc, watch};
+
+/// Largest request head accepted, larger requests are rejected.
+const MAX_REQUEST_SIZE: usize = 8 * 1024;
+
+/// Time a client has to send its request before the connection is closed.
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Connections served at the same time, further clients wait in the listen backlog.
+pub const MAX_CONNECTIONS: usize = 32;
+
+/// Pause after a failed accept, e.g. while the process is out of file descriptors.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Options of the serve mode.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServeOptions {
+    /// Address the HTTP server listens on
+    pub listen: SocketAddr,
+    /// Time between two refreshes of the metrics
+    pub interval: Duration,
+    /// Number of processes exported, sorted by CPU usage
+    pub top: usize,
+}
+
+impl Default for ServeOptions {
+    fn default() -> Self {
+        Self {
+            listen: SocketAddr::from(([127, 0, 0, 1], 9184)),
+            interval: Duration::from_millis(5000),
+            top: 10,
+        }
+    }
+}
+
+/// Usage text of the serve mode.
+pub const USAGE: &str = "Usage: linux_dashboard serve [--listen ADDR] [--interval MS] [--top N]
+  --listen ADDR    address of the metrics endpoint (default 127.0.0.1:9184)
+  --interval MS    refresh interval in milliseconds (default 5000)
+  --top N          number of processes to export (default 10)";
+
+impl ServeOptions {
+    /// Parses the arguments following `serve`.
+    /// Returns an error message for unknown arguments or invalid values.
+    ///
+    /// # Example
+    /// ```
+    /// use linux_dashboard::serve::ServeOptions;
+    /// let args = vec!["--listen".to_string(), "0.0.0.0:9100".to_string()];
+    /// let options = ServeOptions::from_args(&args).unwrap();
+    /// assert_eq!(options.listen.port(), 9100);
+    /// ```
+    ///
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut options = Self::default();
+        let mut args = Args::new(args);
+        while let Some(arg) = args.next() {
+            match arg {
+                "--listen" => {
+                    options.listen = args.parse("--listen", "an address like 127.0.0.1:9184")?;
+                }
+                "--interval" => options.interval = args.interval("--interval")?,
+                "--top" => options.top = args.number("--top")? as usize,
+                other => return Err(format!("unknown argument '{other}'")),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// HTTP server answering `GET /metrics` with the latest metrics page.
+pub struct MetricsServer {
+    listener: TcpListener,
+    page: Arc<RwLock<String>>,
+    messages: Option<mpsc::UnboundedSender<String>>,
+}
+
+impl MetricsServer {
+    /// Binds the server to `address`, port 0 picks a free port.
+    pub async fn bind(address: SocketAddr) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(address).await?,
+            page: Arc::new(RwLock::new(String::new())),
+            messages: None,
+        })
+    }
+
+    /// Sends the problems that do not stop the server, e.g. a failed accept, to `messages`.
+    pub fn report_to(mut self, messages: mpsc::UnboundedSender<String>) -> Self {
+        self.messages = Some(messages);
+        self
+    }
+
+    /// Returns the address the server is listening on.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Returns the metrics page served on `/metrics`, replace its content to update it.
+    pub fn page(&self) -> Arc<RwLock<String>> {
+        Arc::clone(&self.page)
+    }
+
+    /// Accepts connections until the task is dropped, each on its own task.
+    /// A failed accept is reported and retried after a pause instead of ending the server.
+    pub async fn run(self) -> io::Result<()> {
+        let connections = Arc::new(Semaphore::new(MAX_CONNECTIONS));
+        loop {
+            // Waiting for a free slot before accepting keeps further clients in the backlog
+            let Ok(permit) = Arc::clone(&connections).acquire_owned().await else {
+                return Ok(());
+            };
+            let stream = match self.listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    accept_failed(&e, self.messages.as_ref()).await;
+                    continue;
+                }
+            };
+            let page = Arc::clone(&self.page);
+            tokio::spawn(async move {
+                // A client going away mid-request is not an error of the server
+                let _ = handle_connection(stream, &page).await;
+                drop(permit);
+            });
+        }
+    }
+}
+
+/// Reports a failed accept to `messages` and waits before the next one.
+/// Running out of file descriptors or a client aborting the handshake does not end a server.
+pub(crate) async fn accept_failed(
+    error: &io::Error,
+    messages: Option<&mpsc::UnboundedSender<String>>,
+) {
+    if let Some(messages) = messages {
+        let _ = messages.send(format!("cannot accept a connection: {error}"));
+    }
+    tokio::time::sleep(ACCEPT_BACKOFF).await;
+}
+
+async fn handle_connection(mut stream: TcpStream, page: &RwLock<String>) -> io::Result<()> {
+    let request = match tokio::time::timeout(REQUEST_TIMEOUT, read_request(&mut stream)).await {
+        Ok(Ok(Some(request))) => request,
+        Ok(Ok(None)) => {
+            return respond(&mut stream, "431 Request Header Fields Too Large", "", "").await;
+        }
+        Ok(Err(e)) => return Err(e),
+        Err(_) => return respond(&mut stream, "408 Request Timeout", "", "").await,
+    };
+
+    let request = String::from_utf8_lossy(&request);
+    let mut request_line = request.lines().next().unwrap_or_default().split(' ');
+    let method = request_line.next().unwrap_or_default();
+    let path = request_line.next().unwrap_or_default();
+    // Query strings are ignored, Prometheus may append parameters to the scrape URL
+    let path = path.split('?').next().unwrap_or_default();
+
+    match (method, path) {
+        ("GET", "/metrics") => {
+            let body = page.read().unwrap_or_else(|e| e.into_inner()).clone();
+            respond(&mut stream, "200 OK", CONTENT_TYPE, &body).await
+        }
+        ("GET", "/") => {
+            let body = "linux_dashboard exporter, metrics are at /metrics\n";
+            respond(&mut stream, "200 OK", "text/plain; charset=utf-8", body).await
+        }
+        ("GET", _) => respond(&mut stream, "404 Not Found", "text/plain", "not found\n").await,
+        _ => {
+            respond(
+                &mut stream,
+                "405 Method Not Allowed",
+                "text/plain",
+                "method not allowed\n",
+            )
+            .await
+        }
+    }
+}
+
+// Reads the request head, `None` if it is larger than MAX_REQUEST_SIZE
+async fn read_request(stream: &mut TcpStream) -> io::Result<Option<Vec<u8>>> {
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buffer[..read]);
+        if request.len() > MAX_REQUEST_SIZE {
+            return Ok(None);
+        }
+    }
+    Ok(Some(request))
+}
+
+async fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &str,
+) -> io::Result<()> {
+    let mut response = format!(
+        "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        body.len()
+    );
+    if !content_type.is_empty() {
+        response.push_str(&format!("Content-Type: {content_type}\r\n"));
+    }
+    response.push_str("\r\n");
+    response.push_str(body);
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Runs the exporter until the process is stopped.
+/// The metrics are refreshed by the same background task the terminal UI uses.
+/// Problems that do not stop the exporter are sent to `messages`.
+pub async fn serve(
+    options: ServeOptions,
+    messages: mpsc::UnboundedSender<String>,
+) -> io::Result<()> {
+    let server = MetricsServer::bind(options.listen)
+        .await?
+        .report_to(messages);
+    println!("Serving metrics on http://{}/metrics", server.local_addr()?);
+
+    let mut network_manager = NetworkManager::default();
+    let sys = Arc::new(Mutex::new(System::new_all()));
+    network_manager.refresh();
+    // The first page has no CPU usage and network rates yet, they need two samples
+    {
+        let s = sys.lock().unwrap_or_else(|e| e.into_inner());
+        *server.page().write().unwrap_or_else(|e| e.into_inner()) =
+            MetricsSample::collect(&s, &network_manager, options.top).encode();
+    }
+
+    let page = server.page();
+    let top = options.top;
+    let (_interval_tx, interval_rx) = watch::channel(options.interval.as_millis() as u64);
+    let _refresh = spawn_refresh_task(sys, interval_rx, move |s| {
+        network_manager.refresh();
+        let text = MetricsSample::collect(s, &network_manager, top).encode();
+        *page.write().unwrap_or_else(|e| e.into_inner()) = text;
+    });
+    server.run().await
+}
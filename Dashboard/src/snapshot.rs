@@ -0,0 +1,344 @@
+//! This module implements the headless snapshot mode.
+//! Instead of starting the terminal UI, the data of all subsystems is collected once and
+//! printed as a report, e.g. to paste it into an incident ticket or to call
+//! the dashboard from a script without a TTY.
+//! CPU usage and network rates need two samples, so the data is sampled twice,
+//! `SnapshotOptions::sample` apart.
+//! The report is printed as plain text, as JSON (the serialized `Snapshot`) or as CSV
+//! with one `section,item,metric,value` row per value.
+use crate::args::Args;
+use crate::backend::{
+    connections::{ConnectionReader, ListeningPort, listening_ports},
+    converter::{byte_to_gib, format_bytes},
+    cpu::CpuStats,
+    disk::{DiskInfo, format_disks, list_disks},
+    history::unix_now,
+    host::{HostInfo, format_uptime},
+    memory::MemInfo,
+    netstat::ProtocolStats,
+    network::{InterfaceDetails, InterfaceThroughput, NetworkManager, format_throughput},
+    power::{PowerStatus, PowerSupplyReader},
+    process_network::ProcessNetworkTracker,
+    processes::{ProcessInfo, SortOrder, list_processes},
+    sensors::{Sensor, SensorReader},
+};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt::Write;
+use std::time::Duration;
+use sysinfo::System;
+
+/// Output format of the snapshot mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+impl OutputFormat {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "text" => Some(OutputFormat::Text),
+            "json" => Some(OutputFormat::Json),
+            "csv" => Some(OutputFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// Options of the snapshot mode.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotOptions {
+    /// Number of processes listed, sorted by CPU usage
+    pub top: usize,
+    /// Time between the two samples used for CPU usage and network rates
+    pub sample: Duration,
+    pub format: OutputFormat,
+}
+
+impl Default for SnapshotOptions {
+    fn default() -> Self {
+        Self {
+            top: 10,
+            sample: Duration::from_millis(1000),
+            format: OutputFormat::Text,
+        }
+    }
+}
+
+/// Usage text of the snapshot mode.
+pub const USAGE: &str = "Usage: linux_dashboard snapshot [--top N] [--sample MS] [--format FORMAT]
+  --top N          number of processes to list (default 10)
+  --sample MS      sampling window in milliseconds for CPU and network rates (default 1000)
+  --format FORMAT  text, json or csv (default text)";
+
+impl SnapshotOptions {
+    /// Parses the arguments following `snapshot`.
+    /// Returns an error message for unknown arguments or invalid values.
+    ///
+    /// # Example
+    /// ```
+    /// use linux_dashboard::snapshot::SnapshotOptions;
+    /// let args = vec!["--top".to_string(), "5".to_string()];
+    /// let options = SnapshotOptions::from_args(&args).unwrap();
+    /// assert_eq!(options.top, 5);
+    /// ```
+    ///
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut options = Self::default();
+        let mut args = Args::new(args);
+        while let Some(arg) = args.next() {
+            match arg {
+                "--format" => {
+                    options.format = OutputFormat::parse(args.value("--format")?)
+                        .ok_or_else(|| "--format expects text, json or csv".to_string())?;
+                }
+                "--top" => options.top = args.number("--top")? as usize,
+                "--sample" => options.sample = args.interval("--sample")?,
+                other => return Err(format!("unknown argument '{other}'")),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// The data of all subsystems at one point in time.
+/// Sizes are in bytes, rates in bytes per second and usages in percent.
+#[derive(Debug, Clone, Serialize)]
+pub struct Snapshot {
+    pub timestamp: DateTime<Local>,
+    pub sample_ms: u64,
+    pub host: HostInfo,
+    pub uptime_seconds: u64,
+    pub cpu: CpuStats,
+    pub memory: MemInfo,
+    pub disks: Vec<DiskInfo>,
+    pub network: Vec<InterfaceThroughput>,
+    pub interfaces: Vec<InterfaceDetails>,
+    pub protocols: Option<ProtocolStats>,
+    pub listening_ports: Vec<ListeningPort>,
+    pub sensors: Vec<Sensor>,
+    pub power: PowerStatus,
+    /// The top processes by CPU usage
+    pub processes: Vec<ProcessInfo>,
+}
+
+impl Snapshot {
+    /// Collects the data of all subsystems.
+    /// Blocks for the sampling window.
+    pub fn collect(options: &SnapshotOptions) -> Self {
+        let mut sys = System::new_all();
+        let mut network_manager = NetworkManager::default();
+        let connection_reader = ConnectionReader::default();
+        let mut process_network = ProcessNetworkTracker::default();
+        network_manager.refresh();
+        process_network.update(unix_now(), &connection_reader.read_connections());
+
+        std::thread::sleep(options.sample);
+        sys.refresh_all();
+        network_manager.refresh();
+        let connections = connection_reader.read_connections();
+        let bandwidth = process_network.update(unix_now(), &connections);
+
+        let mut processes = list_processes(&sys, SortOrder::CpuDesc, "", &bandwidth);
+        processes.truncate(options.top);
+
+        Self {
+            timestamp: Local::now(),
+            sample_ms: options.sample.as_millis() as u64,
+            host: HostInfo::new(),
+            uptime_seconds: System::uptime(),
+            cpu: CpuStats::collect(&sys),
+            memory: MemInfo::collect(&sys),
+            disks: list_disks(),
+            network: network_manager.throughput().to_vec(),
+            interfaces: network_manager.interface_details().to_vec(),
+            protocols: ProtocolStats::read(),
+            listening_ports: listening_ports(&connections),
+            sensors: SensorReader::default().read_sensors(),
+            power: PowerSupplyReader::default().read_status(),
+            processes,
+        }
+    }
+
+    /// Renders the snapshot in the given format.
+    pub fn render(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Text => self.to_text(),
+            OutputFormat::Json => self.to_json(),
+            OutputFormat::Csv => self.to_csv(),
+        }
+    }
+
+    /// Returns the snapshot as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        // all fields are plain data with string keys, so serialization cannot fail
+        let mut json = serde_json::to_string_pretty(self).expect("snapshot is serializable");
+        json.push('\n');
+        json
+    }
+
+    /// Returns the snapshot as CSV with the columns `section,item,metric,value`.
+    /// `item` identifies the element of a list section (address, PID, name or label,
+    /// the index otherwise), nested fields are joined with dots, e.g. `link.speed_mbps`.
+    pub fn to_csv(&self) -> String {
+        let mut rows = vec![["section", "item", "metric", "value"].map(String::from)];
+        let Ok(Value::Object(sections)) = serde_json::to_value(self) else {
+            return String::new();
+        };
+        for (section, value) in sections {
+            match value {
+                Value::Array(elements) => {
+                    for (index, element) in elements.iter().enumerate() {
+                        let item = item_key(element).unwrap_or_else(|| index.to_string());
+                        flatten(&section, &item, "", element, &mut rows);
+                    }
+                }
+                Value::Object(_) => flatten(&section, "", "", &value, &mut rows),
+                scalar => flatten("snapshot", "", &section, &scalar, &mut rows),
+            }
+        }
+        rows.iter()
+            .map(|row| {
+                let fields: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+                fields.join(",") + "\n"
+            })
+            .collect()
+    }
+
+    /// Returns the snapshot as plain-text report.
+    pub fn to_text(&self) -> String {
+        let mut report = String::new();
+        // writing to a String cannot fail
+        let _ = self.write_text(&mut report);
+        report
+    }
+
+    fn write_text(&self, out: &mut String) -> std::fmt::Result {
+        writeln!(
+            out,
+            "Linux Dashboard snapshot {} (sampled over {} ms)",
+            self.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            self.sample_ms
+        )?;
+
+        let host = &self.host;
+        writeln!(out, "\n== Host ==")?;
+        writeln!(out, "Host Name:      {}", host.host_name)?;
+        writeln!(out, "System Name:    {}", host.system_name)?;
+        writeln!(out, "OS Version:     {}", host.os_version)?;
+        writeln!(out, "Kernel Version: {}", host.kernel_version)?;
+        writeln!(out, "{}", format_uptime(self.uptime_seconds).trim_end())?;
+
+        writeln!(out, "\n== CPU ==")?;
+        writeln!(out, "{}", self.cpu.name)?;
+        writeln!(out, "{}", self.cpu.format_total().trim_end())?;
+        write!(out, "{}", self.cpu.format_cores())?;
+
+        let mem = &self.memory;
+        writeln!(out, "\n== Memory ==")?;
+        writeln!(
+            out,
+            "RAM:  {:.2} GB used of {:.2} GB ({:.1}%), {:.2} GB available, {:.2} GB cache",
+            byte_to_gib(mem.used()),
+            byte_to_gib(mem.total),
+            mem.percent_of_total(mem.used()),
+            byte_to_gib(mem.available),
+            byte_to_gib(mem.cache())
+        )?;
+        writeln!(
+            out,
+            "Swap: {:.2} GB used of {:.2} GB",
+            byte_to_gib(mem.swap_used()),
+            byte_to_gib(mem.swap_total)
+        )?;
+
+        writeln!(out, "\n== Disks ==")?;
+        write!(out, "{}", format_disks(&self.disks))?;
+
+        writeln!(out, "\n== Network ==")?;
+        write!(out, "{}", format_throughput(&self.network))?;
+
+        writeln!(out, "\n== Top {} processes by CPU ==", self.processes.len())?;
+        writeln!(
+            out,
+            "{:>8}  {:<30} {:>8} {:>10}",
+            "PID", "Name", "CPU (%)", "Memory"
+        )?;
+        for process in &self.processes {
+            writeln!(
+                out,
+                "{:>8}  {:<30} {:>8.2} {:>10}",
+                process.pid,
+                process.name,
+                process.cpu_usage,
+                format_bytes(process.memory)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Collects the data of all subsystems and returns the report in the requested format.
+/// Blocks for the sampling window.
+pub fn collect_report(options: &SnapshotOptions) -> String {
+    Snapshot::collect(options).render(options.format)
+}
+
+// Returns the field identifying an element of a list section.
+fn item_key(element: &Value) -> Option<String> {
+    ["address", "pid", "name", "label"]
+        .iter()
+        .find_map(|key| match element.get(key)? {
+            Value::String(value) => Some(value.clone()),
+            Value::Number(value) => Some(value.to_string()),
+            _ => None,
+        })
+}
+
+// Appends one row per scalar below `value`, nested keys are joined with dots.
+fn flatten(section: &str, item: &str, path: &str, value: &Value, rows: &mut Vec<[String; 4]>) {
+    let join = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}.{key}")
+        }
+    };
+    match value {
+        Value::Object(fields) => {
+            for (key, field) in fields {
+                flatten(section, item, &join(key), field, rows);
+            }
+        }
+        Value::Array(elements) => {
+            for (index, element) in elements.iter().enumerate() {
+                flatten(section, item, &join(&index.to_string()), element, rows);
+            }
+        }
+        scalar => {
+            let value = match scalar {
+                Value::Null => String::new(),
+                Value::String(text) => text.clone(),
+                other => other.to_string(),
+            };
+            rows.push([
+                section.to_string(),
+                item.to_string(),
+                path.to_string(),
+                value,
+            ]);
+        }
+    }
+}
+
+// Quotes a CSV field if it contains a separator, a quote or a line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,665 @@
+//! This module keeps a downsampled history of the dashboard data on disk, so the charts
+//! can show the last day or week right after startup instead of only the minutes since then.
+//!
+//! A store is a directory with one file per tier, e.g. 24 hours at a 10 second resolution
+//! and 7 days at a 1 minute resolution. Every tier file is a ring buffer of fixed size:
+//! after the header, it holds one slot per resolution step of the retention window.
+//! A point at time `t` belongs to the bucket `t / resolution`, which is stored in the slot
+//! `bucket % slots`. Every slot records its bucket, so a slot left over from an earlier
+//! round of the ring is recognized as stale and the file never grows.
+//! All samples falling into one bucket are averaged, and every sample is written right away,
+//! so a crash loses nothing.
+//!
+//! Only one process writes to a store, it holds an exclusive lock on the `lock` file.
+//! Other processes, e.g. a second dashboard, open the store read only.
+use crate::args::Args;
+use crate::backend::alerts::parse_duration;
+use crate::backend::network::{InterfaceRate, RateTracker, is_virtual_interface};
+use crate::backend::sample::{Sample, SampleCollector};
+use crate::refresh::spawn_refresh_task;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io;
+use std::os::unix::fs::FileExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use sysinfo::System;
+use tokio::sync::{mpsc, watch};
+
+/// Tiers used if the configuration file lists none.
+pub const DEFAULT_TIERS: [Tier; 2] = [
+    Tier {
+        retention: Duration::from_secs(24 * 60 * 60),
+        resolution: Duration::from_secs(10),
+    },
+    Tier {
+        retention: Duration::from_secs(7 * 24 * 60 * 60),
+        resolution: Duration::from_secs(60),
+    },
+];
+
+/// Time ranges the history view zooms through, up to the longest retention.
+pub const ZOOM_LEVELS: [Duration; 5] = [
+    Duration::from_secs(60 * 60),
+    Duration::from_secs(6 * 60 * 60),
+    Duration::from_secs(24 * 60 * 60),
+    Duration::from_secs(3 * 24 * 60 * 60),
+    Duration::from_secs(7 * 24 * 60 * 60),
+];
+
+/// Upper limit of the slots of a tier, about 90 MB.
+const MAX_SLOTS: u64 = 1 << 20;
+
+const MAGIC: &[u8; 8] = b"LDHISTRY";
+const VERSION: u32 = 1;
+const HEADER_SIZE: u64 = 32;
+/// Number of values of a `HistoryPoint`
+const VALUES: usize = 9;
+/// Bucket, sample count, padding and the values
+const SLOT_SIZE: u64 = 8 + 4 + 4 + VALUES as u64 * 8;
+
+/// The downsampled data of one point in time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct HistoryPoint {
+    /// Seconds since the Unix epoch, the start of the bucket for stored points
+    pub timestamp: f64,
+    /// Total CPU usage in percent
+    pub cpu: f64,
+    pub memory_total: f64,
+    pub memory_used: f64,
+    pub memory_available: f64,
+    pub swap_used: f64,
+    /// Sum over all disks
+    pub disk_total: f64,
+    pub disk_used: f64,
+    /// Bytes per second received over all physical interfaces
+    pub network_rx: f64,
+    /// Bytes per second transmitted over all physical interfaces
+    pub network_tx: f64,
+}
+
+impl HistoryPoint {
+    fn values(&self) -> [f64; VALUES] {
+        [
+            self.cpu,
+            self.memory_total,
+            self.memory_used,
+            self.memory_available,
+            self.swap_used,
+            self.disk_total,
+            self.disk_used,
+            self.network_rx,
+            self.network_tx,
+        ]
+    }
+
+    fn from_values(timestamp: f64, values: [f64; VALUES]) -> Self {
+        let [
+            cpu,
+            memory_total,
+            memory_used,
+            memory_available,
+            swap_used,
+            disk_total,
+            disk_used,
+            network_rx,
+            network_tx,
+        ] = values;
+        Self {
+            timestamp,
+            cpu,
+            memory_total,
+            memory_used,
+            memory_available,
+            swap_used,
+            disk_total,
+            disk_used,
+            network_rx,
+            network_tx,
+        }
+    }
+
+    /// Returns the used disk space in percent of the total, zero without disks.
+    pub fn disk_used_percent(&self) -> f64 {
+        if self.disk_total > 0.0 {
+            self.disk_used / self.disk_total * 100.0
+        } else {
+            0.0
+        }
+    }
+}
+
+/// HistorySampler turns samples into history points.
+/// The network rates are computed from the counters of the previous sample.
+#[derive(Debug, Default)]
+pub struct HistorySampler {
+    rates: RateTracker,
+    sampled: bool,
+}
+
+impl HistorySampler {
+    /// Returns the point of a sample, `None` for the first sample as it has no network rates.
+    /// Loopback and virtual interfaces are left out, their traffic is counted by the
+    /// physical interfaces already. A disk mounted more than once is counted once.
+    pub fn point(&mut self, sample: &Sample) -> Option<HistoryPoint> {
+        let counters: Vec<_> = sample
+            .interfaces
+            .iter()
+            .filter(|interface| !is_virtual_interface(&interface.details.name))
+            .map(|interface| (interface.details.name.clone(), interface.counters))
+            .collect();
+        let rates = self.rates.update(sample.timestamp, &counters);
+        let first = !self.sampled;
+        self.sampled = true;
+        if first {
+            return None;
+        }
+
+        let mut devices = HashSet::new();
+        let disks: Vec<_> = sample
+            .disks
+            .iter()
+            .filter(|disk| devices.insert(disk.name.as_str()))
+            .collect();
+        let disk_total: u64 = disks.iter().map(|disk| disk.total).sum();
+        let disk_available: u64 = disks.iter().map(|disk| disk.available).sum();
+        let rate = |select: fn(&InterfaceRate) -> f64| {
+            rates
+                .iter()
+                .filter_map(|(_, rate)| rate.as_ref().map(select))
+                .sum()
+        };
+
+        Some(HistoryPoint {
+            timestamp: sample.timestamp,
+            cpu: sample.cpu.total_usage as f64,
+            memory_total: sample.memory.total as f64,
+            memory_used: sample.memory.used() as f64,
+            memory_available: sample.memory.available as f64,
+            swap_used: sample.memory.swap_used() as f64,
+            disk_total: disk_total as f64,
+            disk_used: disk_total.saturating_sub(disk_available) as f64,
+            network_rx: rate(|rate| rate.download),
+            network_tx: rate(|rate| rate.upload),
+        })
+    }
+}
+
+/// A tier of the store: how long points are kept and how far apart they are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tier {
+    pub retention: Duration,
+    pub resolution: Duration,
+}
+
+impl Tier {
+    /// Parses a tier written as `RETENTION@RESOLUTION`.
+    /// The resolution is a whole number of seconds, at most `retention`.
+    ///
+    /// # Example
+    /// ```
+    /// use linux_dashboard::store::Tier;
+    /// let tier = Tier::parse("7d@1m").unwrap();
+    /// assert_eq!(tier.slots(), 7 * 24 * 60);
+    /// assert_eq!(tier.to_string(), "7d@1m");
+    /// assert!(Tier::parse("1h@2h").is_err());
+    /// ```
+    ///
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (retention, resolution) = text
+            .split_once('@')
+            .ok_or_else(|| format!("invalid tier '{text}', expected e.g. 24h@10s"))?;
+        let tier = Self {
+            retention: parse_duration(retention.trim())?,
+            resolution: parse_duration(resolution.trim())?,
+        };
+        if tier.resolution.as_secs() == 0 || tier.resolution.subsec_nanos() != 0 {
+            return Err(format!(
+                "tier '{text}': the resolution has to be a whole number of seconds"
+            ));
+        }
+        if tier.retention < tier.resolution {
+            return Err(format!(
+                "tier '{text}': the retention is shorter than the resolution"
+            ));
+        }
+        if tier.slots() > MAX_SLOTS {
+            return Err(format!(
+                "tier '{text}': more than {MAX_SLOTS} points, use a coarser resolution"
+            ));
+        }
+        Ok(tier)
+    }
+
+    /// Returns the number of points the tier keeps.
+    pub fn slots(&self) -> u64 {
+        self.retention
+            .as_secs()
+            .div_ceil(self.resolution.as_secs().max(1))
+    }
+
+    fn file_name(&self) -> String {
+        format!(
+            "{}s-{}s.ring",
+            self.resolution.as_secs(),
+            self.retention.as_secs()
+        )
+    }
+}
+
+impl fmt::Display for Tier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}@{}",
+            short_duration(self.retention),
+            short_duration(self.resolution)
+        )
+    }
+}
+
+// Formats a duration in the largest unit that divides it, e.g. "7d" or "90s"
+fn short_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        0 => "0s".to_string(),
+        _ if secs.is_multiple_of(86400) => format!("{}d", secs / 86400),
+        _ if secs.is_multiple_of(3600) => format!("{}h", secs / 3600),
+        _ if secs.is_multiple_of(60) => format!("{}m", secs / 60),
+        _ => format!("{secs}s"),
+    }
+}
+
+/// One slot of a tier file.
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    bucket: i64,
+    count: u32,
+    values: [f64; VALUES],
+}
+
+impl Slot {
+    fn decode(bytes: &[u8]) -> Self {
+        let u64_at =
+            |at: usize| u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap_or_default());
+        let mut values = [0.0; VALUES];
+        for (i, value) in values.iter_mut().enumerate() {
+            *value = f64::from_bits(u64_at(16 + i * 8));
+        }
+        Self {
+            bucket: u64_at(0) as i64,
+            count: u32::from_le_bytes(bytes[8..12].try_into().unwrap_or_default()),
+            values,
+        }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(SLOT_SIZE as usize);
+        bytes.extend_from_slice(&self.bucket.to_le_bytes());
+        bytes.extend_from_slice(&self.count.to_le_bytes());
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+        for value in self.values {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes
+    }
+}
+
+/// The ring buffer file of one tier.
+struct TierFile {
+    tier: Tier,
+    file: File,
+    /// The slot written last, saves reading it back for every sample of the same bucket
+    current: Option<Slot>,
+}
+
+impl TierFile {
+    fn header(tier: &Tier) -> Vec<u8> {
+        let mut header = Vec::with_capacity(HEADER_SIZE as usize);
+        header.extend_from_slice(MAGIC);
+        header.extend_from_slice(&VERSION.to_le_bytes());
+        header.extend_from_slice(&(tier.resolution.as_secs() as u32).to_le_bytes());
+        header.extend_from_slice(&(tier.slots() as u32).to_le_bytes());
+        header.extend_from_slice(&(SLOT_SIZE as u32).to_le_bytes());
+        header.resize(HEADER_SIZE as usize, 0);
+        header
+    }
+
+    fn size(tier: &Tier) -> u64 {
+        HEADER_SIZE + tier.slots() * SLOT_SIZE
+    }
+
+    // Whether the file was written for this tier by this version
+    fn is_valid(file: &File, tier: &Tier) -> io::Result<bool> {
+        if file.metadata()?.len() != Self::size(tier) {
+            return Ok(false);
+        }
+        let mut header = vec![0u8; HEADER_SIZE as usize];
+        file.read_exact_at(&mut header, 0)?;
+        Ok(header == Self::header(tier))
+    }
+
+    /// Opens the file of the tier for writing.
+    /// A missing file, or a file of another format, is created anew.
+    fn create(dir: &Path, tier: Tier) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(dir.join(tier.file_name()))?;
+        if !Self::is_valid(&file, &tier)? {
+            file.set_len(0)?;
+            file.set_len(Self::size(&tier))?;
+            file.write_all_at(&Self::header(&tier), 0)?;
+        }
+        Ok(Self {
+            tier,
+            file,
+            current: None,
+        })
+    }
+
+    /// Opens the file of the tier for reading, `None` if there is no valid file.
+    fn open(dir: &Path, tier: Tier) -> io::Result<Option<Self>> {
+        let file = match File::open(dir.join(tier.file_name())) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        if !Self::is_valid(&file, &tier)? {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            tier,
+            file,
+            current: None,
+        }))
+    }
+
+    fn offset(&self, bucket: i64) -> u64 {
+        HEADER_SIZE + bucket.rem_euclid(self.tier.slots() as i64) as u64 * SLOT_SIZE
+    }
+
+    /// Adds the point to the average of its bucket.
+    fn push(&mut self, point: &HistoryPoint) -> io::Result<()> {
+        let resolution = self.tier.resolution.as_secs_f64();
+        let bucket = (point.timestamp / resolution).floor() as i64;
+        let slot = match self.current {
+            Some(slot) if slot.bucket == bucket => Some(slot),
+            _ => {
+                let mut bytes = vec![0u8; SLOT_SIZE as usize];
+                self.file.read_exact_at(&mut bytes, self.offset(bucket))?;
+                Some(Slot::decode(&bytes)).filter(|slot| slot.bucket == bucket && slot.count > 0)
+            }
+        };
+
+        let new = point.values();
+        let slot = match slot {
+            Some(mut slot) => {
+                let count = slot.count as f64;
+                for (value, new) in slot.values.iter_mut().zip(new) {
+                    *value = (*value * count + new) / (count + 1.0);
+                }
+                slot.count = slot.count.saturating_add(1);
+                slot
+            }
+            None => Slot {
+                bucket,
+                count: 1,
+                values: new,
+            },
+        };
+        self.file
+            .write_all_at(&slot.encode(), self.offset(bucket))?;
+        self.current = Some(slot);
+        Ok(())
+    }
+
+    /// Returns the points from `since` on, oldest first.
+    fn read(&self, since: f64) -> io::Result<Vec<HistoryPoint>> {
+        let slots = self.tier.slots();
+        let mut bytes = vec![0u8; (slots * SLOT_SIZE) as usize];
+        self.file.read_exact_at(&mut bytes, HEADER_SIZE)?;
+        let resolution = self.tier.resolution.as_secs_f64();
+
+        let mut points: Vec<HistoryPoint> = bytes
+            .chunks_exact(SLOT_SIZE as usize)
+            .enumerate()
+            .map(|(i, bytes)| (i, Slot::decode(bytes)))
+            // Empty, stale and torn slots are skipped
+            .filter(|(i, slot)| {
+                slot.count > 0
+                    && slot.bucket.rem_euclid(slots as i64) == *i as i64
+                    && slot.values.iter().all(|value| value.is_finite())
+            })
+            .map(|(_, slot)| {
+                HistoryPoint::from_values(slot.bucket as f64 * resolution, slot.values)
+            })
+            .filter(|point| point.timestamp >= since)
+            .collect();
+        points.sort_by(|a, b| a.timestamp.total_cmp(&b.timestamp));
+        Ok(points)
+    }
+}
+
+/// The history store in a directory.
+pub struct HistoryStore {
+    dir: PathBuf,
+    /// Finest resolution first
+    tiers: Vec<TierFile>,
+    /// Held while the store is open for writing
+    lock: Option<File>,
+}
+
+impl HistoryStore {
+    /// Opens the store in `dir` for writing, the directory and the tier files are created
+    /// if needed. If another process writes to the store, it is opened read only.
+    pub fn open(dir: &Path, tiers: &[Tier]) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let lock = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(dir.join("lock"))?;
+        match lock.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => return Self::open_read_only(dir, tiers),
+            Err(TryLockError::Error(e)) => return Err(e),
+        }
+        let mut files = tiers
+            .iter()
+            .map(|tier| TierFile::create(dir, *tier))
+            .collect::<io::Result<Vec<_>>>()?;
+        files.sort_by_key(|file| file.tier.resolution);
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            tiers: files,
+            lock: Some(lock),
+        })
+    }
+
+    /// Opens the store in `dir` for reading. Tiers without a file are left out.
+    pub fn open_read_only(dir: &Path, tiers: &[Tier]) -> io::Result<Self> {
+        let mut files = Vec::new();
+        for tier in tiers {
+            if let Some(file) = TierFile::open(dir, *tier)? {
+                files.push(file);
+            }
+        }
+        files.sort_by_key(|file| file.tier.resolution);
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            tiers: files,
+            lock: None,
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns whether this process writes to the store.
+    pub fn is_writable(&self) -> bool {
+        self.lock.is_some()
+    }
+
+    /// Returns the tiers of the store, finest resolution first.
+    pub fn tiers(&self) -> Vec<Tier> {
+        self.tiers.iter().map(|file| file.tier).collect()
+    }
+
+    /// Adds the point to every tier. A store opened read only ignores the point.
+    pub fn push(&mut self, point: &HistoryPoint) -> io::Result<()> {
+        if !self.is_writable() {
+            return Ok(());
+        }
+        for file in &mut self.tiers {
+            file.push(point)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the tier the points of the last `range` are read from:
+    /// the finest tier keeping the whole range, otherwise the one keeping the longest.
+    pub fn tier_for(&self, range: Duration) -> Option<Tier> {
+        let tiers = self.tiers();
+        tiers
+            .iter()
+            .find(|tier| tier.retention >= range)
+            .or_else(|| tiers.iter().max_by_key(|tier| tier.retention))
+            .copied()
+    }
+
+    /// Returns the points of the last `range` before `now` (seconds since the Unix epoch),
+    /// oldest first, from the tier of `tier_for`.
+    pub fn read(&self, range: Duration, now: f64) -> io::Result<Vec<HistoryPoint>> {
+        let Some(tier) = self.tier_for(range) else {
+            return Ok(Vec::new());
+        };
+        let since = now - range.as_secs_f64();
+        match self.tiers.iter().find(|file| file.tier == tier) {
+            Some(file) => Ok(file
+                .read(since)?
+                .into_iter()
+                .filter(|point| point.timestamp <= now)
+                .collect()),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Returns the time ranges of `ZOOM_LEVELS` the tiers cover, plus the longest retention
+    /// if it is not one of them.
+    ///
+    /// # Example
+    /// ```
+    /// use linux_dashboard::store::{HistoryStore, Tier};
+    /// let dir = std::env::temp_dir().join(format!("dashboard_zoom_doctest_{}", std::process::id()));
+    /// let store = HistoryStore::open(&dir, &[Tier::parse("12h@10s").unwrap()]).unwrap();
+    /// let hours: Vec<u64> = store.zoom_levels().iter().map(|r| r.as_secs() / 3600).collect();
+    /// assert_eq!(hours, vec![1, 6, 12]);
+    /// # std::fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    ///
+    pub fn zoom_levels(&self) -> Vec<Duration> {
+        let longest = self
+            .tiers
+            .iter()
+            .map(|file| file.tier.retention)
+            .max()
+            .unwrap_or_default();
+        let mut levels: Vec<Duration> = ZOOM_LEVELS
+            .iter()
+            .copied()
+            .filter(|level| *level <= longest)
+            .collect();
+        if !levels.contains(&longest) && !longest.is_zero() {
+            levels.push(longest);
+        }
+        levels
+    }
+}
+
+/// Options of the collect mode.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollectOptions {
+    /// Time between two samples
+    pub interval: Duration,
+}
+
+/// Usage text of the collect mode.
+pub const USAGE: &str = "Usage: linux_dashboard collect [--interval MS]
+  Writes the history store of the configuration file until stopped, e.g. as a service,
+  so the dashboard shows the history of the time it was not running
+  --interval MS    refresh interval in milliseconds (default 1000)";
+
+impl CollectOptions {
+    /// Parses the arguments following `collect`.
+    /// Returns an error message for unknown arguments or invalid values.
+    ///
+    /// # Example
+    /// ```
+    /// use linux_dashboard::store::CollectOptions;
+    /// let args = vec!["--interval".to_string(), "5000".to_string()];
+    /// let options = CollectOptions::from_args(&args).unwrap();
+    /// assert_eq!(options.interval.as_secs(), 5);
+    /// ```
+    ///
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut interval = Duration::from_millis(1000);
+        let mut args = Args::new(args);
+        while let Some(arg) = args.next() {
+            match arg {
+                "--interval" => interval = args.interval("--interval")?,
+                other => return Err(format!("unknown argument '{other}'")),
+            }
+        }
+        Ok(Self { interval })
+    }
+}
+
+/// Writes samples to the store until Ctrl-C is pressed or writing fails.
+/// The system is refreshed by the same background task the terminal UI uses.
+pub async fn collect(options: CollectOptions, mut store: HistoryStore) -> io::Result<()> {
+    if !store.is_writable() {
+        return Err(io::Error::other(format!(
+            "another process is writing to {}",
+            store.dir().display()
+        )));
+    }
+    let tiers: Vec<String> = store.tiers().iter().map(Tier::to_string).collect();
+    println!(
+        "Collecting the history in {} ({}), press Ctrl-C to stop",
+        store.dir().display(),
+        tiers.join(", ")
+    );
+
+    let mut collector = SampleCollector::default();
+    let mut sampler = HistorySampler::default();
+    let sys = Arc::new(Mutex::new(System::new_all()));
+    {
+        let s = sys.lock().unwrap_or_else(|e| e.into_inner());
+        sampler.point(&collector.collect(&s));
+    }
+
+    let (error_tx, mut error_rx) = mpsc::unbounded_channel();
+    let (interval_tx, interval_rx) = watch::channel(options.interval.as_millis() as u64);
+    let refresh = spawn_refresh_task(sys, interval_rx, move |s| {
+        if let Some(point) = sampler.point(&collector.collect(s))
+            && let Err(e) = store.push(&point)
+        {
+            let _ = error_tx.send(e);
+        }
+    });
+
+    let result = tokio::select! {
+        result = tokio::signal::ctrl_c() => result,
+        Some(e) = error_rx.recv() => Err(e),
+    };
+    drop(interval_tx);
+    let _ = refresh.await;
+    result
+}
@@ -2,21 +2,55 @@
 /// It is responsible for running the terminal UI, managing user interactions, and updating the display based on system information.
 /// It uses the `ratatui` crate for rendering the UI and `sysinfo` for fetching system data.
 /// System data is fetched asynchronously in a background tokio task.
+/// The UI is driven by samples (`backend::sample`), either collected live every tick,
+/// read from a recording in the replay mode or streamed by an agent.
+use crate::actions::ActionDispatcher;
+use crate::agent::{
+    AgentAddress, AgentConnection, AgentHello, AgentHistory, ViewerEvent, spawn_viewer,
+};
+use crate::backend::alerts::{AlertEngine, AlertInput, AlertRule};
 use crate::backend::host::HostInfo;
 use crate::backend::processes::kill_process;
-use crate::backend::processes::{SortOrder, create_process_rows_filtered};
-use crate::backend::system_info::SystemInfo;
+use crate::backend::processes::{ProcessInfo, SortOrder, process_position, sort_processes};
+use crate::config::Config;
+use crate::record::Recording;
+use crate::refresh::spawn_refresh_task;
+use crate::replay::Playback;
+use crate::store::{HistoryPoint, HistorySampler, HistoryStore, Tier};
 use crate::{
     backend::{
-        cpu::{format_cpu_name, format_cpu_usage},
-        host::get_current_user,
-        memory::ram_info_table,
-        network::NetworkManager,
+        connections::{BindScope, Connection, ConnectionFilter, listening_ports},
+        cpu::CpuStats,
+        history::{format_retention, local_time, unix_now},
+        host::{format_uptime, get_current_user},
+        memory::MemInfo,
+        memory_history::{MemoryHistory, UsagePoint},
+        netstat::ProtocolHistory,
+        network::{NetworkManager, format_throughput},
+        network_source::SampleNetworks,
+        oom::OomTracker,
+        power::PowerStatus,
+        sample::{Sample, SampleCollector},
+        sensors::Sensor,
     },
     ui::layout::{self},
+    ui::widgets::{
+        alerts::{alert_banner, alert_history_lines},
+        connections::{listening_ports_table, scrolled_connection_table},
+        history::HistoryCharts,
+        host::host_info_table,
+        memory::{ram_info_table, ram_usage_bar, ram_usage_legend},
+        memory_history::{MemoryChart, swap_activity_chart},
+        netstat::{ProtocolChart, protocol_stats_table},
+        network::{NetworkChart, network_details_table},
+        power::power_status_spans,
+        processes::process_rows,
+        sensors::sensor_table,
+    },
 };
 use chrono::Local;
 use color_eyre::Result;
+use color_eyre::eyre::eyre;
 use crossterm::event::KeyEventKind;
 use crossterm::terminal::disable_raw_mode;
 use crossterm::terminal::{EnterAlternateScreen, LeaveAlternateScreen, enable_raw_mode};
@@ -38,9 +72,16 @@ use std::io;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use sysinfo::System;
+use tokio::sync::{mpsc, watch};
 
 const MIN_WIDTH: u16 = 110;
 const MIN_HEIGHT: u16 = 24;
+/// How often the replay cursor is moved forward.
+const REPLAY_TICK: Duration = Duration::from_millis(100);
+/// How far the arrow keys move the replay cursor, in seconds.
+const SEEK_STEP: f64 = 10.0;
+/// How often the samples received from an agent are shown.
+const AGENT_TICK: Duration = Duration::from_millis(200);
 
 #[derive(PartialEq, Eq)]
 enum ActiveBlock {
@@ -48,11 +89,109 @@ enum ActiveBlock {
     Processes,
 }
 
+/// Where the samples shown by the app come from.
+enum DataSource {
+    /// A sample of the live system is collected every tick.
+    /// The system is refreshed by the background task, at the interval sent through `interval_tx`.
+    Live {
+        sys: Arc<Mutex<System>>,
+        interval_tx: watch::Sender<u64>,
+        collector: SampleCollector,
+    },
+    /// The samples of a recording are shown as the replay cursor passes them.
+    Replay(Box<Playback>),
+    /// The samples are streamed by an agent, which refreshes the system for all its viewers.
+    Agent(Attached),
+}
+
+/// The connection to an agent.
+struct Attached {
+    address: AgentAddress,
+    hello: AgentHello,
+    events: mpsc::UnboundedReceiver<ViewerEvent>,
+    /// Why the connection was lost, until it is back
+    disconnected: Option<String>,
+    /// Timestamp of the latest sample shown
+    latest: f64,
+}
+
+/// The on-disk history shown in the history view.
+struct History {
+    store: HistoryStore,
+    sampler: HistorySampler,
+    zoom_levels: Vec<Duration>,
+    /// Index into `zoom_levels`
+    zoom: usize,
+    /// The points of the current zoom level
+    points: Vec<HistoryPoint>,
+    /// Timestamp the points were read at
+    read_at: f64,
+    /// The last error reading or writing the store
+    error: Option<String>,
+}
+
+impl History {
+    /// Starts zoomed out to the longest range, so the whole history is shown right away.
+    fn new(store: HistoryStore) -> Self {
+        let zoom_levels = store.zoom_levels();
+        let mut history = Self {
+            store,
+            sampler: HistorySampler::default(),
+            zoom: zoom_levels.len().saturating_sub(1),
+            zoom_levels,
+            points: Vec::new(),
+            read_at: 0.0,
+            error: None,
+        };
+        history.reload(unix_now());
+        history
+    }
+
+    fn range(&self) -> Duration {
+        self.zoom_levels.get(self.zoom).copied().unwrap_or_default()
+    }
+
+    fn tier(&self) -> Option<Tier> {
+        self.store.tier_for(self.range())
+    }
+
+    fn reload(&mut self, now: f64) {
+        self.read_at = now;
+        match self.store.read(self.range(), now) {
+            Ok(points) => self.points = points,
+            Err(e) => self.error = Some(format!("cannot read the history: {e}")),
+        }
+    }
+
+    /// Writes the sample to the store. While `visible`, the points are read again
+    /// once a new point of the shown tier is due.
+    fn record(&mut self, sample: &Sample, visible: bool) {
+        if let Some(point) = self.sampler.point(sample)
+            && let Err(e) = self.store.push(&point)
+        {
+            self.error = Some(format!("cannot write the history: {e}"));
+        }
+        let resolution = self.tier().map(|tier| tier.resolution.as_secs_f64());
+        if visible && resolution.is_some_and(|r| sample.timestamp - self.read_at >= r) {
+            self.reload(sample.timestamp);
+        }
+    }
+
+    /// Switches to the next zoom level, from the longest back to the shortest.
+    fn zoom(&mut self) {
+        if !self.zoom_levels.is_empty() {
+            self.zoom = (self.zoom + 1) % self.zoom_levels.len();
+            self.reload(unix_now());
+        }
+    }
+}
+
 #[derive(PartialEq)]
 enum Mode {
     Normal,
     Input,
     Search,
+    ConnectionFilter,
 }
 
 struct App {
@@ -70,15 +209,66 @@ struct App {
     show_popup: bool,
     show_manual: bool,
     sort_order: SortOrder,
-    network_manager: NetworkManager,
+    source: DataSource,
+    /// Start of the time axis of the memory and protocol histories
+    started: Instant,
+    /// Timestamp of the first sample, shown at `started`
+    time_origin: Option<f64>,
+    cpu: CpuStats,
+    processes: Vec<ProcessInfo>,
+    uptime: u64,
+    network_manager: NetworkManager<SampleNetworks>,
+    memory_history: MemoryHistory,
+    mem_info: MemInfo,
+    oom_tracker: OomTracker,
+    show_oom_events: bool,
+    alerts: AlertEngine,
+    /// Runs the alert actions, only live: a replay does not page anyone
+    actions: Option<ActionDispatcher>,
+    show_alerts: bool,
+    /// The on-disk history, or why there is none
+    history: std::result::Result<History, String>,
+    show_history: bool,
+    show_network_details: bool,
+    protocol_history: ProtocolHistory,
+    show_protocol_stats: bool,
+    sensors: Vec<Sensor>,
+    power_status: PowerStatus,
+    show_connections: bool,
+    connections: Vec<Connection>,
+    connection_filter: String,
+    connection_selected: usize,
+    highlighted_pid: Option<u32>,
     kill_message: Option<(String, Instant)>,
-    cached_network_text: String,
     host_info: HostInfo,
     search_query: String,
 }
 
-impl Default for App {
-    fn default() -> Self {
+impl App {
+    fn new(source: DataSource, alert_rules: Vec<AlertRule>) -> Self {
+        let started = Instant::now();
+        let (host_info, history) = match &source {
+            DataSource::Live { .. } => (
+                HostInfo::new(),
+                Err(
+                    "The history is disabled. Set 'enabled = true' in the [history] \
+                     section of the configuration file to keep it."
+                        .to_string(),
+                ),
+            ),
+            DataSource::Replay(playback) => (
+                playback.header().host.clone(),
+                Err("The history store is not shown in a replay.".to_string()),
+            ),
+            DataSource::Agent(attached) => (
+                attached.hello.header.host.clone(),
+                Err(
+                    "The agent does not keep a history. Set 'enabled = true' in the [history] \
+                     section of the configuration file of the agent to keep it."
+                        .to_string(),
+                ),
+            ),
+        };
         Self {
             running: true,
             active_block: ActiveBlock::Cpu,
@@ -94,10 +284,34 @@ impl Default for App {
             show_popup: true,
             show_manual: false,
             sort_order: SortOrder::default(),
-            network_manager: NetworkManager::default(),
+            source,
+            started,
+            time_origin: None,
+            cpu: CpuStats::default(),
+            processes: Vec::new(),
+            uptime: 0,
+            network_manager: NetworkManager::new(SampleNetworks::default()),
+            memory_history: MemoryHistory::new(),
+            mem_info: MemInfo::default(),
+            oom_tracker: OomTracker::default(),
+            show_oom_events: false,
+            alerts: AlertEngine::new(alert_rules),
+            actions: None,
+            show_alerts: false,
+            history,
+            show_history: false,
+            show_network_details: false,
+            protocol_history: ProtocolHistory::new(started),
+            show_protocol_stats: false,
+            sensors: Vec::new(),
+            power_status: PowerStatus::default(),
+            show_connections: false,
+            connections: Vec::new(),
+            connection_filter: String::new(),
+            connection_selected: 0,
+            highlighted_pid: None,
             kill_message: None,
-            cached_network_text: String::new(),
-            host_info: HostInfo::new(),
+            host_info,
             search_query: String::new(),
         }
     }
@@ -106,7 +320,143 @@ impl Default for App {
 /// Entry point for the terminal UI.
 /// Spawns a background tokio task that refreshes system data at the configured interval.
 /// The UI thread reads from the shared state and handles user input without blocking on data fetching.
-pub async fn run_ui(mut terminal: DefaultTerminal) -> Result<()> {
+/// The alert rules are read from `config`.
+pub async fn run_ui(mut terminal: DefaultTerminal, config: &Config) -> Result<()> {
+    let alert_rules = config.alert_rules().map_err(|e| eyre!(e))?;
+    let alert_actions = config.alert_actions().map_err(|e| eyre!(e))?;
+    init_terminal(&mut terminal)?;
+
+    let sys = Arc::new(Mutex::new(System::new_all()));
+    {
+        let mut s = sys.lock().unwrap_or_else(|e| e.into_inner());
+        s.refresh_all();
+    }
+
+    // Channel to communicate the current fetch interval to the background task
+    let (interval_tx, interval_rx) = watch::channel(1000u64);
+
+    // Background task: refreshes system data at the configured interval
+    let bg_handle = spawn_refresh_task(Arc::clone(&sys), interval_rx, |_| {});
+
+    let mut app = App::new(
+        DataSource::Live {
+            sys,
+            interval_tx,
+            collector: SampleCollector::default(),
+        },
+        alert_rules,
+    );
+    app.actions = Some(ActionDispatcher::new(
+        alert_actions,
+        app.host_info.host_name.clone(),
+    ));
+    if config.history.enabled {
+        app.set_history(open_history(config));
+    }
+    let app_result = app.run(&mut terminal);
+
+    // Signal the background task to stop by dropping the sender
+    drop(app);
+    let _ = bg_handle.await;
+
+    restore_terminal()?;
+    app_result
+}
+
+/// Entry point for the replay mode: runs the terminal UI on the samples of a recording
+/// instead of the live system. The alert rules of `config` are evaluated on the recorded data.
+pub fn run_replay(
+    mut terminal: DefaultTerminal,
+    recording: Recording,
+    config: &Config,
+) -> Result<()> {
+    let alert_rules = config.alert_rules().map_err(|e| eyre!(e))?;
+    init_terminal(&mut terminal)?;
+    let mut app = App::new(
+        DataSource::Replay(Box::new(Playback::new(recording))),
+        alert_rules,
+    );
+    let app_result = app.run(&mut terminal);
+    restore_terminal()?;
+    app_result
+}
+
+/// Entry point for the attach mode: runs the terminal UI on the samples streamed by the
+/// agent at `address`, which `connection` was made to. `token` is used to attach again
+/// after the connection was lost. The alert rules of `config` are evaluated for the banner
+/// and the alert history, the actions run in the agent.
+pub async fn run_attach(
+    mut terminal: DefaultTerminal,
+    address: AgentAddress,
+    token: Option<String>,
+    connection: AgentConnection,
+    config: &Config,
+) -> Result<()> {
+    let alert_rules = config.alert_rules().map_err(|e| eyre!(e))?;
+    init_terminal(&mut terminal)?;
+
+    let hello = connection.hello.clone();
+    let (viewer, events) = spawn_viewer(address.clone(), token, connection);
+    // The history files are on the host of the agent
+    let history = if address.is_remote() {
+        Some(Err(
+            "The history of an agent on another host is not shown.".to_string()
+        ))
+    } else {
+        hello.history.as_ref().map(open_agent_history)
+    };
+    let mut app = App::new(
+        DataSource::Agent(Attached {
+            address,
+            hello,
+            events,
+            disconnected: None,
+            latest: f64::NEG_INFINITY,
+        }),
+        alert_rules,
+    );
+    if let Some(history) = history {
+        app.set_history(history);
+    }
+    let app_result = app.run(&mut terminal);
+
+    drop(app);
+    viewer.abort();
+    let _ = viewer.await;
+
+    restore_terminal()?;
+    app_result
+}
+
+/// Opens the history store an agent writes, read only.
+fn open_agent_history(history: &AgentHistory) -> std::result::Result<History, String> {
+    let tiers = history
+        .tiers
+        .iter()
+        .map(|tier| Tier::parse(tier))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    HistoryStore::open_read_only(&history.path, &tiers)
+        .map(History::new)
+        .map_err(|e| {
+            format!(
+                "cannot open the history store {} of the agent: {e}",
+                history.path.display()
+            )
+        })
+}
+
+/// Opens the history store of `config`, read only if another process writes to it.
+fn open_history(config: &Config) -> std::result::Result<History, String> {
+    let dir = config
+        .history_dir()
+        .ok_or_else(|| "no home directory for the history store".to_string())?;
+    let tiers = config.history_tiers()?;
+    HistoryStore::open(&dir, &tiers)
+        .map(History::new)
+        .map_err(|e| format!("cannot open the history store {}: {e}", dir.display()))
+}
+
+fn init_terminal(terminal: &mut DefaultTerminal) -> Result<()> {
     color_eyre::install()?;
 
     // Ensure terminal is restored even on panic
@@ -123,67 +473,31 @@ pub async fn run_ui(mut terminal: DefaultTerminal) -> Result<()> {
     crossterm::execute!(stdout, EnterAlternateScreen)?;
 
     terminal.clear()?;
+    Ok(())
+}
 
-    let sys = Arc::new(Mutex::new(System::new_all()));
-    {
-        let mut s = sys.lock().unwrap_or_else(|e| e.into_inner());
-        s.refresh_all();
-    }
-
-    // Channel to communicate the current fetch interval to the background task
-    let (interval_tx, interval_rx) = tokio::sync::watch::channel(1000u64);
-
-    // Background task: refreshes system data at the configured interval
-    let sys_bg = Arc::clone(&sys);
-    let bg_handle = tokio::spawn(async move {
-        let mut rx = interval_rx;
-        loop {
-            let interval = *rx.borrow();
-            tokio::select! {
-                _ = tokio::time::sleep(Duration::from_millis(interval)) => {
-                    let mut s = sys_bg.lock().unwrap_or_else(|e| e.into_inner());
-                    s.refresh_all();
-                }
-                result = rx.changed() => {
-                    if result.is_err() {
-                        // Sender dropped, exit
-                        break;
-                    }
-                }
-            }
-        }
-    });
-
-    let mut app = App::default();
-    let app_result = app.run(&mut terminal, &sys, &interval_tx);
-
-    // Signal the background task to stop by dropping the sender
-    drop(interval_tx);
-    let _ = bg_handle.await;
-
-    // Cleanup
+fn restore_terminal() -> Result<()> {
     crossterm::execute!(io::stdout(), LeaveAlternateScreen)?;
     disable_raw_mode()?;
-
-    app_result
+    Ok(())
 }
 
 impl App {
-    pub fn run(
-        &mut self,
-        terminal: &mut DefaultTerminal,
-        sys: &Arc<Mutex<System>>,
-        interval_tx: &tokio::sync::watch::Sender<u64>,
-    ) -> Result<()> {
+    pub fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
         let mut last_tick = Instant::now();
         let mut needs_redraw = true;
+        self.refresh_cached_data(Duration::ZERO);
 
         loop {
             if !self.running {
                 return Ok(());
             }
 
-            let tick_rate = Duration::from_millis(self.current_fetch_interval);
+            let tick_rate = match self.source {
+                DataSource::Live { .. } => Duration::from_millis(self.current_fetch_interval),
+                DataSource::Replay(_) => REPLAY_TICK,
+                DataSource::Agent(_) => AGENT_TICK,
+            };
             let timeout = tick_rate
                 .checked_sub(last_tick.elapsed())
                 .unwrap_or(Duration::ZERO);
@@ -192,36 +506,271 @@ impl App {
                 let evt = event::read()?;
                 // Only handle and redraw for key events, ignore mouse events
                 if matches!(&evt, Event::Key(_)) {
-                    let mut s = sys.lock().unwrap_or_else(|e| e.into_inner());
-                    self.handle_event(evt, &mut s)?;
-                    let _ = interval_tx.send(self.current_fetch_interval);
+                    self.handle_event(evt)?;
+                    if let DataSource::Live { interval_tx, .. } = &self.source {
+                        let _ = interval_tx.send(self.current_fetch_interval);
+                    }
                     needs_redraw = true;
                 }
             }
 
             // Tick abgelaufen -> neue Daten verfuegbar, neu zeichnen
-            if last_tick.elapsed() >= tick_rate {
+            let elapsed = last_tick.elapsed();
+            if elapsed >= tick_rate {
                 last_tick = Instant::now();
-                self.cached_network_text = self.network_manager.format_network();
+                self.refresh_cached_data(elapsed);
                 needs_redraw = true;
             }
 
             if needs_redraw {
                 needs_redraw = false;
-                let mut s = sys.lock().unwrap_or_else(|e| e.into_inner());
                 terminal.draw(|frame| {
                     let size = frame.area();
                     if size.width < MIN_WIDTH || size.height < MIN_HEIGHT {
                         self.render_size_error(frame, size);
                     } else {
-                        self.render(frame, &mut s);
+                        self.render(frame);
                     }
                 })?;
             }
         }
     }
 
-    pub fn handle_event(&mut self, evt: Event, sys: &mut System) -> Result<()> {
+    /// Updates the data that is sampled once per tick instead of on every redraw.
+    /// Live, one sample is collected. In a replay, the samples the cursor passed during
+    /// `elapsed` are applied in order. Attached to an agent, the samples received since
+    /// the last tick are applied.
+    fn refresh_cached_data(&mut self, elapsed: Duration) {
+        let samples = match &mut self.source {
+            DataSource::Live { sys, collector, .. } => {
+                let s = sys.lock().unwrap_or_else(|e| e.into_inner());
+                vec![collector.collect(&s)]
+            }
+            DataSource::Replay(playback) => {
+                let due = playback.advance(elapsed);
+                playback.samples(due)
+            }
+            DataSource::Agent(attached) => {
+                let mut samples = Vec::new();
+                while let Ok(event) = attached.events.try_recv() {
+                    match event {
+                        // After attaching again, the backlog repeats samples already shown
+                        ViewerEvent::Sample(sample) if sample.timestamp > attached.latest => {
+                            attached.latest = sample.timestamp;
+                            samples.push(*sample);
+                        }
+                        ViewerEvent::Sample(_) => {}
+                        ViewerEvent::Disconnected(reason) => attached.disconnected = Some(reason),
+                        ViewerEvent::Reconnected(hello) => {
+                            attached.hello = *hello;
+                            attached.disconnected = None;
+                        }
+                    }
+                }
+                samples
+            }
+        };
+        for sample in &samples {
+            self.apply_sample(sample);
+        }
+    }
+
+    /// Feeds a sample to the histories and keeps its data for rendering.
+    fn apply_sample(&mut self, sample: &Sample) {
+        // A corrupt recording or agent may send any timestamp, such samples are skipped
+        if !sample.timestamp.is_finite() {
+            return;
+        }
+        let origin = *self.time_origin.get_or_insert(sample.timestamp);
+        let Some(instant) = Duration::try_from_secs_f64((sample.timestamp - origin).max(0.0))
+            .ok()
+            .and_then(|elapsed| self.started.checked_add(elapsed))
+        else {
+            return;
+        };
+
+        let networks = self.network_manager.source_mut();
+        networks.set_interfaces(sample.interfaces.clone());
+        self.network_manager.refresh_at(sample.timestamp);
+        self.memory_history
+            .update_at(&sample.memory, sample.vmstat, sample.timestamp);
+        self.protocol_history.update_at(sample.protocols, instant);
+        self.oom_tracker.update(
+            sample.vmstat.map(|v| v.oom_kill),
+            &sample.oom_cgroup_counts,
+            sample.tracked_processes(),
+            local_time(sample.timestamp),
+        );
+        let input = AlertInput {
+            cpu: &sample.cpu,
+            memory: &sample.memory,
+            disks: &sample.disks,
+            network: self.network_manager.throughput(),
+            processes: &sample.processes,
+        };
+        let events = self.alerts.evaluate(&input, sample.timestamp);
+        if let Some(actions) = &mut self.actions {
+            actions.dispatch(&events, &input, sample.timestamp);
+        }
+        if let Ok(history) = &mut self.history {
+            history.record(sample, self.show_history);
+        }
+
+        self.mem_info = sample.memory.clone();
+        self.cpu = sample.cpu.clone();
+        self.processes = sample.processes.clone();
+        self.connections = sample.connections.clone();
+        self.sensors = sample.sensors.clone();
+        self.power_status = sample.power.clone();
+        self.uptime = sample.uptime_seconds;
+    }
+
+    /// Sets the on-disk history. The charts can zoom out to its ranges and start with the
+    /// shortest of them, seeded with the stored points.
+    fn set_history(&mut self, history: std::result::Result<History, String>) {
+        if let Ok(history) = &history
+            && let Some(range) = history.zoom_levels.first()
+        {
+            self.network_manager
+                .add_retention_presets(&history.zoom_levels);
+            self.network_manager.set_retention(*range);
+            self.memory_history.set_retention(*range);
+        }
+        self.history = history;
+        self.seed_charts();
+    }
+
+    /// Seeds the network and memory charts with the stored points of their retention window.
+    fn seed_charts(&mut self) {
+        let Ok(history) = &mut self.history else {
+            return;
+        };
+        let points = match history
+            .store
+            .read(self.network_manager.retention(), unix_now())
+        {
+            Ok(points) => points,
+            Err(e) => {
+                history.error = Some(format!("cannot read the history: {e}"));
+                return;
+            }
+        };
+        let network: Vec<(f64, f64, f64)> = points
+            .iter()
+            .map(|point| (point.timestamp, point.network_rx, point.network_tx))
+            .collect();
+        self.network_manager.seed_history(&network);
+        let usage: Vec<UsagePoint> = points
+            .iter()
+            .map(|point| UsagePoint {
+                timestamp: point.timestamp,
+                used: point.memory_used as u64,
+                available: point.memory_available as u64,
+                swap_used: point.swap_used as u64,
+            })
+            .collect();
+        self.memory_history.seed_history(&usage);
+    }
+
+    /// Moves the replay cursor by `seconds` and rebuilds the histories from the samples
+    /// before the new cursor position.
+    fn seek(&mut self, seconds: f64) {
+        let DataSource::Replay(playback) = &mut self.source else {
+            return;
+        };
+        let window = self.network_manager.retention();
+        let range = playback.seek(seconds, window);
+        let samples = playback.samples(range);
+
+        self.network_manager.clear_history();
+        self.time_origin = None;
+        self.memory_history = MemoryHistory::new();
+        self.memory_history.set_retention(window);
+        self.protocol_history = ProtocolHistory::new(self.started);
+        self.oom_tracker = OomTracker::default();
+        self.alerts.reset();
+        for sample in &samples {
+            self.apply_sample(sample);
+        }
+    }
+
+    /// Handles the playback keys of the replay mode, returns false for other keys.
+    fn handle_replay_key(&mut self, code: KeyCode) -> bool {
+        let DataSource::Replay(playback) = &mut self.source else {
+            return false;
+        };
+        match code {
+            KeyCode::Char(' ') => playback.toggle_pause(),
+            KeyCode::Char('+') => playback.faster(),
+            KeyCode::Char('-') => playback.slower(),
+            KeyCode::Left => self.seek(-SEEK_STEP),
+            KeyCode::Right => self.seek(SEEK_STEP),
+            KeyCode::Home => self.seek(f64::NEG_INFINITY),
+            KeyCode::End => self.seek(f64::INFINITY),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Returns the sockets matching the connection filter.
+    fn filtered_connections(&self) -> Vec<&Connection> {
+        let filter = ConnectionFilter::parse(&self.connection_filter);
+        self.connections
+            .iter()
+            .filter(|connection| filter.matches(connection))
+            .collect()
+    }
+
+    /// Handles the keys while the connections view is open.
+    /// Enter jumps to the process owning the selected socket in the process table.
+    fn handle_connections_key(&mut self, code: KeyCode) {
+        let count = self.filtered_connections().len();
+        match code {
+            KeyCode::Char('q') => self.running = false,
+            KeyCode::Char('s') | KeyCode::Esc => self.show_connections = false,
+            KeyCode::Char('f') | KeyCode::Char('/') => {
+                self.mode = Mode::ConnectionFilter;
+            }
+            KeyCode::Up => {
+                self.connection_selected = self.connection_selected.saturating_sub(1);
+            }
+            KeyCode::Down => {
+                self.connection_selected =
+                    (self.connection_selected + 1).min(count.saturating_sub(1));
+            }
+            KeyCode::Enter => {
+                let pid = self
+                    .filtered_connections()
+                    .get(self.connection_selected)
+                    .and_then(|connection| connection.pid);
+                if let Some(pid) = pid {
+                    self.search_query.clear();
+                    self.process_scroll =
+                        process_position(&self.processes, self.sort_order, "", pid).unwrap_or(0);
+                    self.highlighted_pid = Some(pid);
+                    self.active_block = ActiveBlock::Processes;
+                    self.show_connections = false;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Handles the keys of the history view.
+    fn handle_history_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Char('q') => self.running = false,
+            KeyCode::Char('H') | KeyCode::Esc => self.show_history = false,
+            KeyCode::Char('r') => {
+                if let Ok(history) = &mut self.history {
+                    history.zoom();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn handle_event(&mut self, evt: Event) -> Result<()> {
         if let Event::Key(KeyEvent { code, kind, .. }) = evt {
             if kind != KeyEventKind::Press {
                 return Ok(());
@@ -238,7 +787,21 @@ impl App {
                     }
                     KeyCode::Enter => {
                         if let Ok(pid) = self.input.parse::<usize>() {
-                            let msg = kill_process(sys, pid);
+                            let msg = match &self.source {
+                                DataSource::Live { sys, .. } => {
+                                    let mut s = sys.lock().unwrap_or_else(|e| e.into_inner());
+                                    kill_process(&mut s, pid)
+                                }
+                                DataSource::Replay(_) => {
+                                    "Failed: a replay cannot kill processes".to_string()
+                                }
+                                DataSource::Agent(attached) if attached.address.is_remote() => {
+                                    "Failed: processes on another host cannot be killed".to_string()
+                                }
+                                // The agent runs on the same host, the viewer kills
+                                // with its own permissions
+                                DataSource::Agent(_) => kill_process(&mut System::new(), pid),
+                            };
                             self.kill_message = Some((msg, Instant::now()));
                         }
                         self.mode = Mode::Normal;
@@ -263,31 +826,44 @@ impl App {
                 return Ok(());
             }
 
+            // Connection filter mode (filter the connections view)
+            if self.mode == Mode::ConnectionFilter {
+                match code {
+                    KeyCode::Char(c) => self.connection_filter.push(c),
+                    KeyCode::Backspace => {
+                        self.connection_filter.pop();
+                    }
+                    KeyCode::Esc | KeyCode::Enter => self.mode = Mode::Normal,
+                    _ => {}
+                }
+                self.connection_selected = 0;
+                return Ok(());
+            }
+
+            if self.show_connections {
+                self.handle_connections_key(code);
+                return Ok(());
+            }
+
+            if self.show_history {
+                self.handle_history_key(code);
+                return Ok(());
+            }
+
+            if self.handle_replay_key(code) {
+                return Ok(());
+            }
+
             // Normal Mode
             match code {
                 KeyCode::Char('q') => self.running = false,
-                KeyCode::Enter => {
-                    if self.show_popup {
-                        self.show_popup = false;
-                    }
-                }
-                KeyCode::Esc => {
-                    if !self.show_popup {
-                        self.show_manual = !self.show_manual;
-                    }
+                KeyCode::Enter if self.show_popup => {
+                    self.show_popup = false;
                 }
-                KeyCode::Char('i') => {
-                    let interfaces: Vec<_> = self.network_manager.network_history_keys();
-                    if !interfaces.is_empty() {
-                        let current_index = interfaces
-                            .iter()
-                            .position(|x| x == self.network_manager.get_selected_interface())
-                            .unwrap_or(0);
-                        let next_index = (current_index + 1) % interfaces.len();
-                        self.network_manager
-                            .set_selected_interface(interfaces[next_index].clone());
-                    }
+                KeyCode::Esc if !self.show_popup => {
+                    self.show_manual = !self.show_manual;
                 }
+                KeyCode::Char('i') => self.network_manager.select_next_interface(),
                 KeyCode::Char('c') => {
                     self.sort_order = match self.sort_order {
                         SortOrder::CpuAsc => SortOrder::CpuDesc,
@@ -312,6 +888,12 @@ impl App {
                         _ => SortOrder::NameAsc,
                     }
                 }
+                KeyCode::Char('b') => {
+                    self.sort_order = match self.sort_order {
+                        SortOrder::NetworkDesc => SortOrder::NetworkAsc,
+                        _ => SortOrder::NetworkDesc,
+                    }
+                }
                 KeyCode::Tab => {
                     self.active_block = match self.active_block {
                         ActiveBlock::Cpu => ActiveBlock::Processes,
@@ -324,6 +906,7 @@ impl App {
                         self.cpu_scroll_state = self.cpu_scroll_state.position(self.cpu_scroll);
                     }
                     ActiveBlock::Processes => {
+                        self.highlighted_pid = None;
                         self.process_scroll = self.process_scroll.saturating_sub(1);
                         self.process_scroll_state =
                             self.process_scroll_state.position(self.process_scroll);
@@ -335,6 +918,7 @@ impl App {
                         self.cpu_scroll_state = self.cpu_scroll_state.position(self.cpu_scroll);
                     }
                     ActiveBlock::Processes => {
+                        self.highlighted_pid = None;
                         self.process_scroll = self.process_scroll.saturating_add(1);
                         self.process_scroll_state =
                             self.process_scroll_state.position(self.process_scroll);
@@ -348,6 +932,36 @@ impl App {
                     self.current_fetch_interval =
                         self.current_fetch_interval.saturating_add(100).min(60000);
                 }
+                KeyCode::Char('a') => self.network_manager.toggle_all_interfaces(),
+                KeyCode::Char('v') => self.network_manager.toggle_hide_virtual(),
+                KeyCode::Char('r') => {
+                    self.network_manager.cycle_retention();
+                    self.memory_history
+                        .set_retention(self.network_manager.retention());
+                    self.seed_charts();
+                }
+                KeyCode::Char('d') => {
+                    self.show_network_details = !self.show_network_details;
+                }
+                KeyCode::Char('t') => {
+                    self.show_protocol_stats = !self.show_protocol_stats;
+                }
+                KeyCode::Char('o') => {
+                    self.show_oom_events = !self.show_oom_events;
+                }
+                KeyCode::Char('A') => {
+                    self.show_alerts = !self.show_alerts;
+                }
+                KeyCode::Char('H') => {
+                    self.show_history = true;
+                    if let Ok(history) = &mut self.history {
+                        history.reload(unix_now());
+                    }
+                }
+                KeyCode::Char('s') => {
+                    self.show_connections = true;
+                    self.connection_selected = 0;
+                }
                 KeyCode::Char('M') => {
                     self.mode = Mode::Input;
                     self.input.clear();
@@ -415,7 +1029,7 @@ impl App {
         frame.render_widget(paragraph, size);
     }
 
-    fn render(&mut self, frame: &mut Frame, sys: &mut System) {
+    fn render(&mut self, frame: &mut Frame) {
         let area = frame.area();
 
         self.render_outer_frame(frame, area);
@@ -427,13 +1041,17 @@ impl App {
         });
         let chunks = layout::terminal_layout(inner_area);
 
-        self.render_cpu_gauge(frame, sys, chunks[0]);
-        self.render_cpu_cores(frame, sys, chunks[1]);
+        let cpu = self.cpu.clone();
+        self.render_cpu_gauge(frame, &cpu, chunks[0]);
+        self.render_cpu_cores(frame, &cpu, chunks[1]);
         self.render_network_info(frame, chunks[2]);
-        self.render_memory(frame, sys, chunks[3]);
-        self.render_processes(frame, sys, chunks[4]);
+        self.render_memory(frame, chunks[3]);
+        self.render_processes(frame, chunks[4]);
         self.render_network_chart(frame, chunks[5]);
         self.render_host_info(frame, chunks[6]);
+        self.render_memory_history(frame, chunks[7]);
+        self.render_sensors(frame, chunks[8]);
+        self.render_listening_ports(frame, chunks[9]);
 
         if self.show_popup {
             self.render_welcome_popup(frame, area);
@@ -441,9 +1059,36 @@ impl App {
         if self.show_manual {
             self.render_manual(frame, area);
         }
+        self.render_alert_banner(frame, inner_area);
+
+        if self.show_oom_events {
+            self.render_oom_events(frame, area);
+        }
+        if self.show_alerts {
+            self.render_alert_history(frame, area);
+        }
+        if self.show_network_details {
+            self.render_network_details(frame, area);
+        }
+        if self.show_protocol_stats {
+            self.render_protocol_stats(frame, area);
+        }
+        if self.show_connections {
+            self.render_connections(frame, area);
+        }
+        if self.show_history {
+            self.render_history(frame, area);
+        }
     }
 
     fn render_outer_frame(&self, frame: &mut Frame, area: Rect) {
+        // Attached to another host, its name tells which host is shown
+        let identity = match &self.source {
+            DataSource::Agent(attached) if attached.address.is_remote() => {
+                format!("Host: {}", self.host_info.host_name)
+            }
+            _ => format!("User: {}", get_current_user()),
+        };
         let outer_block = Block::default()
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
@@ -456,16 +1101,10 @@ impl App {
                 )])
                 .left_aligned(),
             )
+            .title_bottom(Line::from(vec![Span::styled(identity, Style::default())]).centered())
             .title_bottom(
                 Line::from(vec![Span::styled(
-                    format!("User: {}", get_current_user()),
-                    Style::default(),
-                )])
-                .centered(),
-            )
-            .title_bottom(
-                Line::from(vec![Span::styled(
-                    system_uptime().to_string(),
+                    format_uptime(self.uptime),
                     Style::default(),
                 )])
                 .right_aligned(),
@@ -476,10 +1115,85 @@ impl App {
     fn render_top_bar(&mut self, frame: &mut Frame, area: Rect) {
         let top_bar_area = Rect::new(area.x + 1, area.y, area.width.saturating_sub(2), 1);
 
-        let current_time_str = Local::now().format("%H:%M:%S").to_string();
+        // A replay shows the recorded time with its date, the recording may be days old
+        let current_time_str = match &self.source {
+            DataSource::Live { .. } | DataSource::Agent(_) => {
+                Local::now().format("%H:%M:%S").to_string()
+            }
+            DataSource::Replay(playback) => local_time(playback.cursor())
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string(),
+        };
         let time_paragraph = Paragraph::new(current_time_str).alignment(Alignment::Center);
         frame.render_widget(time_paragraph, top_bar_area);
 
+        // Left of the clock: battery status (only on systems with a battery)
+        // followed by the latest OOM kill, which stays visible until the application is closed.
+        // The content starts after the "System Monitor" title of the outer frame.
+        let mut left_spans = power_status_spans(&self.power_status);
+        if let Some(event) = self.oom_tracker.latest_event() {
+            left_spans.push(Span::styled(
+                format!("⚠ {} ", event.describe()),
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ));
+        }
+        if !left_spans.is_empty() {
+            const TITLE_WIDTH: u16 = 16;
+            let left_area = Rect::new(
+                top_bar_area.x + TITLE_WIDTH,
+                top_bar_area.y,
+                (top_bar_area.width / 2).saturating_sub(TITLE_WIDTH + 5),
+                1,
+            );
+            frame.render_widget(
+                Paragraph::new(Line::from(left_spans)).alignment(Alignment::Left),
+                left_area,
+            );
+        }
+
+        if let DataSource::Replay(playback) = &self.source {
+            let state = if playback.is_paused() { "⏸" } else { "▶" };
+            let (replay_display, color) = match playback.error() {
+                Some(error) => (format!("Replay stopped: {error}"), Color::Red),
+                None => (
+                    format!(
+                        "Replay of {} {state} {}x [{}/{}]",
+                        self.host_info.host_name,
+                        playback.speed(),
+                        playback.position(),
+                        playback.len()
+                    ),
+                    Color::Yellow,
+                ),
+            };
+            frame.render_widget(
+                Paragraph::new(Span::styled(replay_display, Style::default().fg(color)))
+                    .alignment(Alignment::Right),
+                top_bar_area,
+            );
+            return;
+        }
+
+        // The agent sets the interval for all its viewers
+        if let DataSource::Agent(attached) = &self.source {
+            let (agent_display, color) = match &attached.disconnected {
+                Some(reason) => (format!("Agent lost: {reason}, reconnecting"), Color::Red),
+                None => (
+                    format!(
+                        "Agent {} · {}ms",
+                        attached.address, attached.hello.header.interval_ms
+                    ),
+                    Color::Yellow,
+                ),
+            };
+            frame.render_widget(
+                Paragraph::new(Span::styled(agent_display, Style::default().fg(color)))
+                    .alignment(Alignment::Right),
+                top_bar_area,
+            );
+            return;
+        }
+
         let interval_display = format!("Fetch Interval: {}ms", self.current_fetch_interval);
         let minus_btn_text: &str = "[ ◄";
         let plus_btn_text: &str = "► ]";
@@ -520,20 +1234,20 @@ impl App {
         frame.render_widget(fetch_interval_paragraph, top_bar_area);
     }
 
-    fn render_cpu_gauge(&self, frame: &mut Frame, sys: &System, area: Rect) {
+    fn render_cpu_gauge(&self, frame: &mut Frame, cpu: &CpuStats, area: Rect) {
         let cpu_gauge = Gauge::default()
             .block(
                 Block::default()
-                    .title(format_cpu_name(sys))
+                    .title(cpu.name.clone())
                     .borders(Borders::ALL),
             )
             .gauge_style(Style::default().fg(Color::LightBlue).bg(Color::Gray))
-            .percent(sys.global_cpu_usage() as u16);
+            .percent(cpu.total_usage as u16);
         frame.render_widget(cpu_gauge, area);
     }
 
-    fn render_cpu_cores(&mut self, frame: &mut Frame, sys: &System, area: Rect) {
-        let cpu_count = sys.get_cpus().len();
+    fn render_cpu_cores(&mut self, frame: &mut Frame, cpu: &CpuStats, area: Rect) {
+        let cpu_count = cpu.cores.len();
         let visible_lines = area.height.saturating_sub(2) as usize; // -2 for borders
         let max_scroll = cpu_count.saturating_sub(visible_lines);
         self.cpu_scroll = self.cpu_scroll.min(max_scroll);
@@ -547,7 +1261,7 @@ impl App {
             } else {
                 Style::default()
             });
-        let cpu_widget = Paragraph::new(format_cpu_usage(sys))
+        let cpu_widget = Paragraph::new(cpu.format_cores())
             .block(cpu_block)
             .wrap(Wrap { trim: true })
             .scroll((self.cpu_scroll as u16, 0));
@@ -566,39 +1280,80 @@ impl App {
         );
     }
 
-    fn render_memory(&self, frame: &mut Frame, sys: &System, area: Rect) {
+    fn render_memory(&self, frame: &mut Frame, area: Rect) {
         let memory_block = Block::default()
             .title("Memory Usage ")
             .borders(Borders::ALL);
-        let memory_table = ram_info_table(sys).block(memory_block);
-        frame.render_widget(memory_table, area);
+        let inner = memory_block.inner(area);
+        frame.render_widget(memory_block, area);
+
+        // Stacked bar and legend on top, detailed table below
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Min(0),
+            ])
+            .split(inner);
+
+        let mem = &self.mem_info;
+        frame.render_widget(
+            Paragraph::new(ram_usage_bar(mem, chunks[0].width)),
+            chunks[0],
+        );
+        frame.render_widget(Paragraph::new(ram_usage_legend(mem)), chunks[1]);
+        frame.render_widget(ram_info_table(mem), chunks[2]);
+    }
+
+    fn render_memory_history(&self, frame: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(area);
+        let memory_chart = MemoryChart::new(&self.memory_history);
+        frame.render_widget(memory_chart.widget(), chunks[0]);
+        frame.render_widget(swap_activity_chart(&self.memory_history), chunks[1]);
     }
 
     fn render_network_info(&self, frame: &mut Frame, area: Rect) {
         let network_block = Block::default().title("Network").borders(Borders::ALL);
-        let network_widget = Paragraph::new(self.cached_network_text.clone())
+        let network_widget = Paragraph::new(format_throughput(self.network_manager.throughput()))
             .block(network_block)
             .wrap(Wrap { trim: true });
         frame.render_widget(network_widget, area);
     }
 
-    fn render_network_chart(&mut self, frame: &mut Frame, area: Rect) {
-        let network_diagram = self.network_manager.get_network_widget();
-        frame.render_widget(network_diagram, area);
+    fn render_network_chart(&self, frame: &mut Frame, area: Rect) {
+        let network_chart = NetworkChart::new(&self.network_manager);
+        frame.render_widget(network_chart.widget(), area);
     }
 
-    fn render_processes(&mut self, frame: &mut Frame, sys: &System, area: Rect) {
-        let process_rows = create_process_rows_filtered(sys, self.sort_order, &self.search_query);
+    fn render_processes(&mut self, frame: &mut Frame, area: Rect) {
+        let mut process_rows = process_rows(&sort_processes(
+            &self.processes,
+            self.sort_order,
+            &self.search_query,
+        ));
+        // Highlight the process jumped to from the connections view (row 0 is the header)
+        if let Some(pid) = self.highlighted_pid
+            && let Some(position) =
+                process_position(&self.processes, self.sort_order, &self.search_query, pid)
+        {
+            let row = process_rows[position + 1].clone();
+            process_rows[position + 1] =
+                row.style(Style::default().add_modifier(Modifier::REVERSED));
+        }
         let num_processes = process_rows.len();
         let table_height = area.height as usize - 2;
         let max_scroll = num_processes.saturating_sub(table_height);
         self.process_scroll = self.process_scroll.min(max_scroll);
         self.process_scroll_state = self.process_scroll_state.content_length(num_processes);
 
-        if let Some((_, timestamp)) = &self.kill_message {
-            if timestamp.elapsed().as_secs() >= 5 {
-                self.kill_message = None;
-            }
+        if let Some((_, timestamp)) = &self.kill_message
+            && timestamp.elapsed().as_secs() >= 5
+        {
+            self.kill_message = None;
         }
 
         let block_title = if self.mode == Mode::Input {
@@ -606,7 +1361,11 @@ impl App {
         } else if self.mode == Mode::Search {
             format!("Search: {}█", self.search_query)
         } else if !self.search_query.is_empty() {
-            format!("Processes [filter: {}] ({} results)", self.search_query, num_processes.saturating_sub(1))
+            format!(
+                "Processes [filter: {}] ({} results)",
+                self.search_query,
+                num_processes.saturating_sub(1)
+            )
         } else if let Some((msg, _)) = &self.kill_message {
             let color = if msg.starts_with("Failed") || msg.starts_with("No process") {
                 Color::Red
@@ -629,7 +1388,9 @@ impl App {
                     Span::styled("P", Style::default().fg(Color::Yellow)),
                     Span::raw("ID───"),
                     Span::styled("N", Style::default().fg(Color::Yellow)),
-                    Span::raw("ame"),
+                    Span::raw("ame───"),
+                    Span::styled("B", Style::default().fg(Color::Yellow)),
+                    Span::raw("andwidth (netns: namespace traffic, io~: I/O estimate)"),
                 ])
                 .left_aligned(),
             )
@@ -651,6 +1412,7 @@ impl App {
             Constraint::Length(10),
             Constraint::Length(10),
             Constraint::Length(12),
+            Constraint::Length(16),
         ];
 
         let processes_table = Table::new(visible_rows, widths)
@@ -677,10 +1439,40 @@ impl App {
         let host_info_block = Block::default()
             .title("Host System Information ")
             .borders(Borders::ALL);
-        let table = self.host_info.to_table().block(host_info_block);
+        let table = host_info_table(&self.host_info).block(host_info_block);
         frame.render_widget(table, area);
     }
 
+    fn render_sensors(&self, frame: &mut Frame, area: Rect) {
+        let sensors_block = Block::default().title("Sensors ").borders(Borders::ALL);
+        if self.sensors.is_empty() {
+            let paragraph = Paragraph::new("No temperature or fan sensors found")
+                .block(sensors_block)
+                .wrap(Wrap { trim: true });
+            frame.render_widget(paragraph, area);
+        } else {
+            frame.render_widget(sensor_table(&self.sensors).block(sensors_block), area);
+        }
+    }
+
+    fn render_listening_ports(&self, frame: &mut Frame, area: Rect) {
+        let ports = listening_ports(&self.connections);
+        let public = ports
+            .iter()
+            .filter(|port| port.scope == BindScope::AllInterfaces)
+            .count();
+        let title = Line::from(vec![
+            Span::raw(format!("Listening Ports ({}, ", ports.len())),
+            Span::styled(
+                format!("{public} on all interfaces"),
+                Style::default().fg(if public > 0 { Color::Red } else { Color::Green }),
+            ),
+            Span::raw(") "),
+        ]);
+        let ports_block = Block::default().title(title).borders(Borders::ALL);
+        frame.render_widget(listening_ports_table(&ports).block(ports_block), area);
+    }
+
     fn render_welcome_popup(&self, frame: &mut Frame, area: Rect) {
         const POPUP_WIDTH: u16 = 35;
         const POPUP_HEIGHT: u16 = 5;
@@ -722,25 +1514,290 @@ impl App {
         frame.render_widget(popup_paragraph, popup_area);
     }
 
+    fn render_network_details(&self, frame: &mut Frame, area: Rect) {
+        let popup_area = area.inner(Margin {
+            vertical: 3,
+            horizontal: 4,
+        });
+
+        let details_block = Block::default()
+            .title("Network Interfaces")
+            .title_alignment(Alignment::Center)
+            .title_bottom(Line::from(vec![
+                Span::raw("Press 'd' to close ─── "),
+                Span::styled("red", Style::default().fg(Color::Red)),
+                Span::raw(" errors/drops increased in the last 30s"),
+            ]))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::LightBlue));
+
+        let table =
+            network_details_table(self.network_manager.interface_details()).block(details_block);
+
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(table, popup_area);
+    }
+
+    fn render_connections(&mut self, frame: &mut Frame, area: Rect) {
+        let popup_area = area.inner(Margin {
+            vertical: 3,
+            horizontal: 4,
+        });
+        let count = self.filtered_connections().len();
+        self.connection_selected = self.connection_selected.min(count.saturating_sub(1));
+        let connections = self.filtered_connections();
+
+        let title = if self.mode == Mode::ConnectionFilter {
+            format!("Connections | Filter: {}█", self.connection_filter)
+        } else if !self.connection_filter.is_empty() {
+            format!(
+                "Connections [filter: {}] ({} results)",
+                self.connection_filter,
+                connections.len()
+            )
+        } else {
+            format!("Connections ({})", connections.len())
+        };
+        let connections_block = Block::default()
+            .title(title)
+            .title_alignment(Alignment::Center)
+            .title_bottom(Line::from(vec![
+                Span::styled("f", Style::default().fg(Color::Yellow)),
+                Span::raw(" - filter (state:listen port:22 name) "),
+                Span::styled("Enter", Style::default().fg(Color::Yellow)),
+                Span::raw(" - go to process "),
+                Span::styled("s", Style::default().fg(Color::Yellow)),
+                Span::raw(" - close"),
+            ]))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::LightBlue));
+        let table =
+            scrolled_connection_table(&connections, self.connection_selected, popup_area.height)
+                .block(connections_block);
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(table, popup_area);
+    }
+
+    fn render_protocol_stats(&self, frame: &mut Frame, area: Rect) {
+        let popup_area = area.inner(Margin {
+            vertical: 3,
+            horizontal: 8,
+        });
+        let stats_block = Block::default()
+            .title("TCP/UDP Protocol Statistics")
+            .title_alignment(Alignment::Center)
+            .title_bottom(Line::from(vec![
+                Span::raw("Press 't' to close ─── "),
+                Span::styled("red", Style::default().fg(Color::Red)),
+                Span::raw(" counters increasing right now"),
+            ]))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::LightBlue));
+        let inner = stats_block.inner(popup_area);
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(stats_block, popup_area);
+
+        // Counters on top, history of the rates below
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(9), Constraint::Min(0)])
+            .split(inner);
+        frame.render_widget(protocol_stats_table(&self.protocol_history), chunks[0]);
+        let protocol_chart = ProtocolChart::new(&self.protocol_history);
+        frame.render_widget(protocol_chart.widget(), chunks[1]);
+    }
+
+    fn render_oom_events(&self, frame: &mut Frame, area: Rect) {
+        let popup_area = Rect::new(
+            (area.width.saturating_sub(90)) / 2,
+            (area.height.saturating_sub(16)) / 2,
+            90.min(area.width),
+            16.min(area.height),
+        );
+
+        let visible = popup_area.height.saturating_sub(2) as usize;
+        let events = self.oom_tracker.events();
+        let lines: Vec<Line> = if events.is_empty() {
+            vec![Line::from(
+                "No OOM kills detected since the dashboard was started",
+            )]
+        } else {
+            // newest first, only as many as fit into the popup
+            events
+                .iter()
+                .rev()
+                .take(visible)
+                .map(|event| Line::from(event.describe()))
+                .collect()
+        };
+
+        let oom_block = Block::default()
+            .title("OOM Kills")
+            .title_alignment(Alignment::Center)
+            .title_bottom("Press 'o' to close")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::Red));
+
+        let oom_paragraph = Paragraph::new(lines)
+            .block(oom_block)
+            .style(Style::default().fg(Color::White));
+
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(oom_paragraph, popup_area);
+    }
+
+    /// Shows the active alerts in the empty line between the top bar and the panels.
+    fn render_alert_banner(&self, frame: &mut Frame, area: Rect) {
+        let active = self.alerts.active();
+        if active.is_empty() {
+            return;
+        }
+        let banner_area = Rect::new(area.x + 1, area.y, area.width.saturating_sub(2), 1);
+        frame.render_widget(Paragraph::new(alert_banner(&active)), banner_area);
+    }
+
+    fn render_alert_history(&self, frame: &mut Frame, area: Rect) {
+        let popup_area = Rect::new(
+            (area.width.saturating_sub(90)) / 2,
+            (area.height.saturating_sub(16)) / 2,
+            90.min(area.width),
+            16.min(area.height),
+        );
+
+        let visible = popup_area.height.saturating_sub(2) as usize;
+        let messages = self
+            .actions
+            .as_ref()
+            .map(|actions| actions.messages())
+            .unwrap_or_default();
+        let lines = alert_history_lines(
+            self.alerts.history(),
+            self.alerts.rules(),
+            messages,
+            visible,
+        );
+
+        let alerts_block = Block::default()
+            .title("Alerts")
+            .title_alignment(Alignment::Center)
+            .title_bottom("Press 'A' to close")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::Yellow));
+
+        let alerts_paragraph = Paragraph::new(lines)
+            .block(alerts_block)
+            .style(Style::default().fg(Color::White));
+
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(alerts_paragraph, popup_area);
+    }
+
+    fn render_history(&self, frame: &mut Frame, area: Rect) {
+        let popup_area = area.inner(Margin {
+            vertical: 1,
+            horizontal: 2,
+        });
+        frame.render_widget(Clear, popup_area);
+
+        let history = match &self.history {
+            Ok(history) => history,
+            Err(reason) => {
+                let block = Block::default()
+                    .title("History")
+                    .title_alignment(Alignment::Center)
+                    .title_bottom("Press 'H' to close")
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(Color::LightBlue));
+                let paragraph = Paragraph::new(reason.as_str())
+                    .block(block)
+                    .wrap(Wrap { trim: true });
+                frame.render_widget(paragraph, popup_area);
+                return;
+            }
+        };
+
+        let range = history.range();
+        let tier = history.tier();
+        let mut title = format!("History - last {}", format_retention(range));
+        if let Some(tier) = tier {
+            title.push_str(&format!(" ({tier})"));
+        }
+        if !history.store.is_writable() {
+            title.push_str(" [written by another process]");
+        }
+        let mut block = Block::default()
+            .title(title)
+            .title_alignment(Alignment::Center)
+            .title_bottom("Press 'r' to zoom, 'H' to close")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::LightBlue));
+        if let Some(error) = &history.error {
+            block = block.title_bottom(
+                Line::from(Span::styled(
+                    error.as_str(),
+                    Style::default().fg(Color::Red),
+                ))
+                .right_aligned(),
+            );
+        }
+        let inner = block.inner(popup_area);
+        frame.render_widget(block, popup_area);
+
+        let charts = HistoryCharts::new(
+            &history.points,
+            range,
+            tier.map(|tier| tier.resolution).unwrap_or_default(),
+            history.read_at,
+        );
+        let rows =
+            Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)]).split(inner);
+        let top = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(rows[0]);
+        let bottom = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(rows[1]);
+        frame.render_widget(charts.cpu_chart(), top[0]);
+        frame.render_widget(charts.memory_chart(), top[1]);
+        frame.render_widget(charts.disk_chart(), bottom[0]);
+        frame.render_widget(charts.network_chart(), bottom[1]);
+    }
+
     fn render_manual(&self, frame: &mut Frame, area: Rect) {
         let manual_area = Rect::new(
             (area.width.saturating_sub(60)) / 2,
-            (area.height.saturating_sub(20)) / 2,
+            (area.height.saturating_sub(26)) / 2,
             60,
-            20,
+            26.min(area.height),
         );
 
-        let manual_description: [&str; 11] = [
+        let manual_description: [&str; 22] = [
             "Press 'i' to switch network interface\n",
+            "Press 'a' to show all network interfaces in one chart\n",
+            "Press 'v' to hide/show loopback and virtual interfaces\n",
+            "Press 'r' to change the time window of the charts\n",
+            "Press 'd' to show network interface details\n",
+            "Press 't' to show TCP/UDP protocol statistics\n",
+            "Press 'o' to show detected OOM kills\n",
+            "Press 'A' to show the alert history\n",
+            "Press 'H' to show the long-term history, 'r' to zoom\n",
+            "Press 's' to show TCP/UDP connections\n",
             "Press 'c' to sort by CPU usage\n",
             "Press 'm' to sort by Memory usage\n",
             "Press 'p' to sort by PID\n",
             "Press 'n' to sort by Name\n",
+            "Press 'b' to sort by estimated network bandwidth\n",
             "Press '/' to search/filter processes\n",
             "Press 'M' to kill a process by PID\n",
             "Press 'Tab' to switch between CPU and Processes view\n",
             "Use Up/Down arrows to scroll through CPU or Processes\n",
             "Use Left/Right arrows to adjust fetch interval\n",
+            "Replay: Space pause, +/- speed, Left/Right/Home/End seek\n",
             "Press 'q' to quit the application\n",
         ];
 
@@ -767,19 +1824,3 @@ impl App {
         frame.render_widget(manual_paragraph, manual_area);
     }
 }
-
-fn system_uptime() -> String {
-    let uptime = System::uptime();
-    if uptime < 60 {
-        format!("Uptime: {uptime} seconds ")
-    } else if uptime < 3600 {
-        let minutes = uptime / 60;
-        format!("Uptime: {minutes} minutes ")
-    } else if uptime < 86400 {
-        let hours = uptime / 3600;
-        format!("Uptime: {hours} hours ")
-    } else {
-        let days = uptime / 86400;
-        format!("Uptime: {days} days ")
-    }
-}
@@ -3,8 +3,9 @@ use ratatui::layout::{Constraint, Direction, Layout, Rect};
 /// This function is responsible for creating the layout of the terminal UI.  
 /// It divides the terminal into a top section for the Gauge Bar and a bottom section that is  
 /// further divided into left and right parts.  
-/// The left part contains sections for CPU, Network, and Network Diagram,  
-/// while the right part contains sections for Memory, Processes, and System Info.  
+/// The left part contains sections for CPU, Network, Network Diagram and Memory History,  
+/// while the right part contains sections for Memory, Processes, System Info, Sensors
+/// and Listening Ports.  
 /// It returns a vector of Rects representing the layout of the terminal.
 pub fn terminal_layout(area: Rect) -> Vec<Rect> {
     // Main Layout for the terminal
@@ -35,15 +36,17 @@ pub fn terminal_layout(area: Rect) -> Vec<Rect> {
         )
         .split(main_chunks[1]);
 
-    // Divide the left side into three areas
-    // The first area is for CPU, the second for Network, and the third for Network
+    // Divide the left side into four areas
+    // The first area is for CPU, the second for Network, the third for the Network diagram
+    // and the fourth for the Memory history
     let left_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints(
             [
                 Constraint::Percentage(30), // CPU area
                 Constraint::Percentage(20), // Network area
-                Constraint::Percentage(50), // Network diagram
+                Constraint::Percentage(25), // Network diagram
+                Constraint::Percentage(25), // Memory history
             ]
             .as_ref(),
         )
@@ -52,7 +55,14 @@ pub fn terminal_layout(area: Rect) -> Vec<Rect> {
     // Divide the right side into two areas
     let right_chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(30), Constraint::Percentage(70)].as_ref())
+        .constraints(
+            [
+                Constraint::Percentage(40), // Memory and System Info
+                Constraint::Percentage(40), // Processes
+                Constraint::Percentage(20), // Listening ports
+            ]
+            .as_ref(),
+        )
         .split(lower_chunks[1]);
 
     // Divide the right side further into two areas
@@ -67,6 +77,18 @@ pub fn terminal_layout(area: Rect) -> Vec<Rect> {
         )
         .split(right_chunks[0]);
 
+    // Divide the System Info area into host information and sensors
+    let info_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(6), // Host info area
+                Constraint::Min(0),    // Sensors area
+            ]
+            .as_ref(),
+        )
+        .split(right_divided_chunks[1]);
+
     vec![
         main_chunks[0],          // Gauge Bar
         left_chunks[0],          // CPU area
@@ -74,6 +96,9 @@ pub fn terminal_layout(area: Rect) -> Vec<Rect> {
         right_divided_chunks[0], // Memory area
         right_chunks[1],         // Prozesse area
         left_chunks[2],          // Network Diagram
-        right_divided_chunks[1], // System Info area
+        info_chunks[0],          // System Info area
+        left_chunks[3],          // Memory History
+        info_chunks[1],          // Sensors area
+        right_chunks[2],         // Listening ports area
     ]
 }
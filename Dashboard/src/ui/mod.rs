@@ -2,3 +2,4 @@
 //! It handles the terminal layout, user input, and rendering of various system information components.
 pub mod app;
 pub mod layout;
+pub mod widgets;
@@ -0,0 +1,96 @@
+//! Renders the alert banner and the alert history.
+use crate::backend::alerts::{ActiveAlert, AlertEvent, AlertEventKind, AlertRule, Severity};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+
+/// Returns the color of an alert: red for critical alerts, yellow for warnings.
+fn severity_color(severity: Severity) -> Color {
+    match severity {
+        Severity::Warning => Color::Yellow,
+        Severity::Critical => Color::Red,
+    }
+}
+
+/// Returns the banner line listing the active alerts, most severe first.
+pub fn alert_banner(active: &[ActiveAlert]) -> Line<'static> {
+    let mut alerts: Vec<&ActiveAlert> = active.iter().collect();
+    alerts.sort_by_key(|alert| std::cmp::Reverse(alert.severity == Severity::Critical));
+
+    let mut spans = vec![Span::styled(
+        format!(
+            "⚠ {} alert{}: ",
+            alerts.len(),
+            if alerts.len() == 1 { "" } else { "s" }
+        ),
+        Style::default().add_modifier(Modifier::BOLD),
+    )];
+    for (i, alert) in alerts.iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::raw(" | "));
+        }
+        spans.push(Span::styled(
+            alert.to_string(),
+            Style::default()
+                .fg(severity_color(alert.severity))
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+    Line::from(spans)
+}
+
+/// Returns the lines of the alert history popup, newest first and at most `limit` lines.
+/// Without events, the configured rules are listed instead.
+/// The latest failed or suppressed action runs are shown below the events.
+pub fn alert_history_lines(
+    events: &[AlertEvent],
+    rules: &[AlertRule],
+    action_messages: &[String],
+    limit: usize,
+) -> Vec<Line<'static>> {
+    // At most a third of the lines for the action messages
+    let message_lines: Vec<Line<'static>> = action_messages
+        .iter()
+        .rev()
+        .take(limit / 3)
+        .map(|message| {
+            Line::from(Span::styled(
+                message.clone(),
+                Style::default().fg(Color::DarkGray),
+            ))
+        })
+        .collect();
+    let mut lines = event_lines(events, rules, limit - message_lines.len());
+    lines.extend(message_lines);
+    lines
+}
+
+fn event_lines(events: &[AlertEvent], rules: &[AlertRule], limit: usize) -> Vec<Line<'static>> {
+    if rules.is_empty() {
+        return vec![
+            Line::from("No alert rules configured."),
+            Line::from("Add [[alerts]] entries to ~/.config/linux_dashboard/config.toml"),
+        ];
+    }
+    if events.is_empty() {
+        let mut lines = vec![Line::from("No alerts fired yet. Rules:")];
+        lines.extend(
+            rules
+                .iter()
+                .take(limit.saturating_sub(1))
+                .map(|rule| Line::from(format!("  {}: {}", rule.name, rule.expression))),
+        );
+        return lines;
+    }
+    events
+        .iter()
+        .rev()
+        .take(limit)
+        .map(|event| {
+            let color = match event.kind {
+                AlertEventKind::Fired => severity_color(event.severity),
+                AlertEventKind::Resolved => Color::Green,
+            };
+            Line::from(Span::styled(event.describe(), Style::default().fg(color)))
+        })
+        .collect()
+}
@@ -0,0 +1,125 @@
+//! Renders the connections table and the listening ports table.
+use crate::backend::connections::{BindScope, Connection, ListeningPort};
+use ratatui::prelude::Constraint;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Cell, Row, Table};
+
+/// Returns a table with one row per socket: protocol, local and remote address, state
+/// and the owning process. The row at `selected` is highlighted.
+pub fn connection_table(connections: &[&Connection], selected: Option<usize>) -> Table<'static> {
+    let header = Row::new(vec![
+        Cell::from("Proto"),
+        Cell::from("Local Address"),
+        Cell::from("Remote Address"),
+        Cell::from("State"),
+        Cell::from("PID"),
+        Cell::from("Process"),
+    ])
+    .style(Style::default().fg(Color::Yellow));
+
+    let mut rows = vec![header];
+    rows.extend(connections.iter().enumerate().map(|(i, connection)| {
+        let state_color = match connection.state.as_str() {
+            "LISTEN" | "UNCONN" => Color::Cyan,
+            "ESTABLISHED" => Color::Green,
+            "TIME_WAIT" | "CLOSE_WAIT" | "FIN_WAIT1" | "FIN_WAIT2" | "LAST_ACK" => Color::Yellow,
+            _ => Color::White,
+        };
+        let row = Row::new(vec![
+            Cell::from(connection.protocol.as_str()),
+            Cell::from(connection.local.to_string()),
+            Cell::from(connection.remote.to_string()),
+            Cell::from(connection.state.clone()).style(Style::default().fg(state_color)),
+            Cell::from(
+                connection
+                    .pid
+                    .map(|pid| pid.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+            ),
+            Cell::from(
+                connection
+                    .process
+                    .clone()
+                    .unwrap_or_else(|| "-".to_string()),
+            ),
+        ]);
+        if Some(i) == selected {
+            row.style(Style::default().add_modifier(Modifier::REVERSED))
+        } else {
+            row
+        }
+    }));
+
+    let widths = [
+        Constraint::Length(5),
+        Constraint::Min(22),
+        Constraint::Min(22),
+        Constraint::Length(12),
+        Constraint::Length(8),
+        Constraint::Length(16),
+    ];
+    Table::new(rows, widths)
+        .column_spacing(1)
+        .style(Style::default().fg(Color::White))
+}
+
+/// Like `connection_table`, but only with the rows that fit into a table of `height` lines
+/// (borders and header included), scrolled so that the row at `selected` is visible.
+pub fn scrolled_connection_table(
+    connections: &[&Connection],
+    selected: usize,
+    height: u16,
+) -> Table<'static> {
+    // -3 for borders and header, the selected row is kept even if nothing fits
+    let visible = (height.saturating_sub(3) as usize).max(1);
+    let offset = (selected + 1).saturating_sub(visible);
+    let end = (offset + visible).min(connections.len());
+    connection_table(
+        &connections[offset.min(end)..end],
+        Some(selected.saturating_sub(offset)),
+    )
+}
+
+fn scope_color(scope: BindScope) -> Color {
+    match scope {
+        BindScope::AllInterfaces => Color::Red,
+        BindScope::Address => Color::Yellow,
+        BindScope::Localhost => Color::Green,
+    }
+}
+
+/// Returns a compact table of listening ports: protocol, bind address, scope and process.
+/// Ports reachable from the network are highlighted in red.
+pub fn listening_ports_table(ports: &[ListeningPort]) -> Table<'static> {
+    let header = Row::new(vec![
+        Cell::from("Proto"),
+        Cell::from("Bind Address"),
+        Cell::from("Scope"),
+        Cell::from("Process"),
+    ])
+    .style(Style::default().fg(Color::Yellow));
+
+    let mut rows = vec![header];
+    rows.extend(ports.iter().map(|port| {
+        let process = match (&port.process, port.pid) {
+            (Some(name), Some(pid)) => format!("{name} ({pid})"),
+            _ => "-".to_string(),
+        };
+        Row::new(vec![
+            Cell::from(port.protocol.as_str()),
+            Cell::from(port.address.to_string()),
+            Cell::from(port.scope.as_str()).style(Style::default().fg(scope_color(port.scope))),
+            Cell::from(process),
+        ])
+    }));
+
+    let widths = [
+        Constraint::Length(5),
+        Constraint::Min(22),
+        Constraint::Length(14),
+        Constraint::Min(16),
+    ];
+    Table::new(rows, widths)
+        .column_spacing(1)
+        .style(Style::default().fg(Color::White))
+}
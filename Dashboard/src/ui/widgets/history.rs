@@ -0,0 +1,208 @@
+//! Renders the charts of the on-disk history: CPU, memory, disk space and network.
+use crate::backend::converter::byte_to_gib;
+use crate::backend::history::{format_retention, local_time};
+use crate::backend::network::chart_unit;
+use crate::store::HistoryPoint;
+use ratatui::style::Color;
+use ratatui::{
+    style::{Style, Stylize},
+    symbols,
+    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType, LegendPosition},
+};
+use std::time::Duration;
+
+type DataPoint = (f64, f64); // Tuple for time and value
+
+/// A line of a chart, split into segments at the gaps of the history.
+struct Series {
+    name: &'static str,
+    color: Color,
+    segments: Vec<Vec<DataPoint>>,
+}
+
+/// The charts of the history of the last `range`.
+/// The data is converted once when the charts are built, the widgets borrow it for rendering.
+pub struct HistoryCharts {
+    cpu: Vec<Series>,
+    memory: Vec<Series>,
+    memory_max: f64,
+    disk: Vec<Series>,
+    network: Vec<Series>,
+    network_unit: &'static str,
+    network_max: f64,
+    x_title: String,
+    x_bounds: [f64; 2],
+    x_labels: Vec<String>,
+}
+
+// Splits the values into segments wherever two points are more than `max_gap` seconds apart,
+// so the times nothing was collected stay empty instead of being bridged by a line
+fn segments(
+    points: &[HistoryPoint],
+    max_gap: f64,
+    value: impl Fn(&HistoryPoint) -> f64,
+) -> Vec<Vec<DataPoint>> {
+    let mut segments: Vec<Vec<DataPoint>> = Vec::new();
+    let mut last_time = f64::NEG_INFINITY;
+    for point in points {
+        if point.timestamp - last_time > max_gap || segments.is_empty() {
+            segments.push(Vec::new());
+        }
+        if let Some(segment) = segments.last_mut() {
+            segment.push((point.timestamp, value(point)));
+        }
+        last_time = point.timestamp;
+    }
+    segments
+}
+
+impl HistoryCharts {
+    /// Builds the charts of `points`, stored at the given resolution, for the `range`
+    /// up to `now` (seconds since the Unix epoch).
+    pub fn new(points: &[HistoryPoint], range: Duration, resolution: Duration, now: f64) -> Self {
+        // A few missing buckets are bridged, e.g. after a slow refresh
+        let max_gap = resolution.as_secs_f64() * 3.0;
+        let series = |name, color, value: fn(&HistoryPoint) -> f64| Series {
+            name,
+            color,
+            segments: segments(points, max_gap, value),
+        };
+
+        let memory_max = points
+            .iter()
+            .map(|point| {
+                point
+                    .memory_total
+                    .max(point.swap_used)
+                    .max(point.memory_used)
+            })
+            .fold(0.0, f64::max);
+        let network_peak = points
+            .iter()
+            .map(|point| point.network_rx.max(point.network_tx))
+            .fold(0.0, f64::max);
+        let (network_unit, scale) = chart_unit(network_peak);
+
+        let start = now - range.as_secs_f64();
+        let time_format = if range > Duration::from_secs(24 * 60 * 60) {
+            "%a %H:%M"
+        } else {
+            "%H:%M"
+        };
+
+        Self {
+            cpu: vec![series("Total", Color::Cyan, |point| point.cpu)],
+            memory: vec![
+                series("Used", Color::Red, |point| {
+                    byte_to_gib(point.memory_used as u64)
+                }),
+                series("Available", Color::Green, |point| {
+                    byte_to_gib(point.memory_available as u64)
+                }),
+                series("Swap", Color::Magenta, |point| {
+                    byte_to_gib(point.swap_used as u64)
+                }),
+            ],
+            memory_max: byte_to_gib(memory_max as u64),
+            disk: vec![series(
+                "Used",
+                Color::Yellow,
+                HistoryPoint::disk_used_percent,
+            )],
+            network: vec![
+                Series {
+                    name: "▼ Download",
+                    color: Color::Green,
+                    segments: segments(points, max_gap, |point| point.network_rx / scale),
+                },
+                Series {
+                    name: "▲ Upload",
+                    color: Color::Red,
+                    // Upload is drawn below the axis, as in the network chart
+                    segments: segments(points, max_gap, |point| -point.network_tx / scale),
+                },
+            ],
+            network_unit,
+            network_max: network_peak / scale,
+            x_title: format!("Time ({})", format_retention(range)),
+            x_bounds: [start, now],
+            x_labels: [start, (start + now) / 2.0, now]
+                .iter()
+                .map(|t| local_time(*t).format(time_format).to_string())
+                .collect(),
+        }
+    }
+
+    fn chart<'a>(&'a self, title: &'a str, series: &'a [Series], y_axis: Axis<'a>) -> Chart<'a> {
+        // Only the first segment carries the name, so every series appears once in the legend
+        let mut datasets = Vec::new();
+        for entry in series {
+            for (i, segment) in entry.segments.iter().enumerate() {
+                let dataset = Dataset::default()
+                    .marker(symbols::Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .style(Style::default().fg(entry.color))
+                    .data(segment);
+                datasets.push(if i == 0 {
+                    dataset.name(entry.name)
+                } else {
+                    dataset
+                });
+            }
+        }
+
+        let x_axis = Axis::default()
+            .title(self.x_title.clone())
+            .style(Style::default().white())
+            .bounds(self.x_bounds)
+            .labels(self.x_labels.clone());
+
+        Chart::new(datasets)
+            .block(Block::default().title(title).borders(Borders::ALL))
+            .x_axis(x_axis)
+            .y_axis(y_axis.style(Style::default().white()))
+            .legend_position(Some(LegendPosition::TopLeft))
+    }
+
+    /// Creates the chart of the total CPU usage in percent.
+    pub fn cpu_chart(&self) -> Chart<'_> {
+        let y_axis = Axis::default()
+            .title("%")
+            .bounds([0.0, 100.0])
+            .labels(vec!["0", "50", "100"]);
+        self.chart("CPU", &self.cpu, y_axis)
+    }
+
+    /// Creates the chart of memory used, memory available and swap used in GB.
+    pub fn memory_chart(&self) -> Chart<'_> {
+        let max = self.memory_max.ceil().max(1.0);
+        let y_axis = Axis::default()
+            .title("GB")
+            .bounds([0.0, max])
+            .labels(vec![String::from("0"), format!("{max:.0}")]);
+        self.chart("Memory", &self.memory, y_axis)
+    }
+
+    /// Creates the chart of the used disk space of all disks in percent.
+    pub fn disk_chart(&self) -> Chart<'_> {
+        let y_axis = Axis::default()
+            .title("%")
+            .bounds([0.0, 100.0])
+            .labels(vec!["0", "50", "100"]);
+        self.chart("Disk Space (all disks)", &self.disk, y_axis)
+    }
+
+    /// Creates the chart of the network traffic, download above and upload below the axis.
+    pub fn network_chart(&self) -> Chart<'_> {
+        let max = self.network_max.ceil().max(1.0);
+        let y_axis = Axis::default()
+            .title(self.network_unit)
+            .bounds([-max, max])
+            .labels(vec![
+                format!("-{max:.0}"),
+                String::from("0"),
+                format!("{max:.0}"),
+            ]);
+        self.chart("Network (physical interfaces)", &self.network, y_axis)
+    }
+}
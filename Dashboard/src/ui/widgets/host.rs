@@ -0,0 +1,45 @@
+//! Renders the host information table.
+use crate::backend::host::HostInfo;
+use ratatui::prelude::Constraint;
+use ratatui::style::{Color, Style};
+use ratatui::widgets::{Cell, Row, Table};
+
+/// Returns a table with:  
+/// -> system name  
+/// -> kernel version  
+/// -> OS version  
+/// -> host name  
+/// of the Linux system.  
+/// The table has two columns: one for the label and one for the value.  
+///
+/// # Example
+/// ```
+/// use linux_dashboard::backend::host::HostInfo;
+/// use linux_dashboard::ui::widgets::host::host_info_table;
+/// let table = host_info_table(&HostInfo::new());
+/// ```
+///
+pub fn host_info_table(host: &HostInfo) -> Table<'static> {
+    let rows = vec![
+        Row::new(vec![
+            Cell::from("System Name"),
+            Cell::from(host.system_name.clone()),
+        ]),
+        Row::new(vec![
+            Cell::from("Kernel Version"),
+            Cell::from(host.kernel_version.clone()),
+        ]),
+        Row::new(vec![
+            Cell::from("OS Version"),
+            Cell::from(host.os_version.clone()),
+        ]),
+        Row::new(vec![
+            Cell::from("Host Name"),
+            Cell::from(host.host_name.clone()),
+        ]),
+    ];
+    let widths = [Constraint::Length(20), Constraint::Length(30)];
+    Table::new(rows, widths)
+        .column_spacing(1)
+        .style(Style::default().fg(Color::White))
+}
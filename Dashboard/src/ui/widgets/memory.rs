@@ -0,0 +1,126 @@
+//! Renders the RAM usage bar, its legend and the memory breakdown table.
+use crate::backend::converter::byte_to_gib;
+use crate::backend::memory::{MemInfo, MemorySegmentKind, percent};
+use ratatui::prelude::Constraint;
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Cell, Row, Table};
+
+fn segment_color(kind: MemorySegmentKind) -> Color {
+    match kind {
+        MemorySegmentKind::Used => Color::Red,
+        MemorySegmentKind::Buffers => Color::Blue,
+        MemorySegmentKind::Cache => Color::Yellow,
+        MemorySegmentKind::Free => Color::Green,
+    }
+}
+
+/// Builds a stacked bar of the RAM categories (used, buffers, cache, free) with the given width.
+/// Each category gets a number of cells proportional to its share of the total memory.
+pub fn ram_usage_bar(mem: &MemInfo, width: u16) -> Line<'static> {
+    let width = width as usize;
+    let segments = mem.segments();
+    let mut spans = Vec::new();
+    let mut cells_used = 0;
+
+    for (i, segment) in segments.iter().enumerate() {
+        let cells = if i == segments.len() - 1 {
+            // the last segment fills the remaining cells to avoid rounding gaps
+            width.saturating_sub(cells_used)
+        } else {
+            ((mem.percent_of_total(segment.bytes) / 100.0) * width as f64).round() as usize
+        };
+        let cells = cells.min(width.saturating_sub(cells_used));
+        cells_used += cells;
+        spans.push(Span::styled(
+            "█".repeat(cells),
+            Style::default().fg(segment_color(segment.kind)),
+        ));
+    }
+
+    Line::from(spans)
+}
+
+/// Builds the legend for the stacked RAM bar, each label in the color of its segment.
+pub fn ram_usage_legend(mem: &MemInfo) -> Line<'static> {
+    let mut spans = Vec::new();
+    for segment in mem.segments() {
+        spans.push(Span::styled(
+            "■ ",
+            Style::default().fg(segment_color(segment.kind)),
+        ));
+        spans.push(Span::raw(format!(
+            "{} {:.0}%  ",
+            segment.kind.label(),
+            mem.percent_of_total(segment.bytes)
+        )));
+    }
+    Line::from(spans)
+}
+
+fn memory_row(label: &str, bytes: u64, percent_of: Option<u64>) -> Row<'static> {
+    Row::new(vec![
+        Cell::from(label.to_string()),
+        Cell::from(format!("{:.2} GB", byte_to_gib(bytes))),
+        Cell::from(
+            percent_of
+                .map(|whole| format!("{:>5.1}%", percent(bytes, whole)))
+                .unwrap_or_default(),
+        ),
+    ])
+}
+
+/// Returns:
+/// -> Total, Used, Available and Free Memory...
+/// -> Buffers, Cache and Shared Memory...
+/// -> Reclaimable and unreclaimable Slab...
+/// -> Dirty and Writeback pages...
+/// -> Committed memory and HugePages...
+/// -> Total and Used Swap Memory...
+/// information in a table format, with the share of the total in percent.
+/// # Example
+/// ```
+/// use linux_dashboard::backend::memory::MemInfo;
+/// use linux_dashboard::ui::widgets::memory::ram_info_table;
+/// use sysinfo::System;
+/// let sys = System::new_all();
+/// let table = ram_info_table(&MemInfo::collect(&sys));
+/// ```
+///
+pub fn ram_info_table(mem: &MemInfo) -> Table<'static> {
+    let total = Some(mem.total);
+    let hugepages_used = mem.hugepages_total.saturating_sub(mem.hugepages_free);
+
+    let rows = vec![
+        memory_row("Total", mem.total, None),
+        memory_row("Used", mem.used(), total),
+        memory_row("Available", mem.available, total),
+        memory_row("Free", mem.free, total),
+        memory_row("Buffers", mem.buffers, total),
+        memory_row("Cached", mem.cached, total),
+        memory_row("Shared", mem.shared, total),
+        memory_row("Slab (rec.)", mem.slab_reclaimable, total),
+        memory_row("Slab (unrec.)", mem.slab_unreclaimable, total),
+        memory_row("Dirty", mem.dirty, total),
+        memory_row("Writeback", mem.writeback, total),
+        memory_row("Committed", mem.committed_as, Some(mem.commit_limit)),
+        Row::new(vec![
+            Cell::from("HugePages"),
+            Cell::from(format!("{}/{}", hugepages_used, mem.hugepages_total)),
+            Cell::from(format!(
+                "{:.2} GB",
+                byte_to_gib(mem.hugepages_total * mem.hugepage_size)
+            )),
+        ]),
+        memory_row("Swap Total", mem.swap_total, None),
+        memory_row("Swap Used", mem.swap_used(), Some(mem.swap_total)),
+    ];
+    let widths = [
+        Constraint::Length(13),
+        Constraint::Length(10),
+        Constraint::Length(10),
+    ];
+    Table::new(rows, widths)
+        .column_spacing(1)
+        .style(Style::default().fg(Color::White))
+}
@@ -0,0 +1,176 @@
+//! Renders the memory history and swap activity charts.
+use crate::backend::history::format_retention;
+use crate::backend::memory_history::MemoryHistory;
+use crate::ui::widgets::network::format_timestamp;
+use ratatui::style::Color;
+use ratatui::text::{Line, Span};
+use ratatui::{
+    style::{Style, Stylize},
+    symbols,
+    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType},
+};
+use std::time::Duration;
+
+type DataPoint = (f64, f64); // Tuple for time and value
+
+/// The chart of memory used, memory available and swap used in GB over the retention
+/// window. The data is copied once when the chart is built, the widget borrows it.
+pub struct MemoryChart {
+    used: Vec<DataPoint>,
+    available: Vec<DataPoint>,
+    swap_used: Vec<DataPoint>,
+    max_value: f64,
+    x_title: String,
+    x_bounds: [f64; 2],
+    x_labels: Vec<String>,
+}
+
+impl MemoryChart {
+    pub fn new(history: &MemoryHistory) -> Self {
+        let used = history.used_history();
+        let available = history.available_history();
+        let swap_used = history.swap_used_history();
+        let max_value = available
+            .iter()
+            .chain(used.iter())
+            .chain(swap_used.iter())
+            .map(|(_, value)| *value)
+            .fold(history.memory_total(), f64::max);
+
+        // Like the network chart, the x-axis covers the retention window up to the last sample
+        let retention = history.retention();
+        let end = history.last_update();
+        let start = end - retention.as_secs_f64();
+        let time_format = if retention >= Duration::from_secs(3600) {
+            "%H:%M"
+        } else {
+            "%H:%M:%S"
+        };
+        Self {
+            used,
+            available,
+            swap_used,
+            max_value,
+            x_title: format!("Time ({})", format_retention(retention)),
+            x_bounds: [start, end],
+            x_labels: [start, end]
+                .iter()
+                .map(|t| format_timestamp(*t, time_format))
+                .collect(),
+        }
+    }
+
+    pub fn widget(&self) -> Chart<'_> {
+        let datasets = vec![
+            Dataset::default()
+                .name("Used")
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().red())
+                .data(&self.used),
+            Dataset::default()
+                .name("Available")
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().green())
+                .data(&self.available),
+            Dataset::default()
+                .name("Swap")
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().magenta())
+                .data(&self.swap_used),
+        ];
+
+        let x_axis = Axis::default()
+            .title(self.x_title.clone())
+            .style(Style::default().white())
+            .bounds(self.x_bounds)
+            .labels(self.x_labels.clone());
+
+        let y_axis = Axis::default()
+            .title("GB")
+            .style(Style::default().white())
+            .bounds([0.0, self.max_value.ceil().max(1.0)])
+            .labels(vec![
+                String::from("0"),
+                format!("{:.0}", self.max_value.ceil()),
+            ]);
+
+        Chart::new(datasets)
+            .block(
+                Block::default()
+                    .title("Memory History")
+                    .borders(Borders::ALL),
+            )
+            .x_axis(x_axis)
+            .y_axis(y_axis)
+    }
+}
+
+/// Creates a chart of the swap-in (up) and swap-out (down) rates in pages per second
+/// with the major page fault rate as an additional line.
+/// The block title shows the latest rates including minor page faults.
+pub fn swap_activity_chart(history: &MemoryHistory) -> Chart<'_> {
+    let max_value = history
+        .swap_in_history()
+        .iter()
+        .chain(history.swap_out_history().iter())
+        .chain(history.major_faults_history().iter())
+        .map(|(_, value)| value.abs())
+        .fold(1.0, f64::max);
+
+    let datasets = vec![
+        Dataset::default()
+            .name("▲ Swap in")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().green())
+            .data(history.swap_in_history()),
+        Dataset::default()
+            .name("▼ Swap out")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().red())
+            .data(history.swap_out_history()),
+        Dataset::default()
+            .name("Major faults")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().yellow())
+            .data(history.major_faults_history()),
+    ];
+
+    let x_axis = Axis::default()
+        .title("Time")
+        .style(Style::default().white())
+        .bounds(history.rate_time_bounds())
+        .labels(Vec::<String>::new());
+
+    let y_axis = Axis::default()
+        .title("pages/s")
+        .style(Style::default().white())
+        .bounds([-max_value.ceil(), max_value.ceil()])
+        .labels(Vec::<String>::new());
+
+    let rates = history.latest_rates();
+    Chart::new(datasets)
+        .block(
+            Block::default()
+                .title("Swap Activity")
+                .title_bottom(Line::from(vec![
+                    Span::styled("in ", Style::default().fg(Color::Green)),
+                    Span::raw(format!("{:.0}/s ", rates.swap_in)),
+                    Span::styled("out ", Style::default().fg(Color::Red)),
+                    Span::raw(format!("{:.0}/s ", rates.swap_out)),
+                    Span::styled("faults ", Style::default().fg(Color::Yellow)),
+                    Span::raw(format!(
+                        "{:.0}/s ({:.0} major)",
+                        rates.page_faults, rates.major_faults
+                    )),
+                ]))
+                .borders(Borders::ALL),
+        )
+        .x_axis(x_axis)
+        .y_axis(y_axis)
+}
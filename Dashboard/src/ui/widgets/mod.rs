@@ -0,0 +1,14 @@
+//! This module turns the data collected by the backend into ratatui widgets.
+//! Every submodule renders the data of the backend module with the same name,
+//! so the backend stays usable without a terminal UI.
+pub mod alerts;
+pub mod connections;
+pub mod history;
+pub mod host;
+pub mod memory;
+pub mod memory_history;
+pub mod netstat;
+pub mod network;
+pub mod power;
+pub mod processes;
+pub mod sensors;
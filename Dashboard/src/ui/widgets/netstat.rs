@@ -0,0 +1,177 @@
+//! Renders the protocol statistics table and the protocol history chart.
+use crate::backend::netstat::{ProtocolHistory, ProtocolRates};
+use ratatui::prelude::Constraint;
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use ratatui::{
+    style::Stylize,
+    symbols,
+    widgets::{Axis, Block, Borders, Cell, Chart, Dataset, GraphType, Row, Table},
+};
+
+/// Returns a table with the total counters since boot and the current rate of each.
+pub fn protocol_stats_table(history: &ProtocolHistory) -> Table<'static> {
+    let stats = history.latest_stats().unwrap_or_default();
+    let rates = history.latest_rates();
+    let header = Row::new(vec![
+        Cell::from("Counter"),
+        Cell::from("Since boot"),
+        Cell::from("Per second"),
+    ])
+    .style(Style::default().fg(Color::Yellow));
+
+    // Problem counters with a non-zero rate are highlighted,
+    // connection opens are normal activity and never highlighted.
+    let row = |label: &str, total: u64, rate: f64, problem: bool| {
+        let style = if problem && rate > 0.0 {
+            Style::default().fg(Color::Red)
+        } else {
+            Style::default()
+        };
+        Row::new(vec![
+            Cell::from(label.to_string()),
+            Cell::from(total.to_string()),
+            Cell::from(format!("{rate:.1}")).style(style),
+        ])
+    };
+    let rows = vec![
+        header,
+        row(
+            "TCP retransmitted segments",
+            stats.tcp_retrans_segs,
+            rates.retransmits,
+            true,
+        ),
+        row(
+            "TCP active opens",
+            stats.tcp_active_opens,
+            rates.active_opens,
+            false,
+        ),
+        row(
+            "TCP passive opens",
+            stats.tcp_passive_opens,
+            rates.passive_opens,
+            false,
+        ),
+        row(
+            "TCP resets (established + sent)",
+            stats.tcp_estab_resets + stats.tcp_out_rsts,
+            rates.resets,
+            true,
+        ),
+        row(
+            "TCP listen overflows",
+            stats.listen_overflows,
+            rates.listen_overflows,
+            true,
+        ),
+        row(
+            "UDP receive errors",
+            stats.udp_in_errors,
+            rates.udp_errors,
+            true,
+        ),
+        row(
+            "UDP buffer errors",
+            stats.udp_rcvbuf_errors + stats.udp_sndbuf_errors,
+            rates.buffer_errors,
+            true,
+        ),
+    ];
+
+    let widths = [
+        Constraint::Length(32),
+        Constraint::Length(14),
+        Constraint::Length(12),
+    ];
+    Table::new(rows, widths)
+        .column_spacing(1)
+        .style(Style::default().fg(Color::White))
+}
+
+/// The protocol history chart: the retransmission share in percent, the reset rate and
+/// the error rate (listen overflows, UDP receive and buffer errors).
+/// The points are copied out of the history when the chart is built,
+/// the widget borrows them for rendering.
+pub struct ProtocolChart {
+    retransmits: Vec<(f64, f64)>,
+    resets: Vec<(f64, f64)>,
+    errors: Vec<(f64, f64)>,
+    rates: ProtocolRates,
+}
+
+impl ProtocolChart {
+    pub fn new(history: &ProtocolHistory) -> Self {
+        Self {
+            retransmits: history.retransmit_history(),
+            resets: history.resets_history(),
+            errors: history.errors_history(),
+            rates: history.latest_rates(),
+        }
+    }
+
+    /// Creates the chart widget.
+    pub fn widget(&self) -> Chart<'_> {
+        let max_value = self
+            .retransmits
+            .iter()
+            .chain(self.resets.iter())
+            .chain(self.errors.iter())
+            .map(|(_, value)| *value)
+            .fold(1.0, f64::max);
+
+        let datasets = vec![
+            Dataset::default()
+                .name("Retransmits %")
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().yellow())
+                .data(&self.retransmits),
+            Dataset::default()
+                .name("Resets/s")
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().magenta())
+                .data(&self.resets),
+            Dataset::default()
+                .name("Errors/s")
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().red())
+                .data(&self.errors),
+        ];
+
+        let first = self.resets.first().map(|(t, _)| *t).unwrap_or(0.0);
+        let last = self.resets.last().map(|(t, _)| *t).unwrap_or(0.0);
+        let x_axis = Axis::default()
+            .title("Time")
+            .style(Style::default().white())
+            .bounds([first, last.max(first + 1.0)])
+            .labels(Vec::<String>::new());
+
+        let y_axis = Axis::default()
+            .style(Style::default().white())
+            .bounds([0.0, max_value.ceil()])
+            .labels(vec![String::from("0"), format!("{:.0}", max_value.ceil())]);
+
+        let rates = self.rates;
+        Chart::new(datasets)
+            .block(
+                Block::default()
+                    .title("Protocol History")
+                    .title_bottom(Line::from(vec![
+                        Span::styled("retransmits ", Style::default().fg(Color::Yellow)),
+                        Span::raw(format!(
+                            "{:.1}/s ({:.2}%) ",
+                            rates.retransmits, rates.retransmit_percent
+                        )),
+                        Span::styled("resets ", Style::default().fg(Color::Magenta)),
+                        Span::raw(format!("{:.1}/s", rates.resets)),
+                    ]))
+                    .borders(Borders::ALL),
+            )
+            .x_axis(x_axis)
+            .y_axis(y_axis)
+    }
+}
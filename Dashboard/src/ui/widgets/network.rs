@@ -0,0 +1,264 @@
+//! Renders the network traffic chart and the interface details table.
+use crate::backend::history::format_retention;
+use crate::backend::network::{ChartSeries, InterfaceDetails, NetworkManager, SeriesKind};
+use crate::backend::network_source::NetworkSource;
+use chrono::{Local, TimeZone};
+use ratatui::prelude::Constraint;
+use ratatui::style::Color;
+use ratatui::text::{Line, Span};
+use ratatui::{
+    style::{Style, Stylize},
+    symbols,
+    widgets::{Axis, Block, Borders, Cell, Chart, Dataset, GraphType, LegendPosition, Row, Table},
+};
+use std::time::Duration;
+
+/// Colors used for the interfaces in the all interfaces chart.
+const INTERFACE_COLORS: [Color; 8] = [
+    Color::Cyan,
+    Color::Green,
+    Color::Yellow,
+    Color::Magenta,
+    Color::LightBlue,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::Blue,
+];
+
+fn series_color(kind: SeriesKind) -> Color {
+    match kind {
+        SeriesKind::Download => Color::Green,
+        SeriesKind::Upload => Color::Red,
+        SeriesKind::Interface(i) => INTERFACE_COLORS[i % INTERFACE_COLORS.len()],
+        SeriesKind::Total => Color::White,
+        SeriesKind::Stored => Color::Gray,
+    }
+}
+
+// Formats a Unix timestamp as local time for the chart labels
+pub(crate) fn format_timestamp(timestamp: f64, format: &str) -> String {
+    Local
+        .timestamp_opt(timestamp.floor() as i64, 0)
+        .single()
+        .map(|time| time.format(format).to_string())
+        .unwrap_or_default()
+}
+
+/// The network traffic chart of a `NetworkManager`.
+/// The series are scaled to the chart unit once when the chart is built,
+/// the widget borrows them for rendering.
+pub struct NetworkChart {
+    series: Vec<ChartSeries>,
+    unit: &'static str,
+    max_value: f64,
+    title: String,
+    x_title: String,
+    x_bounds: [f64; 2],
+    x_labels: Vec<String>,
+}
+
+impl NetworkChart {
+    // In the single interface mode the chart plots the download and upload of the selected
+    // interface, in the all interfaces mode every interface gets its own color and the legend
+    // shows the interface names plus an aggregate total line.
+    // The unit is determined based on the maximum value to display it in a human-readable format.
+    pub fn new<S: NetworkSource>(manager: &NetworkManager<S>) -> Self {
+        let (unit, scale_factor) = manager.chart_scale();
+        let scale = |data: &[(f64, f64)]| -> Vec<(f64, f64)> {
+            data.iter().map(|(x, y)| (*x, y / scale_factor)).collect()
+        };
+        let series: Vec<ChartSeries> = manager
+            .chart_series()
+            .iter()
+            .map(|entry| ChartSeries {
+                name: entry.name.clone(),
+                kind: entry.kind,
+                download: scale(&entry.download),
+                upload: scale(&entry.upload),
+            })
+            .collect();
+        // value.abs() is used as upload values are negative
+        let max_value = series
+            .iter()
+            .flat_map(|entry| entry.download.iter().chain(entry.upload.iter()))
+            .map(|(_, value)| value.abs())
+            .fold(0.0, f64::max);
+
+        // The x-axis covers the whole retention window up to the last refresh
+        // and is labelled with wall clock times.
+        let retention = manager.retention();
+        let end = manager.last_update();
+        let start = end - retention.as_secs_f64();
+        let time_format = if retention >= Duration::from_secs(3600) {
+            "%H:%M"
+        } else {
+            "%H:%M:%S"
+        };
+
+        let title = if manager.is_showing_all_interfaces() {
+            format!(
+                "Network Traffic - All Interfaces (download above, upload below){}",
+                if manager.is_hiding_virtual() {
+                    " [virtual hidden]"
+                } else {
+                    ""
+                }
+            )
+        } else {
+            format!(
+                "Network Traffic - Interface: {}",
+                manager.get_selected_interface()
+            )
+        };
+
+        Self {
+            series,
+            unit,
+            max_value,
+            title,
+            x_title: format!("Time ({})", format_retention(retention)),
+            x_bounds: [start, end],
+            x_labels: [start, (start + end) / 2.0, end]
+                .iter()
+                .map(|t| format_timestamp(*t, time_format))
+                .collect(),
+        }
+    }
+
+    /// Creates the chart widget. Download is drawn above the x-axis, upload below it.
+    pub fn widget(&self) -> Chart<'_> {
+        // Only the download dataset carries the name, so every series appears once in the legend
+        let mut datasets = Vec::new();
+        for entry in &self.series {
+            let style = Style::default().fg(series_color(entry.kind));
+            let download = Dataset::default()
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(style)
+                .data(&entry.download);
+            let upload = Dataset::default()
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(style)
+                .data(&entry.upload);
+            if entry.download.is_empty() {
+                datasets.push(upload.name(entry.name.clone()));
+            } else {
+                datasets.push(download.name(entry.name.clone()));
+                datasets.push(upload);
+            }
+        }
+
+        let x_axis = Axis::default()
+            .title(self.x_title.clone())
+            .style(Style::default().white())
+            .bounds(self.x_bounds)
+            .labels(self.x_labels.clone());
+
+        let y_axis = Axis::default()
+            .title(self.unit)
+            .style(Style::default().white())
+            .bounds([-self.max_value.ceil(), self.max_value.ceil()])
+            .labels(Vec::<String>::new());
+
+        Chart::new(datasets)
+            .block(
+                Block::default()
+                    .title(self.title.clone())
+                    .title_bottom(Line::from(vec![
+                        Span::styled("i", Style::default().fg(Color::Yellow)),
+                        Span::raw(" - change interface "),
+                        Span::styled("a", Style::default().fg(Color::Yellow)),
+                        Span::raw(" - all interfaces "),
+                        Span::styled("v", Style::default().fg(Color::Yellow)),
+                        Span::raw(" - hide virtual "),
+                        Span::styled("r", Style::default().fg(Color::Yellow)),
+                        Span::raw(" - retention"),
+                    ]))
+                    .borders(Borders::ALL),
+            )
+            .x_axis(x_axis)
+            .y_axis(y_axis)
+            .legend_position(Some(LegendPosition::TopRight))
+    }
+}
+
+/// Returns a table with the details of every interface: state, link speed, MTU, MAC,
+/// IPv4/IPv6 addresses and cumulative packets, errors and drops.
+/// Error and drop counters that increased recently are highlighted in red.
+pub fn network_details_table(details: &[InterfaceDetails]) -> Table<'static> {
+    let header = Row::new(vec![
+        Cell::from("Interface"),
+        Cell::from("State"),
+        Cell::from("Speed"),
+        Cell::from("MTU"),
+        Cell::from("MAC"),
+        Cell::from("Addresses"),
+        Cell::from("Packets RX/TX"),
+        Cell::from("Errors RX/TX"),
+        Cell::from("Drops RX/TX"),
+    ])
+    .style(Style::default().fg(Color::Yellow));
+
+    let mut rows = vec![header];
+    rows.extend(details.iter().map(|interface| {
+        let state_color = match interface.link.operstate.as_str() {
+            "up" => Color::Green,
+            "down" => Color::Red,
+            _ => Color::White,
+        };
+        let fault_style = |rising: bool| {
+            if rising {
+                Style::default().fg(Color::Red).bold()
+            } else {
+                Style::default()
+            }
+        };
+        let addresses: Vec<String> = interface
+            .ipv4
+            .iter()
+            .chain(interface.ipv6.iter())
+            .cloned()
+            .collect();
+        let height = addresses.len().max(1) as u16;
+
+        Row::new(vec![
+            Cell::from(interface.name.clone()),
+            Cell::from(interface.link.operstate.clone()).style(Style::default().fg(state_color)),
+            Cell::from(
+                interface
+                    .link
+                    .speed_mbps
+                    .map(|speed| format!("{speed} Mb/s"))
+                    .unwrap_or_else(|| "-".to_string()),
+            ),
+            Cell::from(interface.mtu.to_string()),
+            Cell::from(interface.mac.clone()),
+            Cell::from(addresses.join("\n")),
+            Cell::from(format!("{}/{}", interface.rx_packets, interface.tx_packets)),
+            Cell::from(format!("{}/{}", interface.rx_errors, interface.tx_errors))
+                .style(fault_style(interface.errors_rising)),
+            Cell::from(format!(
+                "{}/{}",
+                interface.link.rx_dropped, interface.link.tx_dropped
+            ))
+            .style(fault_style(interface.drops_rising)),
+        ])
+        .height(height)
+    }));
+
+    let widths = [
+        Constraint::Length(12),
+        Constraint::Length(8),
+        Constraint::Length(11),
+        Constraint::Length(6),
+        Constraint::Length(17),
+        Constraint::Min(20),
+        Constraint::Length(20),
+        Constraint::Length(13),
+        Constraint::Length(13),
+    ];
+    Table::new(rows, widths)
+        .column_spacing(1)
+        .style(Style::default().fg(Color::White))
+}
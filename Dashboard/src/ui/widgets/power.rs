@@ -0,0 +1,54 @@
+//! Renders the power status shown in the top bar.
+use crate::backend::power::{BatteryState, PowerStatus, format_time_remaining};
+use ratatui::style::{Color, Style};
+use ratatui::text::Span;
+
+/// Returns the spans shown in the top bar for the power status:
+/// charge, state, rate and time remaining for every battery followed by the AC adapter status.
+/// Returns no spans if the system has no battery.
+pub fn power_status_spans(status: &PowerStatus) -> Vec<Span<'static>> {
+    if !status.has_battery() {
+        return Vec::new();
+    }
+
+    let mut spans = Vec::new();
+    for battery in &status.batteries {
+        let color = if battery.capacity < 15.0 {
+            Color::Red
+        } else if battery.capacity < 30.0 {
+            Color::Yellow
+        } else {
+            Color::Green
+        };
+        spans.push(Span::raw(format!("{} ", battery.name)));
+        spans.push(Span::styled(
+            format!("{:.0}%", battery.capacity),
+            Style::default().fg(color),
+        ));
+        spans.push(Span::raw(format!(" {}", battery.state.as_str())));
+        if let Some(watts) = battery.power_watts {
+            spans.push(Span::raw(format!(" {watts:.1} W")));
+        }
+        if let Some(remaining) = battery.time_remaining {
+            let suffix = if battery.state == BatteryState::Charging {
+                "until full"
+            } else {
+                "left"
+            };
+            spans.push(Span::raw(format!(
+                " {} {suffix}",
+                format_time_remaining(remaining)
+            )));
+        }
+        spans.push(Span::raw(" | "));
+    }
+    match status.ac_online {
+        Some(true) => spans.push(Span::raw("AC online ")),
+        Some(false) => spans.push(Span::raw("AC offline ")),
+        None => {
+            spans.pop();
+            spans.push(Span::raw(" "));
+        }
+    }
+    spans
+}
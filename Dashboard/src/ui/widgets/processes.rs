@@ -0,0 +1,57 @@
+//! Renders the rows of the process table.
+use crate::backend::converter::{format_bytes, format_rate};
+use crate::backend::processes::ProcessInfo;
+use ratatui::{
+    style::{Color, Style},
+    widgets::{Cell, Row},
+};
+
+// s is short for string slice (the result is better performance bc, the Compiler is told to not look at the whole string, but only the relevant part).
+// It then gets converted into a String to return a new owned String.
+// Short: Truncates a string to a maximum length and appends "..." if it exceeds that length.
+fn truncate_string(s: &str, max_length: usize) -> String {
+    let char_count = s.chars().count();
+    if char_count <= max_length {
+        s.to_string()
+    } else {
+        let truncated: String = s.chars().take(max_length - 3).collect();
+        format!("{truncated}...")
+    }
+}
+
+/// This function creates a vector of rows representing the given processes, in their order.
+/// The first row is the header, styled with a yellow foreground color.
+/// Each row contains the PID, truncated name, status, CPU usage, memory usage and network
+/// estimate. The network column shows the estimated throughput and the method it was
+/// estimated with ("netns" or "io~"), or "-" if there is no estimate.
+pub fn process_rows(processes: &[ProcessInfo]) -> Vec<Row<'static>> {
+    // create header row
+    let header = Row::new(vec![
+        Cell::from("PID"),
+        Cell::from("Name"),
+        Cell::from("Status"),
+        Cell::from("CPU (%)"),
+        Cell::from("Memory"),
+        Cell::from("Network"),
+    ])
+    .style(Style::default().fg(Color::Yellow));
+
+    // converts the process data into rows for the table
+    let mut rows = vec![header]; // adds header in the first row
+    rows.extend(processes.iter().map(|process| {
+        Row::new(vec![
+            Cell::from(process.pid.to_string()),
+            Cell::from(truncate_string(&process.name, 30)),
+            Cell::from(process.status.clone()),
+            Cell::from(format!("{:.2}", process.cpu_usage)),
+            Cell::from(format_bytes(process.memory)),
+            Cell::from(
+                process
+                    .network
+                    .map(|b| format!("{} {}", format_rate(b.total()), b.method.label()))
+                    .unwrap_or_else(|| "-".to_string()),
+            ),
+        ])
+    }));
+    rows
+}
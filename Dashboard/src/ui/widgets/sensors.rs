@@ -0,0 +1,61 @@
+//! Renders the sensor table.
+use crate::backend::sensors::{Sensor, SensorLevel};
+use ratatui::prelude::Constraint;
+use ratatui::style::{Color, Style};
+use ratatui::widgets::{Cell, Row, Table};
+
+/// Returns the color for a sensor level:
+/// red at or above the critical threshold, yellow at or above the high threshold
+/// or at or below the low threshold, green otherwise.
+fn level_color(level: SensorLevel) -> Color {
+    match level {
+        SensorLevel::Normal => Color::Green,
+        SensorLevel::High => Color::Yellow,
+        SensorLevel::Critical => Color::Red,
+    }
+}
+
+/// Returns a table with one row per sensor: chip, label, current value, low, high and
+/// critical threshold.
+/// The current value is colored depending on the thresholds.
+pub fn sensor_table(sensors: &[Sensor]) -> Table<'static> {
+    let header = Row::new(vec![
+        Cell::from("Chip"),
+        Cell::from("Sensor"),
+        Cell::from("Current"),
+        Cell::from("Low"),
+        Cell::from("High"),
+        Cell::from("Critical"),
+    ])
+    .style(Style::default().fg(Color::Yellow));
+
+    let mut rows = vec![header];
+    rows.extend(sensors.iter().map(|sensor| {
+        let threshold = |value: Option<f64>| {
+            value
+                .map(|v| sensor.format_value(v))
+                .unwrap_or_else(|| "-".to_string())
+        };
+        Row::new(vec![
+            Cell::from(sensor.chip.clone()),
+            Cell::from(sensor.label.clone()),
+            Cell::from(sensor.format_value(sensor.value))
+                .style(Style::default().fg(level_color(sensor.level()))),
+            Cell::from(threshold(sensor.low)),
+            Cell::from(threshold(sensor.high)),
+            Cell::from(threshold(sensor.critical)),
+        ])
+    }));
+
+    let widths = [
+        Constraint::Length(12),
+        Constraint::Length(14),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(10),
+    ];
+    Table::new(rows, widths)
+        .column_spacing(1)
+        .style(Style::default().fg(Color::White))
+}
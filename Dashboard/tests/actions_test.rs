@@ -0,0 +1,303 @@
+//! Tests for the alert actions and the desktop notifications
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::temp_dir;
+    use chrono::Local;
+    use linux_dashboard::actions::{
+        ActionDispatcher, ActionKind, AlertAction, RateLimiter, alert_environment, log_line,
+    };
+    use linux_dashboard::backend::alerts::{
+        AlertEvent, AlertEventKind::Fired, AlertEventKind::Resolved, AlertInput, Severity,
+    };
+    use linux_dashboard::backend::cpu::CpuStats;
+    use linux_dashboard::backend::memory::MemInfo;
+    use linux_dashboard::config::Config;
+    use linux_dashboard::notify::{Notification, Urgency, send_notification_with};
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+    use std::time::{Duration, Instant};
+
+    fn event(kind: linux_dashboard::backend::alerts::AlertEventKind, rule: &str) -> AlertEvent {
+        AlertEvent {
+            time: Local::now(),
+            kind,
+            rule: rule.to_string(),
+            expression: "cpu.total > 90".to_string(),
+            severity: Severity::Critical,
+            value: "95.0%".to_string(),
+        }
+    }
+
+    fn with_input<T>(f: impl FnOnce(&AlertInput) -> T) -> T {
+        let cpu = CpuStats {
+            name: "Test CPU".to_string(),
+            total_usage: 95.0,
+            cores: vec![95.0],
+        };
+        let memory = MemInfo {
+            total: 8000,
+            free: 1000,
+            available: 3000,
+            swap_total: 100,
+            swap_free: 40,
+            ..Default::default()
+        };
+        f(&AlertInput {
+            cpu: &cpu,
+            memory: &memory,
+            disks: &[],
+            network: &[],
+            processes: &[],
+        })
+    }
+
+    #[test]
+    fn test_rate_limiter() {
+        let mut limiter = RateLimiter::new(Duration::from_secs(300), 3);
+        assert!(limiter.allow(Fired, "cpu", 0.0).is_ok());
+        assert!(limiter.allow(Resolved, "cpu", 10.0).is_ok());
+        // a flapping alert is suppressed for the cooldown, and so is its resolve
+        assert!(limiter.allow(Fired, "cpu", 20.0).is_err());
+        assert!(limiter.allow(Resolved, "cpu", 30.0).is_err());
+        // the cooldown is per alert
+        assert!(limiter.allow(Fired, "disk", 40.0).is_ok());
+        // three runs within the hour
+        assert!(limiter.allow(Fired, "memory", 50.0).is_err());
+        assert!(limiter.allow(Resolved, "disk", 60.0).is_err());
+        // the runs of the first hour expire
+        assert!(limiter.allow(Fired, "cpu", 3600.0).is_ok());
+        assert!(limiter.allow(Resolved, "cpu", 3610.0).is_ok());
+        assert!(limiter.allow(Fired, "memory", 3620.0).is_err());
+        assert!(limiter.allow(Fired, "memory", 3640.0).is_ok());
+    }
+
+    /// Waits until the actions running in the background wrote `count` lines to `path`.
+    fn wait_for_lines(path: &Path, count: usize) -> String {
+        let started = Instant::now();
+        loop {
+            let text = fs::read_to_string(path).unwrap_or_default();
+            if text.ends_with('\n') && text.lines().count() >= count
+                || started.elapsed() > Duration::from_secs(10)
+            {
+                return text;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    #[test]
+    fn test_log_action() {
+        let dir = temp_dir("log_action");
+        let path = dir.join("logs").join("alerts.log");
+        let mut action = AlertAction::new(ActionKind::Log(path.clone()));
+        action.cooldown = Duration::from_secs(60);
+        let mut dispatcher = ActionDispatcher::new(vec![action], "testhost");
+
+        // an alert flapping every 10 seconds for 10 minutes is logged once per minute
+        with_input(|input| {
+            for i in 0..30 {
+                let timestamp = 1000.0 + i as f64 * 20.0;
+                dispatcher.dispatch(&[event(Fired, "cpu")], input, timestamp);
+                dispatcher.dispatch(&[event(Resolved, "cpu")], input, timestamp + 10.0);
+            }
+        });
+        let log = wait_for_lines(&path, 20);
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(lines.len(), 20);
+        assert!(lines[0].contains(" FIRED critical cpu: 95.0% (cpu.total > 90)"));
+        assert!(lines[1].contains(" RESOLVED critical cpu: "));
+        assert!(
+            dispatcher
+                .messages()
+                .last()
+                .unwrap()
+                .contains("log action for 'cpu' suppressed")
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_action_filters() {
+        let dir = temp_dir("action_filters");
+        let path = dir.join("alerts.log");
+        let mut action = AlertAction::new(ActionKind::Log(path.clone()));
+        action.on = vec![Fired];
+        action.severity = Some(Severity::Warning);
+        let mut dispatcher = ActionDispatcher::new(vec![action], "testhost");
+
+        let mut warning = event(Fired, "memory");
+        warning.severity = Severity::Warning;
+        with_input(|input| {
+            dispatcher.dispatch(&[event(Fired, "cpu"), warning.clone()], input, 1000.0);
+            warning.kind = Resolved;
+            dispatcher.dispatch(&[warning], input, 1010.0);
+        });
+        let log = wait_for_lines(&path, 1);
+        assert_eq!(log.lines().count(), 1);
+        assert!(log.contains("FIRED warning memory"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_command_action() {
+        let dir = temp_dir("command_action");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("out.txt");
+        let command = format!(
+            "echo \"$DASHBOARD_ALERT_STATE|$DASHBOARD_ALERT_RULE|$DASHBOARD_HOST|$DASHBOARD_CPU_USAGE|$DASHBOARD_SWAP_USED\" >> {}",
+            path.display()
+        );
+        let action = AlertAction::new(ActionKind::Command(command));
+        let mut dispatcher = ActionDispatcher::new(vec![action], "testhost");
+
+        with_input(|input| dispatcher.dispatch(&[event(Fired, "cpu high")], input, 1000.0));
+        assert_eq!(
+            wait_for_lines(&path, 1),
+            "fired|cpu high|testhost|95.0|60\n"
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_failures_are_reported() {
+        let dir = temp_dir("action_failures");
+        fs::create_dir_all(&dir).unwrap();
+        // a file where the log directory should be
+        fs::write(dir.join("file"), "").unwrap();
+        let actions = vec![
+            AlertAction::new(ActionKind::Command("exit 3".to_string())),
+            AlertAction::new(ActionKind::Log(dir.join("file").join("alerts.log"))),
+        ];
+        let mut dispatcher = ActionDispatcher::new(actions, "testhost");
+
+        // the failures are collected by the evaluations after the event
+        let started = Instant::now();
+        with_input(|input| {
+            dispatcher.dispatch(&[event(Fired, "cpu")], input, 1000.0);
+            while dispatcher.messages().len() < 2 && started.elapsed() < Duration::from_secs(10) {
+                std::thread::sleep(Duration::from_millis(20));
+                dispatcher.dispatch(&[], input, 1001.0);
+            }
+        });
+        let messages = dispatcher.messages();
+        assert!(
+            messages
+                .iter()
+                .any(|m| m.contains("command action for 'cpu' failed: exit status: 3"))
+        );
+        assert!(
+            messages
+                .iter()
+                .any(|m| m.contains("log action for 'cpu' failed"))
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_alert_environment() {
+        let environment =
+            with_input(|input| alert_environment(&event(Resolved, "cpu"), input, "box"));
+        let value = |name: &str| {
+            environment
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.as_str())
+        };
+        assert_eq!(value("DASHBOARD_ALERT_STATE"), Some("resolved"));
+        assert_eq!(value("DASHBOARD_ALERT_SEVERITY"), Some("critical"));
+        assert_eq!(value("DASHBOARD_ALERT_VALUE"), Some("95.0%"));
+        assert_eq!(value("DASHBOARD_MEMORY_AVAILABLE"), Some("3000"));
+        assert!(log_line(&event(Fired, "cpu")).contains("FIRED critical cpu"));
+    }
+
+    #[test]
+    fn test_action_config() {
+        let config = Config::parse(
+            r#"
+            [[actions]]
+            type = "command"
+            command = "echo $DASHBOARD_ALERT_RULE"
+            on = ["fired"]
+            cooldown = "10m"
+            max_per_hour = 5
+
+            [[actions]]
+            type = "log"
+            path = "/var/log/dashboard/alerts.log"
+            severity = "critical"
+
+            [[actions]]
+            type = "notify"
+            "#,
+        )
+        .unwrap();
+        let actions = config.alert_actions().unwrap();
+        assert_eq!(actions.len(), 3);
+        assert_eq!(
+            actions[0].kind,
+            ActionKind::Command("echo $DASHBOARD_ALERT_RULE".to_string())
+        );
+        assert_eq!(actions[0].on, vec![Fired]);
+        assert_eq!(actions[0].cooldown, Duration::from_secs(600));
+        assert_eq!(actions[0].max_per_hour, 5);
+        assert_eq!(actions[1].severity, Some(Severity::Critical));
+        assert_eq!(actions[1].on, vec![Fired, Resolved]);
+        assert_eq!(actions[2].kind, ActionKind::Notify);
+
+        assert!(Config::parse("[[actions]]\ntype = \"command\"").is_err());
+        assert!(Config::parse("[[actions]]\ntype = \"log\"\ncommand = \"true\"").is_err());
+        assert!(Config::parse("[[actions]]\ntype = \"mail\"").is_err());
+        assert!(Config::parse("[[actions]]\ntype = \"notify\"\non = [\"sometimes\"]").is_err());
+        assert!(Config::parse("[[actions]]\ntype = \"notify\"\ncooldown = \"soon\"").is_err());
+
+        let config =
+            Config::parse("[[alerts]]\nrule = \"cpu.total > 90\"\nresolve_after = \"2m\"").unwrap();
+        let rules = config.alert_rules().unwrap();
+        assert_eq!(rules[0].resolve_after, Duration::from_secs(120));
+    }
+
+    /// Writes an executable shell script to `path`.
+    fn script(path: &Path, text: &str) {
+        fs::write(path, format!("#!/bin/sh\n{text}\n")).unwrap();
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[test]
+    fn test_send_notification() {
+        let dir = temp_dir("notify");
+        fs::create_dir_all(&dir).unwrap();
+        let arguments = dir.join("arguments");
+        let notify_send = dir.join("notify-send");
+        script(
+            &notify_send,
+            &format!("printf '%s\\n' \"$@\" > {}", arguments.display()),
+        );
+
+        let notification = Notification {
+            summary: "-Alert: cpu".to_string(),
+            body: "95.0% on testhost".to_string(),
+            urgency: Urgency::Critical,
+        };
+        send_notification_with(&notify_send, &notification).unwrap();
+        assert_eq!(
+            fs::read_to_string(&arguments).unwrap(),
+            "--app-name=linux_dashboard\n--urgency=critical\n--\n-Alert: cpu\n95.0% on testhost\n"
+        );
+
+        // a failing notify-send is reported with its error output
+        let failing = dir.join("failing");
+        script(&failing, "echo 'Cannot connect to the bus' >&2; exit 1");
+        let error = send_notification_with(&failing, &notification).unwrap_err();
+        assert!(error.to_string().contains("Cannot connect to the bus"));
+        assert!(send_notification_with(dir.join("missing"), &notification).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
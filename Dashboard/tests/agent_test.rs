@@ -0,0 +1,383 @@
+//! Tests for the agent mode and attaching to an agent
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::{args, temp_dir};
+    use linux_dashboard::agent::{
+        AgentAddress, AgentHello, AgentOptions, AgentServer, AttachOptions, MAX_REFUSAL_QUEUE,
+        MAX_SAMPLE_LINE, REFUSAL_DELAY, SampleFeed, ViewerEvent, connect, ensure_private_dir,
+        load_token, spawn_viewer,
+    };
+    use linux_dashboard::backend::sample::Sample;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::PathBuf;
+    use std::time::Duration;
+    use tokio::time::{Instant, timeout};
+
+    const TOKEN: &str = "s3cret-token-for-tests";
+
+    fn sample(timestamp: f64) -> Sample {
+        let mut sample = Sample {
+            timestamp,
+            uptime_seconds: 100,
+            ..Default::default()
+        };
+        sample.cpu.total_usage = 12.5;
+        sample
+    }
+
+    fn hello() -> AgentHello {
+        AgentHello::new(Duration::from_millis(1000), None)
+    }
+
+    #[test]
+    fn test_agent_options() {
+        let options = AgentOptions::from_args(&args(&[
+            "--socket",
+            "/run/dashboard.sock",
+            "--interval",
+            "2000",
+            "--backlog",
+            "60",
+        ]))
+        .unwrap();
+        assert_eq!(options.socket, PathBuf::from("/run/dashboard.sock"));
+        assert_eq!(options.interval, Duration::from_secs(2));
+        assert_eq!(options.backlog, Duration::from_secs(60));
+        assert_eq!(options.mode, 0o600);
+
+        assert!(AgentOptions::from_args(&args(&["--mode", "1777"])).is_err());
+        assert!(AgentOptions::from_args(&args(&["--mode", "rw"])).is_err());
+        assert!(AgentOptions::from_args(&args(&["--backlog"])).is_err());
+        assert!(AgentOptions::from_args(&args(&["--port", "1"])).is_err());
+        assert!(AgentOptions::from_args(&args(&["--listen", "somewhere"])).is_err());
+
+        let options = AgentOptions::from_args(&args(&[
+            "--listen",
+            "0.0.0.0:9185",
+            "--token-file",
+            "/etc/dashboard/token",
+        ]))
+        .unwrap();
+        assert_eq!(options.listen.unwrap().port(), 9185);
+        assert_eq!(
+            options.token_file,
+            Some(PathBuf::from("/etc/dashboard/token"))
+        );
+    }
+
+    #[test]
+    fn test_attach_options() {
+        let options =
+            AttachOptions::from_args(&args(&["build-box.lan:9185", "--token-file", "token"]))
+                .unwrap();
+        assert_eq!(
+            options.address,
+            AgentAddress::Tcp("build-box.lan:9185".to_string())
+        );
+        assert_eq!(options.token_file, Some(PathBuf::from("token")));
+        assert!(options.address.is_remote());
+
+        let options = AttachOptions::from_args(&args(&["./agent:1.sock"])).unwrap();
+        assert_eq!(
+            options.address,
+            AgentAddress::Unix(PathBuf::from("./agent:1.sock"))
+        );
+        assert!(!options.address.is_remote());
+        assert_eq!(options.address.to_string(), "./agent:1.sock");
+        // a port out of range is no TCP address
+        assert!(!AgentAddress::parse("host:70000").is_remote());
+        assert!(!AgentAddress::parse(":9185").is_remote());
+
+        let options = AttachOptions::from_args(&[]).unwrap();
+        assert!(matches!(options.address, AgentAddress::Unix(_)));
+        assert!(AttachOptions::from_args(&args(&["a.sock", "b.sock"])).is_err());
+        assert!(AttachOptions::from_args(&args(&["--token"])).is_err());
+        assert!(AttachOptions::from_args(&args(&["--token-file"])).is_err());
+    }
+
+    #[test]
+    fn test_load_token() {
+        let dir = temp_dir("agent_token");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("token");
+        fs::write(&path, format!("{TOKEN}\n")).unwrap();
+        assert_eq!(load_token(Some(&path)).unwrap(), Some(TOKEN.to_string()));
+        fs::write(&path, "  \n").unwrap();
+        assert!(load_token(Some(&path)).is_err());
+        fs::write(&path, "two words but long enough").unwrap();
+        assert!(load_token(Some(&path)).is_err());
+        // too short to withstand guessing, or too long for the AUTH line
+        fs::write(&path, "s3cret-token").unwrap();
+        assert!(load_token(Some(&path)).is_err());
+        fs::write(&path, "x".repeat(16)).unwrap();
+        assert!(load_token(Some(&path)).is_ok());
+        fs::write(&path, "x".repeat(513)).unwrap();
+        assert!(load_token(Some(&path)).is_err());
+        assert!(load_token(Some(&dir.join("missing"))).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_private_dir() {
+        let dir = temp_dir("agent_private");
+        fs::create_dir_all(&dir).unwrap();
+
+        let private = dir.join("private");
+        ensure_private_dir(&private).unwrap();
+        let mode = fs::metadata(&private).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+        ensure_private_dir(&private).unwrap();
+
+        // a directory others can enter, or a link to one, is refused
+        let shared = dir.join("shared");
+        fs::create_dir(&shared).unwrap();
+        fs::set_permissions(&shared, fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(ensure_private_dir(&shared).is_err());
+        let link = dir.join("link");
+        std::os::unix::fs::symlink(&private, &link).unwrap();
+        assert!(ensure_private_dir(&link).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_feed_backlog() {
+        let feed = SampleFeed::new(&hello(), Duration::from_secs(10)).unwrap();
+        let (lines, _) = feed.subscribe();
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].contains("\"interval_ms\":1000"));
+
+        for i in 0..20 {
+            feed.publish(&sample(1000.0 + i as f64)).unwrap();
+        }
+        // the hello and the samples of the last 10 seconds
+        let (lines, mut receiver) = feed.subscribe();
+        assert_eq!(lines.len(), 12);
+        assert!(lines[1].starts_with("{\"timestamp\":1009.0"));
+        assert!(lines.iter().all(|line| line.ends_with('\n')));
+
+        feed.publish(&sample(1020.0)).unwrap();
+        assert!(receiver.try_recv().unwrap().contains("1020.0"));
+
+        // without a backlog the latest sample is still kept
+        let feed = SampleFeed::new(&hello(), Duration::ZERO).unwrap();
+        feed.publish(&sample(1000.0)).unwrap();
+        feed.publish(&sample(1001.0)).unwrap();
+        assert_eq!(feed.subscribe().0.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_viewers() {
+        let dir = temp_dir("agent_viewers");
+        let socket = dir.join("run").join("agent.sock");
+        let feed = SampleFeed::new(&hello(), Duration::from_secs(60)).unwrap();
+        feed.publish(&sample(1000.0)).unwrap();
+        feed.publish(&sample(1001.0)).unwrap();
+
+        let server = AgentServer::bind(&socket, 0o660, feed.clone()).unwrap();
+        let mode = fs::metadata(&socket).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o660);
+        // the directory the socket was created in is gone
+        let files: Vec<_> = fs::read_dir(dir.join("run")).unwrap().collect();
+        assert_eq!(files.len(), 1);
+        let agent = tokio::spawn(async move { server.run().await });
+
+        // two viewers get the same backlog, then the same new samples
+        let address = AgentAddress::Unix(socket.clone());
+        let mut first = connect(&address, None).await.unwrap();
+        let mut second = connect(&address, None).await.unwrap();
+        assert_eq!(first.hello.header.interval_ms, 1000);
+        assert!(first.hello.history.is_none());
+        for connection in [&mut first, &mut second] {
+            let sample = connection.next_sample().await.unwrap().unwrap();
+            assert_eq!(sample.timestamp, 1000.0);
+            assert_eq!(sample.cpu.total_usage, 12.5);
+            let sample = connection.next_sample().await.unwrap().unwrap();
+            assert_eq!(sample.timestamp, 1001.0);
+        }
+        feed.publish(&sample(1002.0)).unwrap();
+        for connection in [&mut first, &mut second] {
+            let sample = connection.next_sample().await.unwrap().unwrap();
+            assert_eq!(sample.timestamp, 1002.0);
+        }
+
+        // a second agent on the same socket is refused
+        let error = AgentServer::bind(&socket, 0o600, feed.clone())
+            .err()
+            .unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::AddrInUse);
+
+        // stopping the agent removes the socket and ends the stream
+        agent.abort();
+        let _ = agent.await;
+        assert!(!socket.exists());
+        assert!(first.next_sample().await.unwrap().is_none());
+        assert!(connect(&address, None).await.is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_stale_socket() {
+        let dir = temp_dir("agent_stale");
+        fs::create_dir_all(&dir).unwrap();
+        let feed = SampleFeed::new(&hello(), Duration::ZERO).unwrap();
+
+        // a socket nobody listens on is replaced
+        let socket = dir.join("agent.sock");
+        drop(std::os::unix::net::UnixListener::bind(&socket).unwrap());
+        assert!(socket.exists());
+        let server = AgentServer::bind(&socket, 0o600, feed.clone()).unwrap();
+        drop(server);
+
+        // any other file is left alone
+        let file = dir.join("notes.txt");
+        fs::write(&file, "keep me").unwrap();
+        assert!(AgentServer::bind(&file, 0o600, feed).is_err());
+        assert_eq!(fs::read_to_string(&file).unwrap(), "keep me");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_reconnect() {
+        let dir = temp_dir("agent_reconnect");
+        let socket = dir.join("agent.sock");
+        let feed = SampleFeed::new(&hello(), Duration::from_secs(60)).unwrap();
+        feed.publish(&sample(1000.0)).unwrap();
+        let server = AgentServer::bind(&socket, 0o600, feed.clone()).unwrap();
+        let agent = tokio::spawn(async move { server.run().await });
+
+        let address = AgentAddress::Unix(socket.clone());
+        let connection = connect(&address, None).await.unwrap();
+        let (viewer, mut events) = spawn_viewer(address, None, connection);
+        let mut next = async || {
+            timeout(Duration::from_secs(10), events.recv())
+                .await
+                .unwrap()
+                .unwrap()
+        };
+        assert!(matches!(next().await, ViewerEvent::Sample(s) if s.timestamp == 1000.0));
+
+        agent.abort();
+        let _ = agent.await;
+        assert!(matches!(next().await, ViewerEvent::Disconnected(_)));
+
+        // the viewer attaches again and receives the backlog once more
+        feed.publish(&sample(1001.0)).unwrap();
+        let server = AgentServer::bind(&socket, 0o600, feed).unwrap();
+        let agent = tokio::spawn(async move { server.run().await });
+        assert!(matches!(next().await, ViewerEvent::Reconnected(_)));
+        assert!(matches!(next().await, ViewerEvent::Sample(s) if s.timestamp == 1000.0));
+        assert!(matches!(next().await, ViewerEvent::Sample(s) if s.timestamp == 1001.0));
+
+        viewer.abort();
+        agent.abort();
+        let _ = agent.await;
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_remote_viewer() {
+        let feed = SampleFeed::new(&hello(), Duration::from_secs(60)).unwrap();
+        feed.publish(&sample(1000.0)).unwrap();
+        let listen = "127.0.0.1:0".parse().unwrap();
+        let server = AgentServer::bind_tcp(listen, TOKEN.to_string(), feed.clone())
+            .await
+            .unwrap();
+        let address = server.address().unwrap();
+        assert!(address.is_remote());
+        let agent = tokio::spawn(async move { server.run().await });
+
+        let mut connection = connect(&address, Some(TOKEN)).await.unwrap();
+        assert_eq!(connection.hello.header.interval_ms, 1000);
+        let first = connection.next_sample().await.unwrap().unwrap();
+        assert_eq!(first.timestamp, 1000.0);
+        feed.publish(&sample(1001.0)).unwrap();
+        let second = connection.next_sample().await.unwrap().unwrap();
+        assert_eq!(second.timestamp, 1001.0);
+
+        // a wrong or missing token is refused
+        let error = connect(&address, Some("a-wrong-guess-of-the-token"))
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::PermissionDenied);
+        assert!(error.to_string().contains("invalid token"));
+        let error = connect(&address, Some("s3cret-token-for-tests-but-longer"))
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::PermissionDenied);
+        let error = connect(&address, None).await.err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::PermissionDenied);
+
+        agent.abort();
+        let _ = agent.await;
+        assert!(connection.next_sample().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_guessing_is_limited() {
+        let feed = SampleFeed::new(&hello(), Duration::ZERO).unwrap();
+        let listen = "127.0.0.1:0".parse().unwrap();
+        let server = AgentServer::bind_tcp(listen, TOKEN.to_string(), feed)
+            .await
+            .unwrap();
+        let address = server.address().unwrap();
+        let agent = tokio::spawn(async move { server.run().await });
+
+        // guesses on parallel connections are answered one after another,
+        // beyond the longest queue after its wait
+        let started = Instant::now();
+        let mut guesses = tokio::task::JoinSet::new();
+        for i in 0..10 {
+            let address = address.clone();
+            guesses.spawn(async move {
+                let guess = format!("guess-number-{i}-of-the-token");
+                let error = connect(&address, Some(&guess)).await.err().unwrap();
+                (error, started.elapsed())
+            });
+        }
+
+        // the right token is accepted at once while the guesses wait
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let attached = Instant::now();
+        assert!(connect(&address, Some(TOKEN)).await.is_ok());
+        assert!(attached.elapsed() < REFUSAL_DELAY);
+
+        let mut refused = guesses.join_all().await;
+        refused.sort_by_key(|(_, elapsed)| *elapsed);
+        for (i, (error, elapsed)) in refused.iter().enumerate() {
+            assert!(error.to_string().ends_with("invalid token"));
+            assert!(*elapsed >= (REFUSAL_DELAY * (i as u32 + 1)).min(MAX_REFUSAL_QUEUE));
+            assert!(*elapsed < MAX_REFUSAL_QUEUE + REFUSAL_DELAY);
+        }
+
+        agent.abort();
+        let _ = agent.await;
+    }
+
+    #[tokio::test]
+    async fn test_long_lines_are_refused() {
+        // anything on the port may answer, a line without end is not buffered
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = AgentAddress::Tcp(listener.local_addr().unwrap().to_string());
+        let server = tokio::spawn(async move {
+            use tokio::io::AsyncWriteExt;
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let line = vec![b'x'; MAX_SAMPLE_LINE as usize + 1];
+            let _ = stream.write_all(&line).await;
+            std::future::pending::<()>().await;
+        });
+
+        let error = connect(&address, Some(TOKEN)).await.err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains("longer than"));
+        server.abort();
+    }
+}
@@ -0,0 +1,283 @@
+//! Tests for the alert rules and the configuration file
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::temp_dir;
+    use linux_dashboard::backend::alerts::{
+        AlertEngine, AlertEventKind, AlertInput, AlertRule, Comparison, Condition, Metric,
+        Severity, Threshold, parse_duration,
+    };
+    use linux_dashboard::backend::cpu::CpuStats;
+    use linux_dashboard::backend::disk::DiskInfo;
+    use linux_dashboard::backend::memory::MemInfo;
+    use linux_dashboard::backend::network::InterfaceThroughput;
+    use linux_dashboard::backend::processes::ProcessInfo;
+    use linux_dashboard::config::Config;
+    use std::fs;
+    use std::time::Duration;
+
+    const GIB: u64 = 1024 * 1024 * 1024;
+
+    /// Synthetic data of one refresh
+    struct Data {
+        cpu: CpuStats,
+        memory: MemInfo,
+        disks: Vec<DiskInfo>,
+        network: Vec<InterfaceThroughput>,
+        processes: Vec<ProcessInfo>,
+    }
+
+    impl Data {
+        fn new(cpu_usage: f32) -> Self {
+            Self {
+                cpu: CpuStats {
+                    name: "Test CPU".to_string(),
+                    total_usage: cpu_usage,
+                    cores: vec![cpu_usage, 10.0],
+                },
+                memory: MemInfo {
+                    total: 8 * GIB,
+                    free: GIB,
+                    available: 2 * GIB,
+                    ..Default::default()
+                },
+                disks: vec![DiskInfo {
+                    name: "/dev/sda2".to_string(),
+                    mount_point: "/var".to_string(),
+                    file_system: "ext4".to_string(),
+                    total: 100 * GIB,
+                    available: 4 * GIB,
+                }],
+                network: vec![InterfaceThroughput {
+                    name: "eth0".to_string(),
+                    download: Some(5.0 * 1024.0 * 1024.0),
+                    upload: None,
+                }],
+                processes: vec![ProcessInfo {
+                    pid: 100,
+                    name: "postgres".to_string(),
+                    status: "Run".to_string(),
+                    cpu_usage: 1.0,
+                    memory: GIB,
+                    network: None,
+                }],
+            }
+        }
+
+        fn input(&self) -> AlertInput<'_> {
+            AlertInput {
+                cpu: &self.cpu,
+                memory: &self.memory,
+                disks: &self.disks,
+                network: &self.network,
+                processes: &self.processes,
+            }
+        }
+    }
+
+    /// Returns whether the rule fires on the data right away
+    fn fires(rule: &str, data: &Data) -> bool {
+        let mut engine = AlertEngine::new(vec![AlertRule::parse(rule).unwrap()]);
+        engine.evaluate(&data.input(), 1000.0);
+        !engine.active().is_empty()
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let rule = AlertRule::parse("cpu.total > 90 for 30s").unwrap();
+        assert_eq!(
+            rule.condition,
+            Condition::Compare {
+                metric: Metric::CpuTotal,
+                comparison: Comparison::Greater,
+                threshold: Threshold::Absolute(90.0),
+            }
+        );
+        assert_eq!(rule.duration, Duration::from_secs(30));
+        assert_eq!(rule.name, "cpu.total > 90 for 30s");
+        assert_eq!(rule.severity, Severity::Warning);
+
+        // mount points with dots and slashes
+        let rule = AlertRule::parse("disk./var/lib.d.used >= 95%").unwrap();
+        assert!(matches!(
+            rule.condition,
+            Condition::Compare { metric: Metric::DiskUsed(ref mount), .. } if mount == "/var/lib.d"
+        ));
+
+        let rule = AlertRule::parse("process \"my server\" missing for 1m").unwrap();
+        assert_eq!(
+            rule.condition,
+            Condition::ProcessMissing("my server".to_string())
+        );
+        assert_eq!(rule.duration, Duration::from_secs(60));
+
+        assert!(AlertRule::parse("").is_err());
+        assert!(AlertRule::parse("cpu.total > ").is_err());
+        assert!(AlertRule::parse("cpu.total = 90").is_err());
+        assert!(AlertRule::parse("gpu.total > 90").is_err());
+        assert!(AlertRule::parse("disk..used > 90%").is_err());
+        assert!(AlertRule::parse("cpu.total > 90 for ever").is_err());
+        assert!(AlertRule::parse("process \"postgres missing").is_err());
+        // network rates have no total to take a percentage of
+        assert!(AlertRule::parse("net.eth0.rx > 50%").is_err());
+    }
+
+    #[test]
+    fn test_parse_thresholds_and_durations() {
+        assert_eq!(Threshold::parse("90"), Ok(Threshold::Absolute(90.0)));
+        assert_eq!(Threshold::parse("2.5%"), Ok(Threshold::Percent(2.5)));
+        assert_eq!(
+            Threshold::parse("512MiB"),
+            Ok(Threshold::Absolute(512.0 * 1024.0 * 1024.0))
+        );
+        assert_eq!(Threshold::parse("1GB"), Ok(Threshold::Absolute(GIB as f64)));
+        assert_eq!(
+            Threshold::parse("10M/s"),
+            Ok(Threshold::Absolute(10.0 * 1024.0 * 1024.0))
+        );
+        assert!(Threshold::parse("lots").is_err());
+        assert!(Threshold::parse("%").is_err());
+
+        assert_eq!(parse_duration("45s"), Ok(Duration::from_secs(45)));
+        assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(7200)));
+        assert!(parse_duration("-5s").is_err());
+        assert!(parse_duration("").is_err());
+        // durations too long for `Duration` are an error, not a panic
+        assert!(parse_duration("1e300h").is_err());
+        assert!(parse_duration("inf").is_err());
+    }
+
+    #[test]
+    fn test_conditions() {
+        let data = Data::new(95.0);
+        assert!(fires("cpu.total > 90", &data));
+        assert!(!fires("cpu.total < 90", &data));
+        assert!(fires("cpu.core.1 <= 10", &data));
+        // a core that does not exist never fires
+        assert!(!fires("cpu.core.7 >= 0", &data));
+
+        assert!(fires("mem.available < 3G", &data));
+        assert!(!fires("mem.available < 1G", &data));
+        // 2 of 8 GiB available
+        assert!(fires("mem.available <= 25%", &data));
+        assert!(fires("mem.used > 70%", &data));
+
+        // 96 of 100 GiB used
+        assert!(fires("disk./var.used > 95%", &data));
+        assert!(fires("disk./var.available < 5G", &data));
+        assert!(!fires("disk./home.used > 0%", &data));
+
+        assert!(fires("net.eth0.rx > 4M/s", &data));
+        // no rate yet
+        assert!(!fires("net.eth0.tx >= 0", &data));
+
+        assert!(!fires("process \"postgres\" missing", &data));
+        assert!(fires("process \"redis\" missing", &data));
+    }
+
+    #[test]
+    fn test_duration_and_resolve() {
+        let rule = AlertRule::parse("cpu.total > 90 for 30s").unwrap();
+        let mut engine = AlertEngine::new(vec![rule]);
+        let high = Data::new(95.0);
+        let low = Data::new(20.0);
+
+        assert!(engine.evaluate(&high.input(), 1000.0).is_empty());
+        assert!(engine.evaluate(&high.input(), 1020.0).is_empty());
+        // a dip restarts the duration
+        assert!(engine.evaluate(&low.input(), 1025.0).is_empty());
+        assert!(engine.evaluate(&high.input(), 1030.0).is_empty());
+        assert!(engine.evaluate(&high.input(), 1050.0).is_empty());
+
+        let events = engine.evaluate(&high.input(), 1060.0);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, AlertEventKind::Fired);
+        assert_eq!(events[0].value, "95.0%");
+        let active = engine.active();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].rule, "cpu.total > 90 for 30s");
+
+        // firing again while active gives no new event
+        assert!(engine.evaluate(&high.input(), 1070.0).is_empty());
+
+        let events = engine.evaluate(&low.input(), 1080.0);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, AlertEventKind::Resolved);
+        assert!(engine.active().is_empty());
+        assert_eq!(engine.history().len(), 2);
+
+        engine.reset();
+        assert!(engine.history().is_empty());
+    }
+
+    #[test]
+    fn test_resolve_after() {
+        let mut rule = AlertRule::parse("cpu.total > 90").unwrap();
+        rule.resolve_after = Duration::from_secs(60);
+        let mut engine = AlertEngine::new(vec![rule]);
+        let high = Data::new(95.0);
+        let low = Data::new(20.0);
+
+        assert_eq!(engine.evaluate(&high.input(), 1000.0).len(), 1);
+        // short dips do not resolve the alert
+        assert!(engine.evaluate(&low.input(), 1010.0).is_empty());
+        assert!(engine.evaluate(&low.input(), 1050.0).is_empty());
+        assert!(engine.evaluate(&high.input(), 1060.0).is_empty());
+        assert!(engine.evaluate(&low.input(), 1070.0).is_empty());
+        assert_eq!(engine.active().len(), 1);
+
+        let events = engine.evaluate(&low.input(), 1130.0);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, AlertEventKind::Resolved);
+        assert!(engine.active().is_empty());
+    }
+
+    #[test]
+    fn test_config() {
+        let config = Config::parse(
+            r#"
+            [[alerts]]
+            rule = "cpu.total > 90 for 30s"
+
+            [[alerts]]
+            name = "Database down"
+            rule = 'process "postgres" missing'
+            severity = "critical"
+            "#,
+        )
+        .unwrap();
+        let rules = config.alert_rules().unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].name, "cpu.total > 90 for 30s");
+        assert_eq!(rules[1].name, "Database down");
+        assert_eq!(rules[1].expression, "process \"postgres\" missing");
+        assert_eq!(rules[1].severity, Severity::Critical);
+
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+        // invalid rules, unknown keys and severities are reported
+        assert!(Config::parse("[[alerts]]\nrule = \"cpu.total >\"").is_err());
+        assert!(Config::parse("[[alerts]]\nrul = \"cpu.total > 1\"").is_err());
+        assert!(Config::parse("[[alerts]]\nrule = \"cpu.total > 1\"\nseverity = \"meh\"").is_err());
+    }
+
+    #[test]
+    fn test_load_config_file() {
+        let dir = temp_dir("config");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+
+        // a missing file is the default configuration, unless it was asked for explicitly
+        assert_eq!(Config::load_optional(&path).unwrap(), Config::default());
+        assert!(Config::load(&path).is_err());
+
+        fs::write(&path, "[[alerts]]\nrule = \"mem.available < 1G\"\n").unwrap();
+        assert_eq!(Config::load(&path).unwrap().alerts.len(), 1);
+
+        fs::write(&path, "[[alerts]]\nrule = \"mem.available < 1X\"\n").unwrap();
+        let error = Config::load_optional(&path).unwrap_err();
+        assert!(error.contains("config.toml"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,39 @@
+//! Fixtures shared by the tests: command line arguments, temporary directories
+//! and fake sysfs and procfs trees
+#![allow(dead_code)]
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Writes `content` to `file` below `root`, creating the missing directories.
+pub fn write(root: &Path, file: &str, content: &str) {
+    let path = root.join(file);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+/// Returns the root of an empty fake sysfs tree for the test `name`.
+pub fn fake_sysfs(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("dashboard-sysfs-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    root
+}
+
+/// Returns the root of a fake procfs tree for the test `name`, with an empty `net` directory.
+pub fn fake_proc(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("dashboard-proc-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(root.join("net")).unwrap();
+    root
+}
+
+/// Converts string literals into command line arguments.
+pub fn args(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+/// Returns a temporary directory for the test `name`, removed if it was left behind.
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("dashboard_{name}_test_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
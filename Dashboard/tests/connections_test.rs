@@ -0,0 +1,204 @@
+//! Tests for the connections module, using a fake procfs tree
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::fake_proc;
+    use linux_dashboard::backend::connections::{
+        BindScope, Connection, ConnectionFilter, ConnectionReader, Protocol, listening_ports,
+        parse_proc_net,
+    };
+    use linux_dashboard::ui::widgets::connections::scrolled_connection_table;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+    use ratatui::widgets::{Block, Borders};
+    use std::fs;
+    use std::net::SocketAddr;
+    use std::os::unix::fs::symlink;
+    use std::path::Path;
+
+    const TCP: &str = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000:0016 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 1001 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:B4C2 0100007F:1F90 01 00000000:00000000 00:00000000 00000000  1000        0 1002 1 0000000000000000 20 4 30 10 -1
+";
+
+    const TCP6: &str = "  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000001000000:0277 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 1003 1 0000000000000000 100 0 0 10 0
+";
+
+    const UDP: &str = "   sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops
+  100: 3500007F:0035 00000000:0000 07 00000000:00000000 00:00000000 00000000   101        0 1004 2 0000000000000000 0
+";
+
+    fn add_process(root: &Path, pid: u32, name: &str, inodes: &[u64]) {
+        let dir = root.join(pid.to_string());
+        fs::create_dir_all(dir.join("fd")).unwrap();
+        fs::write(dir.join("comm"), format!("{name}\n")).unwrap();
+        symlink("/dev/null", dir.join("fd/0")).unwrap();
+        for (i, inode) in inodes.iter().enumerate() {
+            symlink(
+                format!("socket:[{inode}]"),
+                dir.join(format!("fd/{}", i + 3)),
+            )
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_parse_proc_net_addresses_and_states() {
+        let connections = parse_proc_net(TCP, Protocol::Tcp);
+        assert_eq!(connections.len(), 2);
+        assert_eq!(
+            connections[0].local,
+            "0.0.0.0:22".parse::<SocketAddr>().unwrap()
+        );
+        assert_eq!(connections[0].state, "LISTEN");
+        assert_eq!(connections[0].inode, 1001);
+        assert_eq!(
+            connections[1].local,
+            "127.0.0.1:46274".parse::<SocketAddr>().unwrap()
+        );
+        assert_eq!(
+            connections[1].remote,
+            "127.0.0.1:8080".parse::<SocketAddr>().unwrap()
+        );
+        assert_eq!(connections[1].state, "ESTABLISHED");
+
+        let connections = parse_proc_net(TCP6, Protocol::Tcp6);
+        assert_eq!(
+            connections[0].local,
+            "[::1]:631".parse::<SocketAddr>().unwrap()
+        );
+
+        // unconnected UDP sockets are shown like `ss` does
+        let connections = parse_proc_net(UDP, Protocol::Udp);
+        assert_eq!(
+            connections[0].local,
+            "127.0.0.53:53".parse::<SocketAddr>().unwrap()
+        );
+        assert_eq!(connections[0].state, "UNCONN");
+    }
+
+    #[test]
+    fn test_read_connections_resolves_owning_processes() {
+        let root = fake_proc("owners");
+        fs::write(root.join("net/tcp"), TCP).unwrap();
+        fs::write(root.join("net/udp"), UDP).unwrap();
+        add_process(&root, 812, "sshd", &[1001]);
+        add_process(&root, 4242, "curl", &[1002]);
+        // not a process directory
+        fs::create_dir_all(root.join("sys/fd")).unwrap();
+
+        let connections = ConnectionReader::new(&root).read_connections();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(connections.len(), 3);
+        assert_eq!(connections[0].pid, Some(812));
+        assert_eq!(connections[0].process.as_deref(), Some("sshd"));
+        assert_eq!(connections[1].pid, Some(4242));
+        // the owner of the UDP socket is not readable
+        assert_eq!(connections[2].protocol, Protocol::Udp);
+        assert_eq!(connections[2].pid, None);
+    }
+
+    #[test]
+    fn test_filter_by_state_port_and_process() {
+        let mut connections = parse_proc_net(TCP, Protocol::Tcp);
+        connections[0].pid = Some(812);
+        connections[0].process = Some("sshd".to_string());
+        connections[1].pid = Some(4242);
+        connections[1].process = Some("curl".to_string());
+
+        let matching = |text: &str| {
+            let filter = ConnectionFilter::parse(text);
+            connections
+                .iter()
+                .filter(|c| filter.matches(c))
+                .map(|c| c.inode)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(matching(""), vec![1001, 1002]);
+        assert_eq!(matching("state:listen"), vec![1001]);
+        assert_eq!(matching("state:ESTABLISHED"), vec![1002]);
+        // the remote port matches as well
+        assert_eq!(matching("port:8080"), vec![1002]);
+        assert_eq!(matching("SSH"), vec![1001]);
+        assert_eq!(matching("4242"), vec![1002]);
+        assert_eq!(matching("state:listen curl"), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn test_listening_ports_sorted_by_exposure() {
+        let mut connections = parse_proc_net(TCP, Protocol::Tcp);
+        connections.extend(parse_proc_net(TCP6, Protocol::Tcp6));
+        connections.extend(parse_proc_net(UDP, Protocol::Udp));
+        // a second socket of the same service (SO_REUSEPORT)
+        connections.push(connections[0].clone());
+
+        let ports = listening_ports(&connections);
+        let summary: Vec<(&str, String, BindScope)> = ports
+            .iter()
+            .map(|p| (p.protocol.as_str(), p.address.to_string(), p.scope))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("tcp", "0.0.0.0:22".to_string(), BindScope::AllInterfaces),
+                ("udp", "127.0.0.53:53".to_string(), BindScope::Localhost),
+                ("tcp6", "[::1]:631".to_string(), BindScope::Localhost),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bind_scope() {
+        let scope = |addr: &str| BindScope::of(&addr.parse::<SocketAddr>().unwrap());
+        assert_eq!(scope("[::]:443"), BindScope::AllInterfaces);
+        assert_eq!(scope("192.168.1.10:8080"), BindScope::Address);
+        assert_eq!(scope("127.0.0.1:5432"), BindScope::Localhost);
+    }
+
+    #[test]
+    fn test_connection_table_scrolls_in_tiny_areas() {
+        let connections: Vec<Connection> = (0..10)
+            .map(|i| Connection {
+                protocol: Protocol::Tcp,
+                local: format!("127.0.0.1:{}", 8000 + i).parse().unwrap(),
+                remote: "0.0.0.0:0".parse().unwrap(),
+                state: "LISTEN".to_string(),
+                inode: i,
+                pid: None,
+                process: None,
+            })
+            .collect();
+        let connections: Vec<&Connection> = connections.iter().collect();
+
+        let render = |height: u16| {
+            let mut terminal = Terminal::new(TestBackend::new(80, height)).unwrap();
+            terminal
+                .draw(|frame| {
+                    let table = scrolled_connection_table(&connections, 9, frame.area().height)
+                        .block(Block::default().borders(Borders::ALL));
+                    frame.render_widget(table, frame.area());
+                })
+                .unwrap();
+            let buffer = terminal.backend().buffer().clone();
+            buffer
+                .content()
+                .iter()
+                .map(|cell| cell.symbol())
+                .collect::<String>()
+        };
+
+        // too short for any row, or even the borders
+        for height in 0..4 {
+            assert!(!render(height).contains("127.0.0.1:8008"));
+        }
+        // the last rows are scrolled into view with the selected one at the bottom
+        let text = render(5);
+        assert!(text.contains("127.0.0.1:8009"));
+        assert!(!text.contains("127.0.0.1:8007"));
+        assert!(render(20).contains("127.0.0.1:8000"));
+    }
+}
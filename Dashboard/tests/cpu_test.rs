@@ -1,4 +1,5 @@
 //! Tests für das Cpu Modul
+#![allow(clippy::doc_lazy_continuation, clippy::vec_init_then_push)]
 #[cfg(test)]
 /// importiert die notwendigen Module für die Tests, wobei mockall::*
 /// alles aus der mockall Bibliothek importiert und die anderen Imports
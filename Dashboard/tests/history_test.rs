@@ -0,0 +1,55 @@
+//! Tests for the time series used by the history charts
+#[cfg(test)]
+mod tests {
+    use linux_dashboard::backend::history::{FULL_RESOLUTION_POINTS, MAX_BUCKETS, TimeSeries};
+    use std::time::Duration;
+
+    #[test]
+    fn test_points_outside_retention_are_dropped() {
+        let mut series = TimeSeries::new();
+        for t in 0..10 {
+            series.push(1000.0 + t as f64, t as f64);
+        }
+        series.compact(1009.0, Duration::from_secs(5));
+
+        let points = series.points();
+        assert_eq!(points.first(), Some(&(1004.0, 4.0)));
+        assert_eq!(points.len(), 6);
+    }
+
+    #[test]
+    fn test_history_stays_bounded_with_fast_refresh() {
+        // one hour at 100ms refresh would be 36000 points without down-sampling
+        let retention = Duration::from_secs(3600);
+        let mut series = TimeSeries::new();
+        for i in 0..36_000 {
+            let t = i as f64 * 0.1;
+            series.push(t, 1.0);
+            series.compact(t, retention);
+        }
+
+        assert!(series.len() <= FULL_RESOLUTION_POINTS + MAX_BUCKETS + 1);
+        // the newest points keep their full resolution
+        let points = series.points();
+        let newest = &points[points.len() - FULL_RESOLUTION_POINTS..];
+        assert!((newest[1].0 - newest[0].0 - 0.1).abs() < 1e-6);
+        // averaging constant values keeps the value
+        assert!(points.iter().all(|(_, v)| (*v - 1.0).abs() < 1e-9));
+    }
+
+    #[test]
+    fn test_down_sampling_uses_weighted_average() {
+        let retention = Duration::from_secs(MAX_BUCKETS as u64 * 10);
+        let mut series = TimeSeries::new();
+        // two samples in the same 10s bucket, followed by enough recent samples
+        series.push(0.0, 2.0);
+        series.push(1.0, 4.0);
+        for i in 0..FULL_RESOLUTION_POINTS {
+            series.push(100.0 + i as f64, 0.0);
+        }
+        series.compact(100.0 + FULL_RESOLUTION_POINTS as f64, retention);
+
+        assert_eq!(series.len(), FULL_RESOLUTION_POINTS + 1);
+        assert_eq!(series.points()[0], (0.5, 3.0));
+    }
+}
@@ -0,0 +1,142 @@
+//! Tests for the memory module
+#[cfg(test)]
+mod tests {
+    use linux_dashboard::backend::memory::{MemInfo, parse_meminfo};
+    use linux_dashboard::backend::memory_history::{MemoryHistory, UsagePoint};
+    use linux_dashboard::backend::vmstat::{VmStat, parse_vmstat};
+    use linux_dashboard::ui::widgets::memory::ram_usage_bar;
+    use std::time::Duration;
+
+    const MEMINFO: &str = "\
+MemTotal:        8000000 kB
+MemFree:         1000000 kB
+MemAvailable:    5000000 kB
+Buffers:          500000 kB
+Cached:          3000000 kB
+SwapCached:            0 kB
+Dirty:              1200 kB
+Writeback:            24 kB
+Shmem:            200000 kB
+Slab:             600000 kB
+SReclaimable:     400000 kB
+SUnreclaim:       200000 kB
+CommitLimit:     6000000 kB
+Committed_AS:    4500000 kB
+HugePages_Total:      16
+HugePages_Free:        4
+Hugepagesize:       2048 kB
+SwapTotal:       2000000 kB
+SwapFree:        1500000 kB
+";
+
+    #[test]
+    fn test_parse_meminfo() {
+        let info = parse_meminfo(MEMINFO);
+
+        assert_eq!(info.total, 8_000_000 * 1024);
+        assert_eq!(info.available, 5_000_000 * 1024);
+        assert_eq!(info.shared, 200_000 * 1024);
+        assert_eq!(info.slab_reclaimable, 400_000 * 1024);
+        assert_eq!(info.slab_unreclaimable, 200_000 * 1024);
+        assert_eq!(info.dirty, 1200 * 1024);
+        assert_eq!(info.writeback, 24 * 1024);
+        assert_eq!(info.committed_as, 4_500_000 * 1024);
+        // hugepage counters have no unit and must not be scaled
+        assert_eq!(info.hugepages_total, 16);
+        assert_eq!(info.hugepages_free, 4);
+        assert_eq!(info.hugepage_size, 2048 * 1024);
+        assert_eq!(info.swap_used(), 500_000 * 1024);
+    }
+
+    #[test]
+    fn test_used_excludes_buffers_and_cache() {
+        let info = parse_meminfo(MEMINFO);
+
+        // 8000000 - 1000000 free - 500000 buffers - 3000000 cached - 400000 reclaimable slab
+        assert_eq!(info.used(), 3_100_000 * 1024);
+        let total: u64 = info.segments().iter().map(|s| s.bytes).sum();
+        assert_eq!(total, info.total);
+    }
+
+    #[test]
+    fn test_ram_usage_bar_fills_width() {
+        let info = parse_meminfo(MEMINFO);
+        let bar = ram_usage_bar(&info, 40);
+        assert_eq!(bar.width(), 40);
+
+        let empty = ram_usage_bar(&MemInfo::default(), 10);
+        assert_eq!(empty.width(), 10);
+    }
+
+    #[test]
+    fn test_swap_rates_use_elapsed_time() {
+        let mem = parse_meminfo(MEMINFO);
+        let mut history = MemoryHistory::new();
+
+        let first = parse_vmstat("pswpin 100\npswpout 50\npgfault 1000\npgmajfault 10\n");
+        let second = VmStat {
+            pswpin: 300,
+            pswpout: 150,
+            pgfault: 5000,
+            pgmajfault: 30,
+            oom_kill: 0,
+        };
+        history.update_at(&mem, Some(first), 1000.0);
+        history.update_at(&mem, Some(second), 1002.0);
+
+        let rates = history.latest_rates();
+        assert_eq!(rates.swap_in, 100.0);
+        assert_eq!(rates.swap_out, 50.0);
+        assert_eq!(rates.page_faults, 2000.0);
+        assert_eq!(rates.major_faults, 10.0);
+        assert_eq!(history.used_history().len(), 2);
+        assert_eq!(history.swap_in_history(), &[(1002.0, 100.0)]);
+    }
+
+    #[test]
+    fn test_swap_rates_ignore_counter_reset() {
+        let mem = MemInfo::default();
+        let mut history = MemoryHistory::new();
+
+        history.update_at(&mem, Some(parse_vmstat("pswpin 500\n")), 1000.0);
+        history.update_at(&mem, Some(parse_vmstat("pswpin 20\n")), 1001.0);
+
+        assert_eq!(history.latest_rates().swap_in, 0.0);
+    }
+
+    #[test]
+    fn test_usage_history_is_seeded_and_follows_the_retention() {
+        const GIB: u64 = 1 << 30;
+        let mem = MemInfo {
+            total: 8 * GIB,
+            free: 4 * GIB,
+            available: 4 * GIB,
+            ..Default::default()
+        };
+        let mut history = MemoryHistory::new();
+        history.set_retention(Duration::from_secs(3600));
+        history.update_at(&mem, None, 10_000.0);
+
+        // stored points overlapping the live history are left out
+        let stored: Vec<UsagePoint> = [6_000.0, 9_000.0, 9_990.0, 10_000.0]
+            .iter()
+            .map(|timestamp| UsagePoint {
+                timestamp: *timestamp,
+                used: 2 * GIB,
+                available: 6 * GIB,
+                swap_used: 0,
+            })
+            .collect();
+        history.seed_history(&stored);
+        let used = history.used_history();
+        assert_eq!(used, vec![(9_000.0, 2.0), (9_990.0, 2.0), (10_000.0, 4.0)]);
+        assert_eq!(history.available_history()[0], (9_000.0, 6.0));
+
+        // zooming in drops the stored points outside of the window
+        history.set_retention(Duration::from_secs(60));
+        assert_eq!(
+            history.used_history(),
+            vec![(9_990.0, 2.0), (10_000.0, 4.0)]
+        );
+    }
+}
@@ -0,0 +1,118 @@
+//! Tests for the network protocol statistics
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::fake_proc;
+    use linux_dashboard::backend::netstat::{
+        ProtocolHistory, ProtocolRates, ProtocolStats, RETENTION, parse_counter_table,
+    };
+    use std::fs;
+    use std::time::{Duration, Instant};
+
+    const SNMP: &str = "Ip: Forwarding DefaultTTL InReceives
+Ip: 1 64 123456
+Icmp: InMsgs InErrors
+Icmp: 10 0
+Tcp: RtoAlgorithm RtoMin RtoMax MaxConn ActiveOpens PassiveOpens AttemptFails EstabResets CurrEstab InSegs OutSegs RetransSegs InErrs OutRsts InCsumErrors
+Tcp: 1 200 120000 -1 1500 300 12 40 25 900000 800000 1200 3 77 0
+Udp: InDatagrams NoPorts InErrors OutDatagrams RcvbufErrors SndbufErrors InCsumErrors IgnoredMulti MemErrors
+Udp: 50000 20 5 48000 4 1 0 0 0
+";
+
+    const NETSTAT: &str = "TcpExt: SyncookiesSent ListenOverflows ListenDrops
+TcpExt: 0 9 11
+IpExt: InNoRoutes InTruncatedPkts
+IpExt: 0 0
+";
+
+    #[test]
+    fn test_read_snmp_and_netstat() {
+        let root = fake_proc("netstat");
+        fs::write(root.join("net/snmp"), SNMP).unwrap();
+        fs::write(root.join("net/netstat"), NETSTAT).unwrap();
+
+        let stats = ProtocolStats::read_from(&root.join("net")).expect("stats");
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(stats.tcp_active_opens, 1500);
+        assert_eq!(stats.tcp_passive_opens, 300);
+        assert_eq!(stats.tcp_estab_resets, 40);
+        assert_eq!(stats.tcp_out_rsts, 77);
+        assert_eq!(stats.tcp_out_segs, 800000);
+        assert_eq!(stats.tcp_retrans_segs, 1200);
+        assert_eq!(stats.udp_in_errors, 5);
+        assert_eq!(stats.udp_rcvbuf_errors, 4);
+        assert_eq!(stats.udp_sndbuf_errors, 1);
+        assert_eq!(stats.listen_overflows, 9);
+        assert_eq!(stats.listen_drops, 11);
+    }
+
+    #[test]
+    fn test_missing_snmp_file_yields_none() {
+        assert!(ProtocolStats::read_from(std::path::Path::new("/nonexistent")).is_none());
+    }
+
+    #[test]
+    fn test_rates_and_retransmit_share() {
+        let old = ProtocolStats::from_counters(&parse_counter_table(SNMP));
+        let new = ProtocolStats {
+            tcp_out_segs: old.tcp_out_segs + 2_000,
+            tcp_retrans_segs: old.tcp_retrans_segs + 50,
+            tcp_estab_resets: old.tcp_estab_resets + 4,
+            tcp_out_rsts: old.tcp_out_rsts + 6,
+            ..old
+        };
+
+        let rates = ProtocolRates::between(&old, &new, 2.0);
+        assert_eq!(rates.retransmits, 25.0);
+        assert_eq!(rates.retransmit_percent, 2.5);
+        assert_eq!(rates.resets, 5.0);
+        assert_eq!(rates.udp_errors, 0.0);
+    }
+
+    #[test]
+    fn test_history_records_rates_from_elapsed_time() {
+        let start = Instant::now();
+        let mut history = ProtocolHistory::new(start);
+        let first = ProtocolStats {
+            tcp_out_segs: 1_000,
+            tcp_retrans_segs: 10,
+            ..Default::default()
+        };
+        history.update_at(Some(first), start);
+        assert!(history.retransmit_history().is_empty());
+
+        let second = ProtocolStats {
+            tcp_out_segs: 1_500,
+            tcp_retrans_segs: 15,
+            ..first
+        };
+        history.update_at(Some(second), start + Duration::from_millis(500));
+        assert_eq!(history.latest_rates().retransmits, 10.0);
+        assert_eq!(history.retransmit_history(), vec![(0.5, 1.0)]);
+
+        // a missing sample is ignored
+        history.update_at(None, start + Duration::from_secs(1));
+        assert_eq!(history.latest_stats(), Some(second));
+    }
+
+    #[test]
+    fn test_history_keeps_the_retention_window() {
+        let start = Instant::now();
+        let mut history = ProtocolHistory::new(start);
+        let seconds = RETENTION.as_secs() * 2;
+        for second in 0..=seconds {
+            let stats = ProtocolStats {
+                tcp_estab_resets: second,
+                ..Default::default()
+            };
+            history.update_at(Some(stats), start + Duration::from_secs(second));
+        }
+        let resets = history.resets_history();
+        // points older than the window are dropped, older ones merged, the newest kept
+        assert!(resets.first().unwrap().0 >= RETENTION.as_secs_f64());
+        assert_eq!(resets.last().unwrap(), &(seconds as f64, 1.0));
+        assert!(resets.len() < RETENTION.as_secs() as usize);
+    }
+}
@@ -0,0 +1,505 @@
+//! Tests for the network module
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::{fake_sysfs, write};
+    use linux_dashboard::backend::network::{
+        InterfaceCounters, InterfaceDetails, InterfaceRate, InterfaceThroughput, NetworkManager,
+        RateTracker, SeriesKind, read_link_info,
+    };
+    use linux_dashboard::backend::network_source::{
+        InterfaceSample, NetworkSource, SampleNetworks,
+    };
+    use std::collections::VecDeque;
+    use std::fs;
+    use std::time::Duration;
+
+    #[test]
+    fn test_read_link_info() {
+        let root = fake_sysfs("link");
+        write(&root, "class/net/eth0/operstate", "up\n");
+        write(&root, "class/net/eth0/speed", "1000\n");
+        write(&root, "class/net/eth0/statistics/rx_dropped", "12\n");
+        write(&root, "class/net/eth0/statistics/tx_dropped", "3\n");
+        write(&root, "class/net/veth1/operstate", "up\n");
+        write(&root, "class/net/veth1/speed", "-1\n");
+
+        let eth0 = read_link_info(&root, "eth0");
+        let veth = read_link_info(&root, "veth1");
+        let missing = read_link_info(&root, "wlan0");
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(eth0.operstate, "up");
+        assert_eq!(eth0.speed_mbps, Some(1000));
+        assert_eq!(eth0.rx_dropped, 12);
+        assert_eq!(eth0.tx_dropped, 3);
+        assert_eq!(veth.speed_mbps, None);
+        assert_eq!(missing.operstate, "unknown");
+    }
+
+    fn faulty(errors: u64, drops: u64) -> InterfaceSample {
+        let mut sample = InterfaceSample::default();
+        sample.details.name = "eth0".to_string();
+        sample.details.rx_errors = errors;
+        sample.details.link.rx_dropped = drops;
+        sample
+    }
+
+    fn faults(manager: &NetworkManager<SampleNetworks>) -> (bool, bool) {
+        let details = &manager.interface_details()[0];
+        (details.errors_rising, details.drops_rising)
+    }
+
+    #[test]
+    fn test_rising_errors_and_drops_are_flagged() {
+        let mut manager = NetworkManager::new(SampleNetworks::default());
+        // counters that are already high at the first refresh are not flagged
+        manager.source_mut().set_interfaces(vec![faulty(10, 10)]);
+        manager.refresh_at(100.0);
+        assert_eq!(faults(&manager), (false, false));
+
+        manager.source_mut().set_interfaces(vec![faulty(12, 10)]);
+        manager.refresh_at(101.0);
+        assert_eq!(faults(&manager), (true, false));
+
+        manager.source_mut().set_interfaces(vec![faulty(12, 11)]);
+        manager.refresh_at(110.0);
+        assert_eq!(faults(&manager), (true, true));
+
+        // the flags stay raised for 30 seconds after the last increase
+        manager.refresh_at(131.5);
+        assert_eq!(faults(&manager), (false, true));
+        manager.refresh_at(140.0);
+        assert_eq!(faults(&manager), (false, false));
+
+        // a counter reset is no increase, only the next increase raises the flag again
+        manager.source_mut().set_interfaces(vec![faulty(0, 0)]);
+        manager.refresh_at(141.0);
+        assert_eq!(faults(&manager), (false, false));
+        manager.source_mut().set_interfaces(vec![faulty(1, 0)]);
+        manager.refresh_at(142.0);
+        assert_eq!(faults(&manager), (true, false));
+
+        // clearing the history forgets the counters
+        manager.clear_history();
+        manager.source_mut().set_interfaces(vec![faulty(5, 5)]);
+        manager.refresh_at(143.0);
+        assert_eq!(faults(&manager), (false, false));
+    }
+
+    fn counters(received: u64, transmitted: u64) -> InterfaceCounters {
+        InterfaceCounters {
+            received,
+            transmitted,
+        }
+    }
+
+    #[test]
+    fn test_rates_are_per_second_for_any_interval() {
+        let mut tracker = RateTracker::default();
+        let first = vec![("eth0".to_string(), counters(1_000, 500))];
+        assert_eq!(
+            tracker.update(100.0, &first),
+            vec![("eth0".to_string(), None)]
+        );
+
+        // 2500 ms interval: 5000 bytes received means 2000 B/s, not 5000 B/s
+        let second = vec![("eth0".to_string(), counters(6_000, 3_000))];
+        let rates = tracker.update(102.5, &second);
+        assert_eq!(
+            rates,
+            vec![(
+                "eth0".to_string(),
+                Some(InterfaceRate {
+                    download: 2000.0,
+                    upload: 1000.0
+                })
+            )]
+        );
+
+        // 100 ms interval
+        let third = vec![("eth0".to_string(), counters(6_100, 3_000))];
+        let rates = tracker.update(102.6, &third);
+        let rate = rates[0].1.unwrap();
+        assert!((rate.download - 1000.0).abs() < 1e-6);
+        assert_eq!(rate.upload, 0.0);
+    }
+
+    #[test]
+    fn test_counter_reset_does_not_underflow() {
+        let mut tracker = RateTracker::default();
+        tracker.update(
+            0.0,
+            &[("wlan0".to_string(), counters(9_000_000, 9_000_000))],
+        );
+
+        // interface restarted, counters start again from zero
+        let rates = tracker.update(1.0, &[("wlan0".to_string(), counters(4_096, 1_024))]);
+        assert_eq!(
+            rates[0].1,
+            Some(InterfaceRate {
+                download: 4096.0,
+                upload: 1024.0
+            })
+        );
+    }
+
+    #[test]
+    fn test_new_and_removed_interfaces() {
+        let mut tracker = RateTracker::default();
+        tracker.update(0.0, &[("eth0".to_string(), counters(0, 0))]);
+
+        // tun0 appears with large counters, that must not show up as a spike
+        let rates = tracker.update(
+            1.0,
+            &[
+                ("eth0".to_string(), counters(100, 100)),
+                ("tun0".to_string(), counters(50_000_000, 1_000)),
+            ],
+        );
+        assert!(rates[0].1.is_some());
+        assert_eq!(rates[1], ("tun0".to_string(), None));
+
+        // eth0 disappears and comes back later, it starts over without a rate
+        tracker.update(2.0, &[("tun0".to_string(), counters(50_000_100, 1_000))]);
+        let rates = tracker.update(3.0, &[("eth0".to_string(), counters(200, 200))]);
+        assert_eq!(rates, vec![("eth0".to_string(), None)]);
+    }
+
+    #[test]
+    fn test_non_increasing_timestamp_yields_no_rate() {
+        let mut tracker = RateTracker::default();
+        tracker.update(5.0, &[("eth0".to_string(), counters(0, 0))]);
+        let rates = tracker.update(5.0, &[("eth0".to_string(), counters(10, 10))]);
+        assert_eq!(rates[0].1, None);
+    }
+
+    /// Network source that replays a scripted sequence of refreshes.
+    /// Every refresh takes the next list of (interface, received, transmitted) entries,
+    /// the last list is repeated once the script is exhausted.
+    struct ScriptedSource {
+        script: VecDeque<Vec<(&'static str, u64, u64)>>,
+        current: Vec<(&'static str, u64, u64)>,
+    }
+
+    impl ScriptedSource {
+        fn new(script: Vec<Vec<(&'static str, u64, u64)>>) -> Self {
+            Self {
+                script: script.into(),
+                current: Vec::new(),
+            }
+        }
+    }
+
+    impl NetworkSource for ScriptedSource {
+        fn refresh(&mut self) {
+            if let Some(next) = self.script.pop_front() {
+                self.current = next;
+            }
+        }
+
+        fn interfaces(&self) -> Vec<InterfaceSample> {
+            self.current
+                .iter()
+                .map(|(name, received, transmitted)| InterfaceSample {
+                    counters: counters(*received, *transmitted),
+                    details: InterfaceDetails {
+                        name: name.to_string(),
+                        ..Default::default()
+                    },
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_manager_records_per_second_rates() {
+        // the first entry is consumed when the manager is created
+        let source = ScriptedSource::new(vec![
+            vec![("eth0", 0, 0)],
+            vec![("eth0", 0, 0)],
+            vec![("eth0", 4_000, 2_000)],
+        ]);
+        let mut manager = NetworkManager::new(source);
+
+        let text = manager.format_network_at(1_000.0);
+        assert_eq!(text, "eth0: measuring...\n");
+        let text = manager.format_network_at(1_002.0);
+        assert_eq!(text, "eth0: 2 KB/s (down), 1000 B/s (up)\n");
+
+        let (download, upload) = manager.history("eth0").unwrap();
+        assert_eq!(download.points(), vec![(1_002.0, 2_000.0)]);
+        assert_eq!(upload.points(), vec![(1_002.0, -1_000.0)]);
+    }
+
+    #[test]
+    fn test_manager_exposes_throughput_data() {
+        let source = ScriptedSource::new(vec![
+            vec![("eth0", 0, 0)],
+            vec![("eth0", 0, 0), ("wlan0", 0, 0)],
+            vec![("eth0", 3_000, 1_000), ("wlan0", 500, 0)],
+        ]);
+        let mut manager = NetworkManager::new(source);
+        manager.refresh_at(10.0);
+        assert!(manager.throughput().iter().all(|i| i.download.is_none()));
+
+        manager.refresh_at(11.0);
+        assert_eq!(
+            manager.throughput(),
+            &[
+                InterfaceThroughput {
+                    name: "eth0".to_string(),
+                    download: Some(3_000.0),
+                    upload: Some(1_000.0),
+                },
+                InterfaceThroughput {
+                    name: "wlan0".to_string(),
+                    download: Some(500.0),
+                    upload: Some(0.0),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chart_series_of_all_interfaces_include_total() {
+        let source = ScriptedSource::new(vec![
+            vec![("eth0", 0, 0)],
+            vec![("eth0", 0, 0), ("wlan0", 0, 0)],
+            vec![("eth0", 1_000, 400), ("wlan0", 3_000, 100)],
+        ]);
+        let mut manager = NetworkManager::new(source);
+        manager.refresh_at(0.0);
+        manager.refresh_at(1.0);
+
+        let single = manager.chart_series();
+        let kinds: Vec<SeriesKind> = single.iter().map(|series| series.kind).collect();
+        assert_eq!(kinds, vec![SeriesKind::Download, SeriesKind::Upload]);
+        assert_eq!(single[0].download, vec![(1.0, 1_000.0)]);
+
+        manager.toggle_all_interfaces();
+        let all = manager.chart_series();
+        let kinds: Vec<SeriesKind> = all.iter().map(|series| series.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                SeriesKind::Interface(0),
+                SeriesKind::Interface(1),
+                SeriesKind::Total
+            ]
+        );
+        assert_eq!(all[2].download, vec![(1.0, 4_000.0)]);
+        assert_eq!(all[2].upload, vec![(1.0, -500.0)]);
+    }
+
+    #[test]
+    fn test_overlay_hides_virtual_interfaces_and_totals_over_time() {
+        let source = ScriptedSource::new(vec![
+            vec![("eth0", 0, 0)],
+            vec![("eth0", 0, 0), ("lo", 0, 0), ("veth1", 0, 0)],
+            vec![
+                ("eth0", 1_000, 100),
+                ("lo", 5_000, 5_000),
+                ("veth1", 200, 0),
+            ],
+            vec![
+                ("eth0", 3_000, 100),
+                ("lo", 5_000, 5_000),
+                ("veth1", 200, 0),
+                ("wlan0", 0, 0),
+            ],
+            vec![
+                ("eth0", 3_000, 100),
+                ("lo", 5_000, 5_000),
+                ("veth1", 200, 0),
+                ("wlan0", 700, 300),
+            ],
+        ]);
+        let mut manager = NetworkManager::new(source);
+        for second in 0..4 {
+            manager.refresh_at(second as f64);
+        }
+        manager.toggle_all_interfaces();
+        assert!(manager.is_showing_all_interfaces());
+
+        // loopback and virtual interfaces are hidden by default
+        assert!(manager.is_hiding_virtual());
+        assert_eq!(manager.overlay_interfaces(), vec!["eth0", "wlan0"]);
+        let series = manager.chart_series();
+        let names: Vec<&str> = series.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["eth0", "wlan0", "Total"]);
+        assert_eq!(series[1].kind, SeriesKind::Interface(1));
+        // wlan0 appeared later and only adds to the last point of the total
+        assert_eq!(
+            series[2].download,
+            vec![(1.0, 1_000.0), (2.0, 2_000.0), (3.0, 700.0)]
+        );
+        assert_eq!(
+            series[2].upload,
+            vec![(1.0, -100.0), (2.0, 0.0), (3.0, -300.0)]
+        );
+
+        manager.toggle_hide_virtual();
+        assert_eq!(
+            manager.overlay_interfaces(),
+            vec!["eth0", "lo", "veth1", "wlan0"]
+        );
+        let series = manager.chart_series();
+        assert_eq!(series.len(), 5);
+        assert_eq!(series[4].download[0], (1.0, 6_200.0));
+        // the scale follows the largest value of any series, the total included
+        assert_eq!(manager.chart_scale(), ("KB/s", 1024.0));
+
+        // back to the selected interface: download and upload of eth0, no total
+        manager.toggle_all_interfaces();
+        let series = manager.chart_series();
+        let kinds: Vec<SeriesKind> = series.iter().map(|s| s.kind).collect();
+        assert_eq!(kinds, vec![SeriesKind::Download, SeriesKind::Upload]);
+        assert_eq!(series[0].download.len(), 3);
+        assert!(series[0].upload.is_empty());
+    }
+
+    #[test]
+    fn test_manager_trims_history_to_retention() {
+        let mut script = vec![vec![("eth0", 0, 0)]];
+        script.extend((0..=120).map(|i| vec![("eth0", i * 100, 0)]));
+        let mut manager = NetworkManager::new(ScriptedSource::new(script));
+        manager.set_retention(Duration::from_secs(60));
+
+        for second in 0..=120 {
+            manager.format_network_at(second as f64);
+        }
+
+        let (download, _) = manager.history("eth0").unwrap();
+        let points = download.points();
+        assert_eq!(points.first().unwrap().0, 60.0);
+        assert_eq!(points.last().unwrap(), &(120.0, 100.0));
+    }
+
+    #[test]
+    fn test_manager_is_seeded_with_a_stored_total() {
+        let script = vec![
+            vec![("eth0", 0, 0)],
+            vec![("eth0", 0, 0)],
+            vec![("eth0", 1_000, 0)],
+        ];
+        let mut manager = NetworkManager::new(ScriptedSource::new(script));
+        manager.add_retention_presets(&[Duration::from_secs(24 * 3600)]);
+        // from the default of 5 minutes over 15 minutes and 1 hour
+        for _ in 0..3 {
+            manager.cycle_retention();
+        }
+        assert_eq!(manager.retention(), Duration::from_secs(6 * 3600));
+        manager.cycle_retention();
+        assert_eq!(manager.retention(), Duration::from_secs(24 * 3600));
+
+        manager.format_network_at(10_000.0);
+        manager.format_network_at(10_001.0);
+        manager.seed_history(&[(9_000.0, 500.0, 100.0), (10_001.0, 1.0, 1.0)]);
+        let series = manager.chart_series();
+        let stored = series.last().unwrap();
+        assert_eq!(stored.kind, SeriesKind::Stored);
+        assert_eq!(stored.download, vec![(9_000.0, 500.0)]);
+        assert_eq!(stored.upload, vec![(9_000.0, -100.0)]);
+
+        // the stored total counts for the scale, but not for the live throughput
+        assert_eq!(manager.chart_scale().0, "B/s");
+        assert_eq!(manager.throughput()[0].download, Some(1_000.0));
+        manager.clear_history();
+        assert!(
+            manager
+                .chart_series()
+                .iter()
+                .all(|s| s.kind != SeriesKind::Stored)
+        );
+    }
+
+    #[test]
+    fn test_manager_cycles_interfaces_in_order() {
+        let source = ScriptedSource::new(vec![vec![("wlan0", 0, 0), ("eth0", 0, 0), ("lo", 0, 0)]]);
+        let mut manager = NetworkManager::new(source);
+        assert_eq!(manager.get_selected_interface(), "eth0");
+
+        manager.select_next_interface();
+        assert_eq!(manager.get_selected_interface(), "lo");
+        manager.select_next_interface();
+        assert_eq!(manager.get_selected_interface(), "wlan0");
+        manager.select_next_interface();
+        assert_eq!(manager.get_selected_interface(), "eth0");
+    }
+
+    #[test]
+    fn test_manager_chart_scale_follows_largest_rate() {
+        let source = ScriptedSource::new(vec![
+            vec![("eth0", 0, 0)],
+            vec![("eth0", 0, 0)],
+            vec![("eth0", 512, 0)],
+            vec![("eth0", 512, 3 * 1024 * 1024)],
+        ]);
+        let mut manager = NetworkManager::new(source);
+        assert_eq!(manager.chart_scale(), ("B/s", 1.0));
+
+        manager.format_network_at(0.0);
+        manager.format_network_at(1.0);
+        assert_eq!(manager.chart_scale(), ("B/s", 1.0));
+
+        // upload is stored negative, it still determines the scale
+        manager.format_network_at(2.0);
+        assert_eq!(manager.chart_scale(), ("MB/s", 1024.0 * 1024.0));
+    }
+
+    #[test]
+    fn test_manager_picks_up_new_interfaces() {
+        let source = ScriptedSource::new(vec![
+            vec![("eth0", 0, 0)],
+            vec![("eth0", 0, 0)],
+            vec![("eth0", 100, 0), ("tun0", 5_000_000, 0)],
+            vec![("eth0", 200, 0), ("tun0", 5_001_000, 0)],
+        ]);
+        let mut manager = NetworkManager::new(source);
+        manager.format_network_at(0.0);
+        assert_eq!(manager.network_history_keys(), vec!["eth0"]);
+
+        // the new interface gets a history but no rate for its first sample
+        let text = manager.format_network_at(1.0);
+        assert!(text.contains("tun0: measuring..."));
+        assert_eq!(manager.network_history_keys(), vec!["eth0", "tun0"]);
+        assert!(manager.history("tun0").unwrap().0.is_empty());
+
+        manager.format_network_at(2.0);
+        let (download, _) = manager.history("tun0").unwrap();
+        assert_eq!(download.points(), vec![(2.0, 1_000.0)]);
+
+        manager.select_next_interface();
+        assert_eq!(manager.get_selected_interface(), "tun0");
+    }
+
+    #[test]
+    fn test_manager_retires_removed_interfaces_and_keeps_selection_valid() {
+        let source = ScriptedSource::new(vec![
+            vec![("eth0", 0, 0), ("tun0", 0, 0)],
+            vec![("eth0", 0, 0), ("tun0", 0, 0)],
+            vec![("eth0", 0, 0)],
+        ]);
+        let mut manager = NetworkManager::new(source);
+        manager.set_selected_interface("tun0".to_string());
+        manager.format_network_at(0.0);
+        assert_eq!(manager.get_selected_interface(), "tun0");
+
+        manager.format_network_at(1.0);
+        assert_eq!(manager.network_history_keys(), vec!["eth0"]);
+        assert!(manager.history("tun0").is_none());
+        assert_eq!(manager.get_selected_interface(), "eth0");
+    }
+
+    #[test]
+    fn test_manager_without_interfaces_at_startup() {
+        let source = ScriptedSource::new(vec![vec![], vec![("usb0", 0, 0)]]);
+        let mut manager = NetworkManager::new(source);
+        assert_eq!(manager.get_selected_interface(), "");
+
+        manager.format_network_at(0.0);
+        assert_eq!(manager.get_selected_interface(), "usb0");
+    }
+}
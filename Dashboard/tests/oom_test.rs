@@ -0,0 +1,88 @@
+//! Tests for the OOM tracker
+#[cfg(test)]
+mod tests {
+    use chrono::Local;
+    use linux_dashboard::backend::oom::{OomTracker, TrackedProcess, read_cgroup_oom_counts};
+    use std::collections::HashMap;
+    use std::fs;
+
+    fn process(pid: u32, name: &str, memory: u64) -> TrackedProcess {
+        TrackedProcess {
+            pid,
+            name: name.to_string(),
+            memory,
+        }
+    }
+
+    #[test]
+    fn test_system_oom_kill_names_largest_disappeared_process() {
+        let mut tracker = OomTracker::new("/nonexistent");
+        let no_cgroups = HashMap::new();
+        let before = vec![
+            process(10, "bash", 1_000),
+            process(20, "postgres", 8_000_000),
+            process(30, "cc1plus", 2_000_000),
+        ];
+        tracker.update(Some(5), &no_cgroups, before, Local::now());
+        assert!(tracker.events().is_empty());
+
+        let after = vec![process(10, "bash", 1_000)];
+        tracker.update(Some(6), &no_cgroups, after, Local::now());
+
+        let event = tracker.latest_event().expect("an OOM event");
+        assert_eq!(event.source, "system");
+        assert_eq!(event.kills, 1);
+        assert_eq!(event.victim, Some(process(20, "postgres", 8_000_000)));
+        assert!(event.describe().contains("postgres (PID 20)"));
+    }
+
+    #[test]
+    fn test_process_exit_without_counter_change_is_no_event() {
+        let mut tracker = OomTracker::new("/nonexistent");
+        let no_cgroups = HashMap::new();
+        tracker.update(Some(5), &no_cgroups, vec![process(1, "a", 1)], Local::now());
+        tracker.update(Some(5), &no_cgroups, vec![], Local::now());
+        assert!(tracker.events().is_empty());
+    }
+
+    #[test]
+    fn test_cgroup_kill_is_reported_once_for_deepest_cgroup() {
+        let mut tracker = OomTracker::new("/nonexistent");
+        let mut counts = HashMap::from([
+            ("/".to_string(), 0),
+            ("/ci.slice".to_string(), 0),
+            ("/ci.slice/job-1".to_string(), 0),
+        ]);
+        tracker.update(
+            Some(0),
+            &counts,
+            vec![process(7, "rustc", 100)],
+            Local::now(),
+        );
+
+        counts.insert("/".to_string(), 1);
+        counts.insert("/ci.slice".to_string(), 1);
+        counts.insert("/ci.slice/job-1".to_string(), 1);
+        tracker.update(Some(1), &counts, vec![], Local::now());
+
+        assert_eq!(tracker.events().len(), 1);
+        let event = &tracker.events()[0];
+        assert_eq!(event.source, "/ci.slice/job-1");
+        assert_eq!(event.victim.as_ref().map(|p| p.pid), Some(7));
+    }
+
+    #[test]
+    fn test_read_cgroup_oom_counts() {
+        let root = std::env::temp_dir().join(format!("dashboard-oom-{}", std::process::id()));
+        let child = root.join("system.slice");
+        fs::create_dir_all(&child).unwrap();
+        fs::write(root.join("memory.events"), "low 0\noom 1\noom_kill 1\n").unwrap();
+        fs::write(child.join("memory.events"), "oom 4\noom_kill 3\n").unwrap();
+
+        let counts = read_cgroup_oom_counts(&root);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(counts.get("/"), Some(&1));
+        assert_eq!(counts.get("/system.slice"), Some(&3));
+    }
+}
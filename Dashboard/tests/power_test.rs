@@ -0,0 +1,101 @@
+//! Tests for the power supply module, using fixture files
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::{fake_sysfs, write};
+    use linux_dashboard::backend::power::{BatteryState, PowerSupplyReader};
+    use linux_dashboard::ui::widgets::power::power_status_spans;
+    use std::fs;
+    use std::time::Duration;
+
+    #[test]
+    fn test_discharging_energy_battery() {
+        let root = fake_sysfs("energy");
+        write(&root, "class/power_supply/AC/type", "Mains\n");
+        write(&root, "class/power_supply/AC/online", "0\n");
+        write(&root, "class/power_supply/BAT0/type", "Battery\n");
+        write(&root, "class/power_supply/BAT0/status", "Discharging\n");
+        write(&root, "class/power_supply/BAT0/capacity", "50\n");
+        write(&root, "class/power_supply/BAT0/energy_now", "20000000\n");
+        write(&root, "class/power_supply/BAT0/energy_full", "40000000\n");
+        write(&root, "class/power_supply/BAT0/power_now", "10000000\n");
+
+        let status = PowerSupplyReader::new(&root).read_status();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(status.ac_online, Some(false));
+        let battery = &status.batteries[0];
+        assert_eq!(battery.name, "BAT0");
+        assert_eq!(battery.capacity, 50.0);
+        assert_eq!(battery.state, BatteryState::Discharging);
+        assert_eq!(battery.power_watts, Some(10.0));
+        // 20 Wh at 10 W
+        assert_eq!(battery.time_remaining, Some(Duration::from_secs(7200)));
+
+        let text: String = power_status_spans(&status)
+            .iter()
+            .map(|s| s.content.to_string())
+            .collect();
+        assert!(text.contains("BAT0 50% Discharging 10.0 W 2h 00m left"));
+        assert!(text.contains("AC offline"));
+    }
+
+    #[test]
+    fn test_charging_charge_battery_uses_voltage() {
+        let root = fake_sysfs("charge");
+        write(&root, "class/power_supply/ADP1/type", "Mains\n");
+        write(&root, "class/power_supply/ADP1/online", "1\n");
+        write(&root, "class/power_supply/BAT1/type", "Battery\n");
+        write(&root, "class/power_supply/BAT1/status", "Charging\n");
+        write(&root, "class/power_supply/BAT1/charge_now", "1000000\n");
+        write(&root, "class/power_supply/BAT1/charge_full", "4000000\n");
+        write(&root, "class/power_supply/BAT1/current_now", "1500000\n");
+        write(&root, "class/power_supply/BAT1/voltage_now", "10000000\n");
+        // peripheral batteries are not shown
+        write(&root, "class/power_supply/hid-mouse/type", "Battery\n");
+        write(&root, "class/power_supply/hid-mouse/scope", "Device\n");
+
+        let status = PowerSupplyReader::new(&root).read_status();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(status.ac_online, Some(true));
+        assert_eq!(status.batteries.len(), 1);
+        let battery = &status.batteries[0];
+        // no capacity file, 1 Ah of 4 Ah
+        assert_eq!(battery.capacity, 25.0);
+        assert_eq!(battery.power_watts, Some(15.0));
+        // 30 Wh missing at 15 W
+        assert_eq!(battery.time_remaining, Some(Duration::from_secs(7200)));
+    }
+
+    #[test]
+    fn test_no_battery_hides_status() {
+        let root = fake_sysfs("desktop");
+        write(&root, "class/power_supply/AC/type", "Mains\n");
+        write(&root, "class/power_supply/AC/online", "1\n");
+
+        let status = PowerSupplyReader::new(&root).read_status();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert!(!status.has_battery());
+        assert!(power_status_spans(&status).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_firmware_values_give_no_time() {
+        let root = fake_sysfs("firmware");
+        write(&root, "class/power_supply/BAT0/type", "Battery\n");
+        write(&root, "class/power_supply/BAT0/status", "Discharging\n");
+        write(&root, "class/power_supply/BAT0/energy_now", "-1\n");
+        write(&root, "class/power_supply/BAT0/energy_full", "nan\n");
+        write(&root, "class/power_supply/BAT0/power_now", "10000000\n");
+
+        let status = PowerSupplyReader::new(&root).read_status();
+        fs::remove_dir_all(&root).unwrap();
+
+        let battery = &status.batteries[0];
+        assert_eq!(battery.power_watts, Some(10.0));
+        assert_eq!(battery.time_remaining, None);
+    }
+}
@@ -0,0 +1,137 @@
+//! Tests for the per-process network estimation, using a fake procfs tree
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::fake_proc;
+    use linux_dashboard::backend::connections::ConnectionReader;
+    use linux_dashboard::backend::process_network::{AttributionMethod, ProcessNetworkTracker};
+    use std::fs;
+    use std::os::unix::fs::symlink;
+    use std::path::{Path, PathBuf};
+
+    const HOST_NS: &str = "net:[4026531840]";
+    const CONTAINER_NS: &str = "net:[4026532500]";
+
+    /// Returns a fake procfs tree where the dashboard (`self`) and the init process
+    /// are in the host namespace.
+    fn fake_host(name: &str) -> PathBuf {
+        let root = fake_proc(name);
+        fs::create_dir_all(root.join("self/ns")).unwrap();
+        symlink(HOST_NS, root.join("self/ns/net")).unwrap();
+        add_process(&root, 1, HOST_NS);
+        root
+    }
+
+    fn add_process(root: &Path, pid: u32, namespace: &str) {
+        let dir = root.join(pid.to_string());
+        fs::create_dir_all(dir.join("ns")).unwrap();
+        fs::create_dir_all(dir.join("fd")).unwrap();
+        symlink(namespace, dir.join("ns/net")).unwrap();
+    }
+
+    fn write_net_dev(root: &Path, pid: u32, received: u64, transmitted: u64) {
+        let content = format!(
+            "Inter-|   Receive |  Transmit\n face |bytes packets|bytes packets\n    lo: 999 1 0 0 0 0 0 0 999 1 0 0 0 0 0 0\n  eth0: {received} 10 0 0 0 0 0 0 {transmitted} 12 0 0 0 0 0 0\n"
+        );
+        fs::create_dir_all(root.join(format!("{pid}/net"))).unwrap();
+        fs::write(root.join(format!("{pid}/net/dev")), content).unwrap();
+    }
+
+    fn write_io(root: &Path, pid: u32, rchar: u64, wchar: u64) {
+        fs::write(
+            root.join(format!("{pid}/io")),
+            format!("rchar: {rchar}\nwchar: {wchar}\nsyscr: 1\nsyscw: 1\nread_bytes: 0\n"),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_namespaced_processes_share_namespace_traffic() {
+        let root = fake_host("netns");
+        add_process(&root, 500, CONTAINER_NS);
+        add_process(&root, 501, CONTAINER_NS);
+        write_net_dev(&root, 500, 1_000, 0);
+        write_net_dev(&root, 501, 1_000, 0);
+
+        let mut tracker = ProcessNetworkTracker::new(&root);
+        assert!(tracker.update(10.0, &[]).is_empty());
+
+        write_net_dev(&root, 500, 5_000, 2_000);
+        write_net_dev(&root, 501, 5_000, 2_000);
+        let bandwidth = tracker.update(12.0, &[]);
+        fs::remove_dir_all(&root).unwrap();
+
+        for pid in [500, 501] {
+            let process = bandwidth[&pid];
+            assert_eq!(process.method, AttributionMethod::Namespace);
+            assert_eq!(process.download, 2_000.0);
+            assert_eq!(process.upload, 1_000.0);
+        }
+        // PID 1 is in the host namespace and owns no sockets
+        assert!(!bandwidth.contains_key(&1));
+    }
+
+    #[test]
+    fn test_socket_owners_use_io_counters_as_proxy() {
+        let root = fake_host("io");
+        fs::write(
+            root.join("net/tcp"),
+            "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n   0: 0100007F:B4C2 0100007F:1F90 01 00000000:00000000 00:00000000 00000000  1000        0 7001 1\n",
+        )
+        .unwrap();
+        add_process(&root, 300, HOST_NS);
+        symlink("socket:[7001]", root.join("300/fd/3")).unwrap();
+        write_io(&root, 300, 100, 100);
+        // same namespace, no socket: no estimate although its I/O is readable
+        add_process(&root, 400, HOST_NS);
+        write_io(&root, 400, 0, 0);
+
+        let connections = ConnectionReader::new(&root).read_connections();
+        let mut tracker = ProcessNetworkTracker::new(&root);
+        tracker.update(0.0, &connections);
+        write_io(&root, 300, 600, 350);
+        write_io(&root, 400, 10_000, 10_000);
+        let bandwidth = tracker.update(0.5, &connections);
+        fs::remove_dir_all(&root).unwrap();
+
+        let process = bandwidth[&300];
+        assert_eq!(process.method, AttributionMethod::IoProxy);
+        assert_eq!(process.method.label(), "io~");
+        assert_eq!(process.download, 1_000.0);
+        assert_eq!(process.upload, 500.0);
+        assert_eq!(process.total(), 1_500.0);
+        assert!(!bandwidth.contains_key(&400));
+    }
+
+    #[test]
+    fn test_unreadable_host_namespace_skips_namespace_attribution() {
+        // e.g. a restricted procfs: the namespace of the dashboard can not be read
+        let root = fake_proc("no-self");
+        fs::write(
+            root.join("net/tcp"),
+            "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n   0: 0100007F:B4C2 0100007F:1F90 01 00000000:00000000 00:00000000 00000000  1000        0 7001 1\n",
+        )
+        .unwrap();
+        add_process(&root, 500, CONTAINER_NS);
+        write_net_dev(&root, 500, 1_000, 0);
+        symlink("socket:[7001]", root.join("500/fd/3")).unwrap();
+        write_io(&root, 500, 0, 0);
+        add_process(&root, 501, HOST_NS);
+        write_net_dev(&root, 501, 1_000, 0);
+
+        let connections = ConnectionReader::new(&root).read_connections();
+        let mut tracker = ProcessNetworkTracker::new(&root);
+        tracker.update(0.0, &connections);
+        write_net_dev(&root, 500, 9_000, 9_000);
+        write_net_dev(&root, 501, 9_000, 9_000);
+        write_io(&root, 500, 100, 0);
+        let bandwidth = tracker.update(1.0, &connections);
+        fs::remove_dir_all(&root).unwrap();
+
+        // no process gets the traffic of a namespace, the socket owner falls back to its I/O
+        assert_eq!(bandwidth.len(), 1);
+        assert_eq!(bandwidth[&500].method, AttributionMethod::IoProxy);
+        assert_eq!(bandwidth[&500].download, 100.0);
+    }
+}
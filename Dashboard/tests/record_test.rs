@@ -0,0 +1,256 @@
+//! Tests for recording samples to a file and replaying them
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::args;
+    use flate2::read::MultiGzDecoder;
+    use linux_dashboard::backend::host::HostInfo;
+    use linux_dashboard::backend::processes::{
+        ProcessInfo, SortOrder, process_position, sort_processes,
+    };
+    use linux_dashboard::backend::sample::Sample;
+    use linux_dashboard::record::{
+        RECORDING_VERSION, RecordOptions, Recorder, Recording, RecordingHeader,
+    };
+    use linux_dashboard::replay::Playback;
+    use std::io::{Cursor, Read};
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    fn header() -> RecordingHeader {
+        RecordingHeader {
+            version: RECORDING_VERSION,
+            host: HostInfo {
+                system_name: "Linux".to_string(),
+                kernel_version: "6.1.0".to_string(),
+                os_version: "12".to_string(),
+                host_name: "testhost".to_string(),
+            },
+            interval_ms: 1000,
+        }
+    }
+
+    fn process(pid: u32, name: &str, cpu_usage: f32) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: name.to_string(),
+            status: "Run".to_string(),
+            cpu_usage,
+            memory: pid as u64 * 1024,
+            network: None,
+        }
+    }
+
+    fn sample(timestamp: f64) -> Sample {
+        Sample {
+            timestamp,
+            uptime_seconds: 100 + timestamp as u64,
+            processes: vec![process(1, "init", 0.5), process(42, "worker", 12.0)],
+            ..Default::default()
+        }
+    }
+
+    /// One sample per second, starting at 1000 seconds
+    fn samples(count: usize) -> Vec<Sample> {
+        (0..count).map(|i| sample(1000.0 + i as f64)).collect()
+    }
+
+    fn recording(count: usize) -> Recording {
+        Recording::read(Cursor::new(record(&samples(count)))).unwrap()
+    }
+
+    fn read_all(recording: &mut Recording) -> Vec<Sample> {
+        recording.samples(0..recording.len()).unwrap()
+    }
+
+    fn record(samples: &[Sample]) -> Vec<u8> {
+        let mut recorder = Recorder::new(Vec::new(), &header()).unwrap();
+        for sample in samples {
+            recorder.write_sample(sample).unwrap();
+        }
+        recorder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_options_from_args() {
+        let options = RecordOptions::from_args(&args(&["out.gz", "--interval", "2000"])).unwrap();
+        assert_eq!(options.path, PathBuf::from("out.gz"));
+        assert_eq!(options.interval, Duration::from_millis(2000));
+
+        // too short intervals are raised to the minimum CPU update interval
+        let options = RecordOptions::from_args(&args(&["out.gz", "--interval", "1"])).unwrap();
+        assert_eq!(options.interval, sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+
+        assert!(RecordOptions::from_args(&[]).is_err());
+        assert!(RecordOptions::from_args(&args(&["a.gz", "b.gz"])).is_err());
+        assert!(RecordOptions::from_args(&args(&["out.gz", "--interval"])).is_err());
+        assert!(RecordOptions::from_args(&args(&["out.gz", "--top", "3"])).is_err());
+    }
+
+    #[test]
+    fn test_recording_round_trip() {
+        let samples = samples(3);
+        let bytes = record(&samples);
+        // the file is gzip compressed
+        assert_eq!(&bytes[..2], &[0x1f, 0x8b]);
+
+        let mut read = Recording::read(Cursor::new(bytes)).unwrap();
+        assert_eq!(read.header.host.host_name, "testhost");
+        assert_eq!(read.header.interval_ms, 1000);
+        assert_eq!(read.timeline(), [1000.0, 1001.0, 1002.0]);
+        assert_eq!(read_all(&mut read), samples);
+    }
+
+    #[test]
+    fn test_unchanged_entries_are_written_as_indices() {
+        let mut samples = samples(3);
+        samples[2].processes[1].cpu_usage = 50.0;
+        samples[2].processes.insert(0, process(7, "new", 1.0));
+        let mut text = String::new();
+        MultiGzDecoder::new(&record(&samples)[..])
+            .read_to_string(&mut text)
+            .unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 4);
+        // the first sample of a chunk is complete
+        assert!(lines[1].contains("\"name\":\"worker\""));
+        assert!(lines[2].contains("\"processes\":[0,1]"));
+        assert!(lines[3].contains("\"processes\":[{\"pid\":7,"));
+        assert!(lines[3].contains("},0,{\"pid\":42,"));
+
+        let mut read = Recording::read(Cursor::new(record(&samples))).unwrap();
+        assert_eq!(read_all(&mut read), samples);
+    }
+
+    #[test]
+    fn test_read_chunks() {
+        // one chunk holds a minute of samples
+        let samples = samples(150);
+        let mut read = Recording::read(Cursor::new(record(&samples))).unwrap();
+        assert_eq!(read.len(), 150);
+        assert_eq!(read.samples(130..132).unwrap(), samples[130..132]);
+        assert_eq!(read.samples(10..12).unwrap(), samples[10..12]);
+        assert_eq!(read.samples(12..70).unwrap(), samples[12..70]);
+        assert_eq!(read.samples(149..200).unwrap(), samples[149..]);
+        assert!(read.samples(150..151).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_clock_set_back() {
+        let mut samples = samples(4);
+        samples[2].timestamp = 500.0;
+        let mut read = Recording::read(Cursor::new(record(&samples))).unwrap();
+        assert_eq!(read.timeline(), [1000.0, 1001.0, 1001.0, 1003.0]);
+        assert_eq!(read_all(&mut read)[2].timestamp, 500.0);
+
+        let mut playback = Playback::new(read);
+        assert_eq!(playback.seek(-100.0, Duration::ZERO), 0..1);
+        assert_eq!(playback.advance(Duration::from_secs(1)), 1..3);
+        assert_eq!(playback.seek(100.0, Duration::ZERO), 3..4);
+    }
+
+    #[test]
+    fn test_read_truncated_recording() {
+        let samples = samples(150);
+        let bytes = record(&samples);
+
+        // a stream cut off before the trailer keeps at least the complete chunks
+        let mut read = Recording::read(Cursor::new(bytes[..bytes.len() - 8].to_vec())).unwrap();
+        assert!(read.len() >= 120);
+        assert_eq!(read_all(&mut read), samples[..read.len()]);
+
+        // a half written last line is skipped
+        let mut text = String::new();
+        text.push_str(&serde_json::to_string(&header()).unwrap());
+        text.push('\n');
+        text.push_str(&serde_json::to_string(&samples[0]).unwrap());
+        text.push('\n');
+        let partial = serde_json::to_string(&samples[1]).unwrap();
+        text.push_str(&partial[..partial.len() / 2]);
+        let mut read = Recording::read(Cursor::new(text)).unwrap();
+        assert_eq!(read_all(&mut read), samples[..1]);
+    }
+
+    #[test]
+    fn test_read_invalid_recording() {
+        assert!(Recording::read(Cursor::new("")).is_err());
+        assert!(Recording::read(Cursor::new("not json\n")).is_err());
+
+        // a broken line in the middle is reported with its line number
+        let header_line = serde_json::to_string(&header()).unwrap();
+        let sample_line = serde_json::to_string(&sample(1000.0)).unwrap();
+        let text = format!("{header_line}\n{{broken\n{sample_line}\n");
+        let error = Recording::read(Cursor::new(text)).unwrap_err();
+        assert!(error.to_string().starts_with("line 2:"));
+
+        // recordings of a newer format are rejected
+        let mut newer = header();
+        newer.version = RECORDING_VERSION + 1;
+        let text = serde_json::to_string(&newer).unwrap();
+        assert!(Recording::read(Cursor::new(text)).is_err());
+    }
+
+    #[test]
+    fn test_playback_advance() {
+        let mut playback = Playback::new(recording(10));
+        assert_eq!(playback.cursor(), 1000.0);
+        // the first sample is due right away
+        assert_eq!(playback.advance(Duration::ZERO), 0..1);
+
+        assert_eq!(playback.advance(Duration::from_millis(2500)), 1..3);
+        assert_eq!(playback.cursor(), 1002.5);
+
+        playback.faster();
+        assert_eq!(playback.speed(), 2.0);
+        assert_eq!(playback.advance(Duration::from_secs(1)), 3..5);
+
+        playback.toggle_pause();
+        assert_eq!(playback.advance(Duration::from_secs(5)), 5..5);
+        playback.toggle_pause();
+
+        // the playback pauses at the end of the recording
+        assert_eq!(playback.advance(Duration::from_secs(60)), 5..10);
+        assert!(playback.is_finished());
+        assert!(playback.is_paused());
+        assert_eq!(playback.cursor(), 1009.0);
+    }
+
+    #[test]
+    fn test_playback_seek() {
+        let mut playback = Playback::new(recording(10));
+        playback.advance(Duration::ZERO);
+
+        // the samples within the window before the cursor are returned to rebuild the histories
+        assert_eq!(playback.seek(6.0, Duration::from_secs(3)), 4..7);
+        assert_eq!(playback.position(), 7);
+        assert_eq!(playback.samples(4..7), samples(10)[4..7]);
+
+        // seeking is clamped to the recording, at least the current sample is returned
+        assert_eq!(playback.seek(-100.0, Duration::from_secs(3)), 0..1);
+        assert_eq!(playback.cursor(), 1000.0);
+        assert_eq!(playback.seek(f64::INFINITY, Duration::ZERO), 9..10);
+        assert!(playback.is_finished());
+
+        let mut empty = Playback::new(recording(0));
+        assert_eq!(empty.advance(Duration::from_secs(1)), 0..0);
+        assert_eq!(empty.seek(5.0, Duration::from_secs(3)), 0..0);
+    }
+
+    #[test]
+    fn test_sort_recorded_processes() {
+        let processes = sample(1000.0).processes;
+        let sorted = sort_processes(&processes, SortOrder::CpuDesc, "");
+        assert_eq!(sorted[0].pid, 42);
+        let filtered = sort_processes(&processes, SortOrder::PidAsc, "WORK");
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(
+            process_position(&processes, SortOrder::CpuDesc, "", 1),
+            Some(1)
+        );
+        assert_eq!(
+            process_position(&processes, SortOrder::CpuDesc, "", 7),
+            None
+        );
+    }
+}
@@ -0,0 +1,103 @@
+//! Tests for the sensors module, using a fake sysfs tree
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::{fake_sysfs, write};
+    use linux_dashboard::backend::sensors::{SensorKind, SensorLevel, SensorReader};
+    use std::fs;
+
+    #[test]
+    fn test_reads_hwmon_temperatures_and_fans() {
+        let root = fake_sysfs("hwmon");
+        write(&root, "class/hwmon/hwmon0/name", "coretemp\n");
+        write(&root, "class/hwmon/hwmon0/temp1_label", "Package id 0\n");
+        write(&root, "class/hwmon/hwmon0/temp1_input", "45000\n");
+        write(&root, "class/hwmon/hwmon0/temp1_max", "80000\n");
+        write(&root, "class/hwmon/hwmon0/temp1_crit", "100000\n");
+        write(&root, "class/hwmon/hwmon0/temp2_label", "Core 0\n");
+        write(&root, "class/hwmon/hwmon0/temp2_input", "85000\n");
+        write(&root, "class/hwmon/hwmon0/temp2_max", "80000\n");
+        write(&root, "class/hwmon/hwmon10/name", "nct6775\n");
+        write(&root, "class/hwmon/hwmon10/fan1_input", "1200\n");
+        write(&root, "class/hwmon/hwmon10/fan1_min", "300\n");
+        write(&root, "class/hwmon/hwmon10/fan1_max", "1200\n");
+        write(&root, "class/hwmon/hwmon10/fan2_input", "0\n");
+        write(&root, "class/hwmon/hwmon10/fan2_min", "300\n");
+        write(&root, "class/hwmon/hwmon10/fan3_input", "0\n");
+        write(&root, "class/hwmon/hwmon10/fan3_min", "0\n");
+        write(&root, "class/hwmon/hwmon2/name", "nvme\n");
+        write(&root, "class/hwmon/hwmon2/temp1_input", "101500\n");
+        write(&root, "class/hwmon/hwmon2/temp1_crit", "100000\n");
+
+        let sensors = SensorReader::new(&root).read_sensors();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(sensors.len(), 6);
+        assert_eq!(sensors[0].chip, "coretemp");
+        assert_eq!(sensors[0].label, "Package id 0");
+        assert_eq!(sensors[0].value, 45.0);
+        assert_eq!(sensors[0].high, Some(80.0));
+        assert_eq!(sensors[0].critical, Some(100.0));
+        assert_eq!(sensors[0].level(), SensorLevel::Normal);
+        assert_eq!(sensors[1].level(), SensorLevel::High);
+        // hwmon2 is sorted before hwmon10
+        assert_eq!(sensors[2].chip, "nvme");
+        assert_eq!(sensors[2].label, "temp1");
+        assert_eq!(sensors[2].level(), SensorLevel::Critical);
+        assert_eq!(sensors[3].kind, SensorKind::Fan);
+        assert_eq!(sensors[3].value, 1200.0);
+        assert_eq!(sensors[3].format_value(sensors[3].value), "1200 RPM");
+        // a fan at full speed is fine, a stalled fan is not, unless it has no minimum
+        assert_eq!(sensors[3].low, Some(300.0));
+        assert_eq!(sensors[3].high, None);
+        assert_eq!(sensors[3].level(), SensorLevel::Normal);
+        assert_eq!(sensors[4].level(), SensorLevel::High);
+        assert_eq!(sensors[5].low, None);
+        assert_eq!(sensors[5].level(), SensorLevel::Normal);
+    }
+
+    #[test]
+    fn test_reads_thermal_zones_with_trip_points() {
+        let root = fake_sysfs("thermal");
+        write(&root, "class/thermal/thermal_zone0/type", "x86_pkg_temp\n");
+        write(&root, "class/thermal/thermal_zone0/temp", "52000\n");
+        write(
+            &root,
+            "class/thermal/thermal_zone0/trip_point_0_type",
+            "passive\n",
+        );
+        write(
+            &root,
+            "class/thermal/thermal_zone0/trip_point_0_temp",
+            "90000\n",
+        );
+        write(
+            &root,
+            "class/thermal/thermal_zone0/trip_point_1_type",
+            "critical\n",
+        );
+        write(
+            &root,
+            "class/thermal/thermal_zone0/trip_point_1_temp",
+            "105000\n",
+        );
+        // cooling devices live in the same directory and must be ignored
+        write(&root, "class/thermal/cooling_device0/type", "Processor\n");
+
+        let sensors = SensorReader::new(&root).read_sensors();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(sensors.len(), 1);
+        assert_eq!(sensors[0].label, "x86_pkg_temp");
+        assert_eq!(sensors[0].value, 52.0);
+        assert_eq!(sensors[0].high, Some(90.0));
+        assert_eq!(sensors[0].critical, Some(105.0));
+    }
+
+    #[test]
+    fn test_missing_sysfs_root_yields_no_sensors() {
+        let sensors = SensorReader::new("/nonexistent/sysfs").read_sensors();
+        assert!(sensors.is_empty());
+    }
+}
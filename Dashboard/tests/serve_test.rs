@@ -0,0 +1,187 @@
+//! Tests for the Prometheus exporter (serve mode)
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::args;
+    use linux_dashboard::backend::cpu::CpuStats;
+    use linux_dashboard::backend::disk::DiskInfo;
+    use linux_dashboard::backend::memory::MemInfo;
+    use linux_dashboard::backend::network::InterfaceThroughput;
+    use linux_dashboard::backend::processes::ProcessInfo;
+    use linux_dashboard::metrics::MetricsSample;
+    use linux_dashboard::serve::{MAX_CONNECTIONS, MetricsServer, REQUEST_TIMEOUT, ServeOptions};
+    use std::net::SocketAddr;
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    fn sample() -> MetricsSample {
+        MetricsSample {
+            cpu: CpuStats {
+                name: "Test CPU".to_string(),
+                total_usage: 37.5,
+                cores: vec![25.0, 50.0],
+            },
+            memory: MemInfo {
+                total: 8000,
+                free: 1000,
+                available: 3000,
+                buffers: 500,
+                cached: 1500,
+                swap_total: 2000,
+                swap_free: 1500,
+                ..Default::default()
+            },
+            disks: vec![DiskInfo {
+                name: "/dev/sda1".to_string(),
+                mount_point: "/".to_string(),
+                file_system: "ext4".to_string(),
+                total: 100,
+                available: 40,
+            }],
+            network: vec![
+                InterfaceThroughput {
+                    name: "eth0".to_string(),
+                    download: Some(2048.0),
+                    upload: Some(512.0),
+                },
+                InterfaceThroughput {
+                    name: "wlan0".to_string(),
+                    download: None,
+                    upload: None,
+                },
+            ],
+            interfaces: Vec::new(),
+            processes: vec![ProcessInfo {
+                pid: 42,
+                name: "we\"ird".to_string(),
+                status: "Run".to_string(),
+                cpu_usage: 12.5,
+                memory: 4096,
+                network: None,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_options_from_args() {
+        assert_eq!(
+            ServeOptions::from_args(&[]).unwrap(),
+            ServeOptions::default()
+        );
+
+        let options = ServeOptions::from_args(&args(&[
+            "--listen",
+            "0.0.0.0:9100",
+            "--interval",
+            "2000",
+            "--top",
+            "3",
+        ]))
+        .unwrap();
+        assert_eq!(
+            options.listen,
+            "0.0.0.0:9100".parse::<SocketAddr>().unwrap()
+        );
+        assert_eq!(options.interval, Duration::from_millis(2000));
+        assert_eq!(options.top, 3);
+
+        assert!(ServeOptions::from_args(&args(&["--listen", "localhost"])).is_err());
+        assert!(ServeOptions::from_args(&args(&["--interval"])).is_err());
+        assert!(ServeOptions::from_args(&args(&["--port", "1"])).is_err());
+    }
+
+    #[test]
+    fn test_encode_metrics() {
+        let text = sample().encode();
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert!(lines.contains(&"# TYPE dashboard_cpu_usage_percent gauge"));
+        assert!(lines.contains(&"dashboard_cpu_usage_percent 37.5"));
+        assert!(lines.contains(&"dashboard_cpu_core_usage_percent{core=\"1\"} 50"));
+        assert!(lines.contains(&"dashboard_memory_used_bytes 5000"));
+        assert!(lines.contains(&"dashboard_swap_used_bytes 500"));
+        assert!(lines.contains(
+            &"dashboard_disk_available_bytes{device=\"/dev/sda1\",mount_point=\"/\",file_system=\"ext4\"} 40"
+        ));
+        assert!(
+            lines.contains(&"dashboard_network_receive_bytes_per_second{interface=\"eth0\"} 2048")
+        );
+        assert!(lines.contains(&"# TYPE dashboard_network_receive_packets_total counter"));
+        // interfaces without a rate yet are left out
+        assert!(!text.contains("wlan0"));
+        // quotes in label values are escaped
+        assert!(
+            lines.contains(&"dashboard_process_memory_bytes{pid=\"42\",name=\"we\\\"ird\"} 4096")
+        );
+
+        // every sample belongs to a family declared before it
+        let mut declared = Vec::new();
+        for line in lines {
+            if let Some(rest) = line.strip_prefix("# TYPE ") {
+                declared.push(rest.split(' ').next().unwrap().to_string());
+            } else if !line.starts_with('#') {
+                let name = line.split(['{', ' ']).next().unwrap();
+                assert_eq!(declared.last().map(String::as_str), Some(name));
+            }
+        }
+    }
+
+    async fn get(address: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        let request = format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n");
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_server_answers_metrics_requests() {
+        let server = MetricsServer::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let address = server.local_addr().unwrap();
+        *server.page().write().unwrap() = sample().encode();
+        let handle = tokio::spawn(server.run());
+
+        let response = get(address, "/metrics?debug=1").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("Content-Type: text/plain; version=0.0.4"));
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        assert_eq!(body, sample().encode());
+
+        let response = get(address, "/missing").await;
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+
+        handle.abort();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_idle_clients_time_out() {
+        let server = MetricsServer::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let address = server.local_addr().unwrap();
+        let handle = tokio::spawn(server.run());
+
+        // clients that never send a request take all connection slots
+        let started = tokio::time::Instant::now();
+        let mut idle = Vec::new();
+        for _ in 0..MAX_CONNECTIONS {
+            idle.push(TcpStream::connect(address).await.unwrap());
+        }
+        // the next request is served once they timed out
+        let response = get(address, "/").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(started.elapsed() >= REQUEST_TIMEOUT);
+        for mut stream in idle {
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            assert!(response.starts_with("HTTP/1.1 408 Request Timeout\r\n"));
+        }
+
+        handle.abort();
+    }
+}
@@ -0,0 +1,97 @@
+//! Tests for the headless snapshot mode
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::args;
+    use linux_dashboard::snapshot::{OutputFormat, Snapshot, SnapshotOptions, collect_report};
+    use serde_json::Value;
+    use std::time::Duration;
+
+    #[test]
+    fn test_options_from_args() {
+        assert_eq!(
+            SnapshotOptions::from_args(&[]).unwrap(),
+            SnapshotOptions::default()
+        );
+
+        let options =
+            SnapshotOptions::from_args(&args(&["--sample", "2500", "--top", "3"])).unwrap();
+        assert_eq!(options.top, 3);
+        assert_eq!(options.sample, Duration::from_millis(2500));
+
+        // too short windows are raised to the minimum CPU update interval
+        let options = SnapshotOptions::from_args(&args(&["--sample", "1"])).unwrap();
+        assert_eq!(options.sample, sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+
+        assert!(SnapshotOptions::from_args(&args(&["--top"])).is_err());
+        assert!(SnapshotOptions::from_args(&args(&["--top", "many"])).is_err());
+        assert!(SnapshotOptions::from_args(&args(&["--json"])).is_err());
+
+        let options = SnapshotOptions::from_args(&args(&["--format", "csv"])).unwrap();
+        assert_eq!(options.format, OutputFormat::Csv);
+        assert!(SnapshotOptions::from_args(&args(&["--format", "xml"])).is_err());
+    }
+
+    #[test]
+    fn test_report_contains_all_sections() {
+        let options = SnapshotOptions {
+            top: 2,
+            sample: sysinfo::MINIMUM_CPU_UPDATE_INTERVAL,
+            ..Default::default()
+        };
+        let report = collect_report(&options);
+
+        for section in [
+            "== Host ==",
+            "== CPU ==",
+            "== Memory ==",
+            "== Disks ==",
+            "== Network ==",
+            "== Top 2 processes by CPU ==",
+        ] {
+            assert!(report.contains(section), "missing {section}");
+        }
+        let process_lines = report
+            .split("== Top 2 processes by CPU ==\n")
+            .nth(1)
+            .unwrap()
+            .lines()
+            .count();
+        // header plus at most two processes
+        assert!(process_lines <= 3);
+    }
+
+    #[test]
+    fn test_json_and_csv_export() {
+        let snapshot = Snapshot::collect(&SnapshotOptions {
+            top: 3,
+            sample: sysinfo::MINIMUM_CPU_UPDATE_INTERVAL,
+            ..Default::default()
+        });
+
+        let json: Value = serde_json::from_str(&snapshot.to_json()).expect("valid JSON");
+        assert_eq!(json["host"]["host_name"], snapshot.host.host_name.as_str());
+        assert_eq!(json["memory"]["total"], snapshot.memory.total);
+        assert_eq!(
+            json["cpu"]["cores"].as_array().unwrap().len(),
+            snapshot.cpu.cores.len()
+        );
+        assert!(json["processes"].as_array().unwrap().len() <= 3);
+
+        let csv = snapshot.to_csv();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("section,item,metric,value"));
+        assert!(csv.contains(&format!("memory,,total,{}\n", snapshot.memory.total)));
+        for process in &snapshot.processes {
+            assert!(csv.contains(&format!(
+                "processes,{},memory,{}\n",
+                process.pid, process.memory
+            )));
+        }
+        // every row has exactly four columns unless a field is quoted
+        for line in lines.filter(|line| !line.contains('"')) {
+            assert_eq!(line.split(',').count(), 4, "{line}");
+        }
+    }
+}
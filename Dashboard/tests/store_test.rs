@@ -0,0 +1,236 @@
+//! Tests for the on-disk history store
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::temp_dir;
+    use linux_dashboard::backend::disk::DiskInfo;
+    use linux_dashboard::backend::network::InterfaceCounters;
+    use linux_dashboard::backend::network_source::InterfaceSample;
+    use linux_dashboard::backend::sample::Sample;
+    use linux_dashboard::config::Config;
+    use linux_dashboard::store::{DEFAULT_TIERS, HistoryPoint, HistorySampler, HistoryStore, Tier};
+    use std::fs;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    fn point(timestamp: f64, cpu: f64) -> HistoryPoint {
+        HistoryPoint {
+            timestamp,
+            cpu,
+            memory_total: 8e9,
+            memory_used: 2e9,
+            ..Default::default()
+        }
+    }
+
+    fn interface(name: &str, received: u64, transmitted: u64) -> InterfaceSample {
+        let mut sample = InterfaceSample {
+            counters: InterfaceCounters {
+                received,
+                transmitted,
+            },
+            ..Default::default()
+        };
+        sample.details.name = name.to_string();
+        sample
+    }
+
+    #[test]
+    fn test_parse_tiers() {
+        let tier = Tier::parse("24h@10s").unwrap();
+        assert_eq!(tier.retention, Duration::from_secs(86400));
+        assert_eq!(tier.resolution, Duration::from_secs(10));
+        assert_eq!(tier.slots(), 8640);
+        assert_eq!(tier.to_string(), "1d@10s");
+        assert_eq!(Tier::parse(" 90m @ 90s ").unwrap().to_string(), "90m@90s");
+
+        assert!(Tier::parse("24h").is_err());
+        assert!(Tier::parse("24h@0s").is_err());
+        assert!(Tier::parse("24h@1.5s").is_err());
+        assert!(Tier::parse("10s@1m").is_err());
+        assert!(Tier::parse("365d@1s").is_err());
+    }
+
+    #[test]
+    fn test_history_config() {
+        let config = Config::parse("").unwrap();
+        assert!(!config.history.enabled);
+        assert_eq!(config.history_tiers().unwrap(), DEFAULT_TIERS.to_vec());
+
+        let config = Config::parse(
+            "[history]\nenabled = true\npath = \"/var/lib/dashboard\"\ntiers = [\"1h@1s\", \"30d@5m\"]",
+        )
+        .unwrap();
+        assert!(config.history.enabled);
+        assert_eq!(
+            config.history_dir(),
+            Some(PathBuf::from("/var/lib/dashboard"))
+        );
+        let tiers = config.history_tiers().unwrap();
+        assert_eq!(tiers[1].retention, Duration::from_secs(30 * 86400));
+
+        assert!(Config::parse("[history]\ntiers = []").is_err());
+        assert!(Config::parse("[history]\ntiers = [\"1h\"]").is_err());
+        assert!(Config::parse("[history]\nsize = 5").is_err());
+    }
+
+    #[test]
+    fn test_push_and_read() {
+        let dir = temp_dir("store_push");
+        let tiers = [
+            Tier::parse("1m@10s").unwrap(),
+            Tier::parse("5m@1m").unwrap(),
+        ];
+        let mut store = HistoryStore::open(&dir, &tiers).unwrap();
+        assert!(store.is_writable());
+
+        // two samples per 10 second bucket are averaged
+        for i in 0..12 {
+            let timestamp = 60_000.0 + i as f64 * 5.0;
+            store.push(&point(timestamp, i as f64)).unwrap();
+        }
+        let points = store.read(Duration::from_secs(60), 60_060.0).unwrap();
+        let cpu: Vec<f64> = points.iter().map(|p| p.cpu).collect();
+        assert_eq!(cpu, vec![0.5, 2.5, 4.5, 6.5, 8.5, 10.5]);
+        assert_eq!(points[0].timestamp, 60_000.0);
+        assert_eq!(points[0].memory_used, 2e9);
+
+        // the longer range is read from the coarser tier
+        assert_eq!(store.tier_for(Duration::from_secs(300)), Some(tiers[1]));
+        let points = store.read(Duration::from_secs(300), 60_060.0).unwrap();
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].cpu, 5.5);
+
+        // the ring overwrites the oldest buckets
+        store.push(&point(60_065.0, 50.0)).unwrap();
+        let points = store.read(Duration::from_secs(60), 60_070.0).unwrap();
+        assert_eq!(points.len(), 6);
+        assert_eq!(points[0].timestamp, 60_010.0);
+        assert_eq!(points[5].cpu, 50.0);
+
+        // the history survives reopening, the bucket average continues
+        drop(store);
+        let mut store = HistoryStore::open(&dir, &tiers).unwrap();
+        store.push(&point(60_066.0, 0.0)).unwrap();
+        let points = store.read(Duration::from_secs(60), 60_070.0).unwrap();
+        assert_eq!(points[5].cpu, 25.0);
+
+        // the tier files have a fixed size
+        let size = fs::metadata(dir.join("10s-60s.ring")).unwrap().len();
+        assert_eq!(size, 32 + 6 * 88);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_single_writer() {
+        let dir = temp_dir("store_lock");
+        let tiers = [Tier::parse("1m@10s").unwrap()];
+        let mut writer = HistoryStore::open(&dir, &tiers).unwrap();
+        writer.push(&point(1000.0, 42.0)).unwrap();
+
+        // a second store is read only and sees the points of the writer
+        let mut reader = HistoryStore::open(&dir, &tiers).unwrap();
+        assert!(!reader.is_writable());
+        reader.push(&point(1010.0, 1.0)).unwrap();
+        let points = reader.read(Duration::from_secs(60), 1020.0).unwrap();
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].cpu, 42.0);
+
+        drop(writer);
+        drop(reader);
+        assert!(HistoryStore::open(&dir, &tiers).unwrap().is_writable());
+
+        // a file of another format is started anew
+        fs::write(dir.join("10s-60s.ring"), b"garbage").unwrap();
+        let reader = HistoryStore::open_read_only(&dir, &tiers).unwrap();
+        assert!(reader.tiers().is_empty());
+        let store = HistoryStore::open(&dir, &tiers).unwrap();
+        assert!(
+            store
+                .read(Duration::from_secs(60), 1020.0)
+                .unwrap()
+                .is_empty()
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_zoom_levels() {
+        let dir = temp_dir("store_zoom");
+        let store = HistoryStore::open(&dir, &DEFAULT_TIERS).unwrap();
+        let hours: Vec<u64> = store
+            .zoom_levels()
+            .iter()
+            .map(|level| level.as_secs() / 3600)
+            .collect();
+        assert_eq!(hours, vec![1, 6, 24, 72, 168]);
+        assert_eq!(
+            store.tier_for(Duration::from_secs(3600)),
+            Some(DEFAULT_TIERS[0])
+        );
+        assert_eq!(
+            store.tier_for(Duration::from_secs(30 * 86400)),
+            Some(DEFAULT_TIERS[1])
+        );
+
+        let store =
+            HistoryStore::open_read_only(&temp_dir("store_missing"), &DEFAULT_TIERS).unwrap();
+        assert!(store.zoom_levels().is_empty());
+        assert!(
+            store
+                .read(Duration::from_secs(3600), 1000.0)
+                .unwrap()
+                .is_empty()
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_sampler() {
+        let mut sampler = HistorySampler::default();
+        let mut sample = Sample {
+            timestamp: 1000.0,
+            interfaces: vec![interface("eth0", 1000, 500), interface("lo", 0, 0)],
+            disks: vec![
+                DiskInfo {
+                    name: "/dev/sda1".to_string(),
+                    mount_point: "/".to_string(),
+                    file_system: "ext4".to_string(),
+                    total: 100,
+                    available: 40,
+                },
+                DiskInfo {
+                    name: "/dev/sda1".to_string(),
+                    mount_point: "/var/lib/docker".to_string(),
+                    file_system: "ext4".to_string(),
+                    total: 100,
+                    available: 40,
+                },
+            ],
+            ..Default::default()
+        };
+        sample.cpu.total_usage = 30.0;
+        // no network rates yet
+        assert_eq!(sampler.point(&sample), None);
+
+        sample.timestamp = 1002.0;
+        sample.interfaces = vec![
+            interface("eth0", 3000, 1500),
+            interface("lo", 1 << 30, 1 << 30),
+        ];
+        let point = sampler.point(&sample).unwrap();
+        assert_eq!(point.timestamp, 1002.0);
+        assert_eq!(point.cpu, 30.0);
+        // loopback traffic is left out
+        assert_eq!(point.network_rx, 1000.0);
+        assert_eq!(point.network_tx, 500.0);
+        // the same device mounted twice is counted once
+        assert_eq!(point.disk_total, 100.0);
+        assert_eq!(point.disk_used, 60.0);
+        assert_eq!(point.disk_used_percent(), 60.0);
+    }
+}